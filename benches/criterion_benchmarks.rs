@@ -23,7 +23,7 @@ fn make_playfield_random(pf: &mut EfficientPlayField) {
 
 fn canonical_form_benchmark(c: &mut Criterion) {
     let mut test_play_fields = [EfficientPlayField::default(); 2048];
-    test_play_fields.iter_mut().for_each(|pf| make_playfield_random(pf));
+    test_play_fields.iter_mut().for_each(make_playfield_random);
 
     c.bench_function("canonical_form1", move |b| {
         b.iter(|| {
@@ -36,7 +36,7 @@ fn canonical_form_benchmark(c: &mut Criterion) {
 
 fn move_triple_benchmark(c: &mut Criterion) {
     let mut test_play_fields = [EfficientPlayField::default(); 2048];
-    test_play_fields.iter_mut().for_each(|pf| make_playfield_random(pf));
+    test_play_fields.iter_mut().for_each(make_playfield_random);
 
     c.bench_function("move_triple1", move |b| {
         b.iter(|| {
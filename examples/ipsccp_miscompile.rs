@@ -0,0 +1,45 @@
+//! Minimal reproducer of the miscompile worked around in `SetPhaseSolver::solve_game`: a recursion handing a saturating
+//! decrement of one argument on as the other argument. Built without optimizations it prints 1. With rustc 1.95.0 (LLVM
+//! 22.1.2) and `-C opt-level=2` or higher it overflows its stack instead, as the interprocedural constant propagation
+//! (the IPSCCP pass, found with `-C llvm-args=-opt-bisect-limit=<n>`) narrows `opponents_hand` to the range 2..4, which
+//! misses the smaller hands the recursion passes on:
+//!
+//! ```text
+//! cargo run --example ipsccp_miscompile             # 1
+//! cargo run --example ipsccp_miscompile --release   # stack overflow
+//! ```
+//!
+//! Returning `Result<u8, ()>` or placing without the successor vector hides the bug.
+
+fn solve(movers: u32, opponents: u32, movers_hand: u8, opponents_hand: u8) -> Result<u8, String> {
+    if movers.count_ones() as u8 + movers_hand < 3 {
+        return Ok(0);
+    } else if movers_hand == 0 && opponents_hand == 0 {
+        return Ok(1);
+    }
+
+    let mut successors = Vec::new();
+    let mut free = !(movers | opponents) & 0xff;
+    while free != 0 {
+        let target = free.trailing_zeros();
+        free &= free - 1;
+        successors.push(movers | 1 << target);
+    }
+
+    let mut value = 0;
+    for new_movers in successors {
+        match solve(opponents, new_movers, opponents_hand, movers_hand.saturating_sub(1))? {
+            0 => {
+                value = 2;
+                break;
+            }
+            1 => value = 1,
+            _ => {}
+        }
+    }
+    Ok(value)
+}
+
+fn main() {
+    println!("{}", solve(0, 0, 3, 3).unwrap());
+}
@@ -0,0 +1,207 @@
+//! A full report about a single [Position] for teaching & debugging: its canonical form, the move triples of both
+//! players, all legal moves and the evaluation of the engine & the solved database, as far as they are available.
+
+use std::{fmt::Display, io};
+
+use crate::{
+    engine::{self, SearchResult},
+    game::{
+        efficient_state::{
+            moves::{closes_mill, EfficientMove},
+            symmetry::Symmetry,
+            EfficientPlayField,
+        },
+        position::Position,
+        PlayerColor,
+    },
+    solver::{database::Database, PositionValue, MIN_STONES},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalysedMove {
+    pub legal_move: EfficientMove,
+    pub closes_mill: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub position: Position,
+    pub canonical_form: EfficientPlayField,
+    /// The symmetry mapping the play field onto its canonical form
+    pub symmetry: Symmetry,
+    /// [EfficientPlayField::get_move_triple] of white & black
+    pub move_triples: [(u32, u32, u32); 2],
+    pub legal_moves: Vec<AnalysedMove>,
+    /// Some if the player on turn already lost
+    pub winner: Option<PlayerColor>,
+    /// None if no search depth was given
+    pub engine: Option<SearchResult>,
+    /// The value for the player on turn. None if no database was given, the set phase isn't over yet or the class of
+    /// the position isn't solved.
+    pub database_value: Option<PositionValue>,
+}
+
+/// Analyses the position, searching `engine_depth` plies deep & looking it up in the database if given. The position is
+/// expected to pass [Position::check_stone_counts], only the database lookup can fail.
+pub fn analyse(position: &Position, engine_depth: Option<u8>, database: Option<&Database>) -> io::Result<Analysis> {
+    let (canonical_form, symmetry) = position.board.get_canonical_form_with_symmetry();
+
+    let mut board = position.board;
+    let move_triples = [
+        board.get_move_triple(PlayerColor::White),
+        board.get_move_triple(PlayerColor::Black),
+    ];
+
+    let (movers_stones, opponents_stones) = position.board.get_masks_of(position.side_to_move);
+    let legal_moves = position
+        .legal_moves()
+        .into_iter()
+        .map(|legal_move| {
+            let stones_after = match legal_move.start {
+                Some(start) => movers_stones ^ (1 << start) ^ (1 << legal_move.target),
+                None => movers_stones | (1 << legal_move.target),
+            };
+            AnalysedMove {
+                legal_move,
+                closes_mill: closes_mill(stones_after, legal_move.target as usize),
+            }
+        })
+        .collect();
+
+    let winner = position.winner();
+    let engine = match engine_depth {
+        Some(depth) if winner.is_none() => Some(engine::search(position, depth)),
+        _ => None,
+    };
+
+    // The database only knows the move phase
+    let in_database = position.stones_in_hand == (0, 0)
+        && MIN_STONES as u32 <= movers_stones.count_ones()
+        && MIN_STONES as u32 <= opponents_stones.count_ones();
+    let database_value = match database {
+        Some(database) if in_database => database.lookup(movers_stones, opponents_stones)?,
+        _ => None,
+    };
+
+    Ok(Analysis {
+        position: *position,
+        canonical_form,
+        symmetry,
+        move_triples,
+        legal_moves,
+        winner,
+        engine,
+        database_value,
+    })
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let position = &self.position;
+
+        writeln!(f, "{}", position.board)?;
+        writeln!(f, "Code:           {}", position.board.to_string_representation())?;
+        writeln!(f, "To move:        {:?}", position.side_to_move)?;
+        writeln!(
+            f,
+            "In hand:        white {}, black {}",
+            position.stones_in_hand.0, position.stones_in_hand.1
+        )?;
+        writeln!(f, "Canonical form: {}", self.canonical_form.to_string_representation())?;
+        writeln!(f, "Symmetry:       {}", self.symmetry)?;
+
+        for (color, (moves, moves_to_mill, stones_to_take)) in
+            [PlayerColor::White, PlayerColor::Black].iter().zip(self.move_triples)
+        {
+            writeln!(
+                f,
+                "{:<16}{moves} moves, {moves_to_mill} into a mill, {stones_to_take} stones to take",
+                format!("{color:?}:")
+            )?;
+        }
+
+        if let Some(winner) = self.winner {
+            writeln!(f, "Game over:      {winner:?} won")?;
+        }
+
+        writeln!(f, "Legal moves:    {}", self.legal_moves.len())?;
+        for analysed_move in &self.legal_moves {
+            let mut flags = Vec::new();
+            if analysed_move.closes_mill {
+                flags.push("mill");
+            }
+            if analysed_move.legal_move.take.is_some() {
+                flags.push("capture");
+            }
+
+            if flags.is_empty() {
+                writeln!(f, "  {}", analysed_move.legal_move)?;
+            } else {
+                writeln!(f, "  {:<12}{}", analysed_move.legal_move.to_string(), flags.join(", "))?;
+            }
+        }
+
+        if let Some(result) = &self.engine {
+            let best_move = result.best_move.map(|best_move| best_move.to_string());
+            writeln!(
+                f,
+                "Engine:         {} with score {} ({} nodes)",
+                best_move.as_deref().unwrap_or("-"),
+                result.score,
+                result.nodes
+            )?;
+        }
+        if let Some(value) = self.database_value {
+            writeln!(f, "Database:       {value:?} for {:?}", position.side_to_move)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_mills_and_captures() {
+        let position = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBEEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (7, 7),
+        };
+        let analysis = analyse(&position, Some(1), None).unwrap();
+
+        // A7 closes the mill & takes one of the two black stones
+        let closing: Vec<_> = analysis
+            .legal_moves
+            .iter()
+            .filter(|analysed| analysed.closes_mill)
+            .collect();
+        assert_eq!(closing.len(), 2);
+        assert!(closing.iter().all(|analysed| analysed.legal_move.target == 7));
+        assert_eq!(analysis.legal_moves.len(), 21);
+
+        // The move triple counts slides of the move phase
+        assert_eq!(analysis.move_triples[0], (2, 0, 2));
+        assert_eq!(analysis.engine.unwrap().best_move.unwrap().target, 7);
+        assert_eq!(analysis.database_value, None);
+
+        let report = analysis.to_string();
+        assert!(report.contains("a7xd6"));
+        assert!(report.contains("mill, capture"));
+    }
+
+    #[test]
+    fn canonical_form_with_symmetry() {
+        let position = Position {
+            board: EfficientPlayField::from_coded("EEEEEEEEEEEEEEEEEEEEEEEW"),
+            side_to_move: PlayerColor::Black,
+            stones_in_hand: (8, 9),
+        };
+        let analysis = analyse(&position, None, None).unwrap();
+
+        assert_eq!(analysis.symmetry.apply(&position.board), analysis.canonical_form);
+        assert_eq!(analysis.canonical_form, position.board.clone().get_canonical_form());
+        assert!(analysis.engine.is_none());
+    }
+}
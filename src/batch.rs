@@ -0,0 +1,174 @@
+//! Line based batch processing of board codes, one code per line, as used by the assignments 4 & 5:
+//!
+//! - [canonical_forms] writes for every board the number of the first line with the same canonical form, counting from
+//!   1. Boards equal under the symmetries of the play field get the same number.
+//! - [move_triples] writes for every board the amount of moves, of moves closing a mill & of stones the opponent could
+//!   take after closing one.
+//!
+//! Malformed lines are reported with their line number & leave an empty line in the output, so the output lines stay
+//! aligned with the input lines.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{self, BufRead, Write},
+};
+
+use crate::game::{
+    efficient_state::{ordering::FieldOrder, EfficientPlayField},
+    PlayerColor,
+};
+
+/// A line which isn't a board code, `line` counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/// Calls the function with the number & board of each line & writes its result, or an empty line for malformed boards
+fn process_lines(
+    input: impl BufRead,
+    mut output: impl Write,
+    order: &FieldOrder,
+    mut process: impl FnMut(usize, EfficientPlayField) -> String,
+) -> io::Result<Vec<LineError>> {
+    let mut errors = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        // Tolerates Windows line endings & trailing spaces
+        let code = line.trim_end();
+
+        match order.import(code) {
+            Ok(board) => writeln!(output, "{}", process(index + 1, board))?,
+            Err(error) => {
+                errors.push(LineError {
+                    line: index + 1,
+                    message: format!("'{code}': {error}"),
+                });
+                writeln!(output)?;
+            }
+        }
+    }
+
+    output.flush()?;
+    Ok(errors)
+}
+
+pub fn canonical_forms(input: impl BufRead, output: impl Write, order: &FieldOrder) -> io::Result<Vec<LineError>> {
+    let mut first_lines: HashMap<EfficientPlayField, usize> = HashMap::new();
+
+    process_lines(input, output, order, |line, mut board| {
+        first_lines
+            .entry(board.get_canonical_form())
+            .or_insert(line)
+            .to_string()
+    })
+}
+
+/// The triples are counted for the stones of the color, like in the move phase
+pub fn move_triples(
+    input: impl BufRead,
+    output: impl Write,
+    order: &FieldOrder,
+    color: PlayerColor,
+) -> io::Result<Vec<LineError>> {
+    process_lines(input, output, order, |_, mut board| {
+        let (moves, moves_into_mill, stones_to_take) = board.get_move_triple(color);
+        format!("{moves} {moves_into_mill} {stones_to_take}")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example input of the assignments, © FMI Uni Stuttgart
+    const INPUT_FELDER: &str = "\
+BBEEEEEBEEEEWEWWBWWEEEBE
+BBEEEWEBBEWEBEEEEEEEEEEE
+BEEEWWBEWEWEEEEWEEEEEBBB
+BWEWEEWEBEBBEBWEWEEBEWWB
+EBBBEEEWEEBEWEBEEEEEEEEE
+EBEEWBWWEBBEBEWBEWEWBEWE
+EEBEBWWEWEWWEEEEEEEBBBEE
+";
+
+    fn run(
+        process: impl FnOnce(&[u8], &mut Vec<u8>) -> io::Result<Vec<LineError>>,
+        input: &str,
+    ) -> (String, Vec<LineError>) {
+        let mut output = Vec::new();
+        let errors = process(input.as_bytes(), &mut output).unwrap();
+        (String::from_utf8(output).unwrap(), errors)
+    }
+
+    #[test]
+    fn assignment4() {
+        let (output, errors) = run(
+            |input, output| canonical_forms(input, output, &FieldOrder::RingClockwise),
+            INPUT_FELDER,
+        );
+        assert!(errors.is_empty());
+        // Several example boards are rotations or mirrors of earlier ones
+        assert_eq!(output, "1\n2\n1\n4\n2\n4\n1\n");
+
+        // The first board rotated by 90° & with swapped inner & outer ring, both are equivalent to it
+        let first = INPUT_FELDER.lines().next().unwrap();
+        let rotated = format!("{}{}", &first[2..8], &first[..2])
+            + &format!("{}{}", &first[10..16], &first[8..10])
+            + &format!("{}{}", &first[18..24], &first[16..18]);
+        let swapped = format!("{}{}{}", &first[16..24], &first[8..16], &first[..8]);
+
+        let (output, _) = run(
+            |input, output| canonical_forms(input, output, &FieldOrder::RingClockwise),
+            &format!("{INPUT_FELDER}{rotated}\n{swapped}\n"),
+        );
+        assert!(output.ends_with("1\n1\n1\n"));
+    }
+
+    #[test]
+    fn assignment5() {
+        let (output, errors) = run(
+            |input, output| move_triples(input, output, &FieldOrder::RingClockwise, PlayerColor::White),
+            INPUT_FELDER,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(output.lines().count(), 7);
+
+        // A single white stone on D7 can move to G7, A7 & D6
+        let (output, _) = run(
+            |input, output| move_triples(input, output, &FieldOrder::RingClockwise, PlayerColor::White),
+            "WEEEEEEEEEEEEEEEEEEEEEEE\n",
+        );
+        assert_eq!(output, "3 0 0\n");
+        let (output, _) = run(
+            |input, output| move_triples(input, output, &FieldOrder::RingClockwise, PlayerColor::Black),
+            "WEEEEEEEEEEEEEEEEEEEEEEE\n",
+        );
+        // Black can't move, but could take the white stone after closing a mill
+        assert_eq!(output, "0 0 1\n");
+    }
+
+    #[test]
+    fn errors_are_reported_per_line() {
+        let (output, errors) = run(
+            |input, output| canonical_forms(input, output, &FieldOrder::RingClockwise),
+            "WEEEEEEEEEEEEEEEEEEEEEEE\r\nWEEE\nWEEEEEEEEEEEEEEEEEEEEEEX\nEEEEEEEEEEEEEEEEWEEEEEEE\n",
+        );
+        assert_eq!(output, "1\n\n\n1\n");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(
+            errors[1].to_string(),
+            "Line 3: 'WEEEEEEEEEEEEEEEEEEEEEEX': Invalid character 'X' at position 24, expected E, W or B"
+        );
+    }
+}
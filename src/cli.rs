@@ -0,0 +1,805 @@
+//! The subcommands of the binary & the interactive game, which is started when no subcommand is given.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+};
+
+use muehle::{
+    analysis,
+    batch::{self, LineError},
+    engine::{book::OpeningBook, protocol, EnginePlayer},
+    game::{
+        efficient_state::ordering::FieldOrder,
+        events::EventLog,
+        notation::Move,
+        painting::*,
+        position::{ExtendedPosition, Position},
+        record::{csv, GameRecord},
+        svg::Highlights,
+        PlayerColor,
+    },
+    network::{spectators::Spectators, NetworkGame, DEFAULT_PORT},
+    solver::{
+        database::Database,
+        driver::{self, SolverConfig},
+        set_phase, verify,
+    },
+};
+
+use yansi::Paint;
+
+use crate::{
+    config::{Config, Theme},
+    coordination::GameCoordinator,
+    remote,
+    replay::Replay,
+};
+
+/// Usage: `muehle [--log <file>] [--spectators <port>] [--time <control>] [--best-of <n>] [--book <file>]
+/// [--config <file>] [--set <key>=<value>]...`
+///
+/// The interactive game for two players on the empty play field.
+pub fn play(args: &[String]) {
+    if let Some(unknown) = unknown_game_flag(args, &GAME_FLAGS) {
+        print_usage(unknown);
+    }
+    let config = config(args);
+    let book = config.book.clone();
+    // Without names there's no game to play
+    if let Ok(coordinator) = GameCoordinator::setup(config, io::stdin().lock(), io::stdout()) {
+        start_logged(coordinator, book, args);
+    }
+}
+
+/// The flags of the interactive games, which all take a value
+const GAME_FLAGS: [&str; 7] = [
+    "--log",
+    "--spectators",
+    "--time",
+    "--best-of",
+    "--book",
+    "--config",
+    "--set",
+];
+
+/// The first flag which isn't one of `flags`, the values following the flags are skipped
+fn unknown_game_flag<'a>(args: &'a [String], flags: &[&str]) -> Option<&'a String> {
+    args.iter().step_by(2).find(|flag| !flags.contains(&flag.as_str()))
+}
+
+/// The config file given by `--config`, [Config::path] otherwise, with the overrides of `--set <key>=<value>`, `--time
+/// <control>`, `--best-of <n>` & `--book <file>` anywhere in the arguments applied in their order. Plain themes turn
+/// the colors off.
+fn config(args: &[String]) -> Config {
+    let path = match args.iter().position(|arg| arg == "--config") {
+        Some(index) => Some(parse_value::<PathBuf>("--config", args.get(index + 1))),
+        None => Config::path(),
+    };
+    let mut config = match path.map(|path| (Config::load(&path), path)) {
+        None => Config::default(),
+        Some((Ok(config), _)) => config,
+        Some((Err(error), path)) => exit_with_error(&format!("Reading '{}' failed: {error}.", path.display())),
+    };
+
+    for (index, flag) in args.iter().enumerate() {
+        let (key, value) = match flag.as_str() {
+            "--set" => {
+                let setting = parse_value::<String>("--set", args.get(index + 1));
+                match setting.split_once('=') {
+                    Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+                    None => exit_with_error(&format!("Expected <key>=<value> for '--set', not '{setting}'.")),
+                }
+            }
+            "--time" => ("time".to_string(), parse_value::<String>("--time", args.get(index + 1))),
+            "--best-of" => (
+                "best_of".to_string(),
+                parse_value::<String>("--best-of", args.get(index + 1)),
+            ),
+            "--book" => ("book".to_string(), parse_value::<String>("--book", args.get(index + 1))),
+            _ => continue,
+        };
+        if let Err(error) = config.set(&key, &value) {
+            exit_with_error(&format!("{error}."));
+        }
+    }
+
+    if config.theme == Theme::Plain {
+        Paint::disable();
+    }
+    config
+}
+
+/// Usage: `muehle resume <file> [--log <file>] [--spectators <port>] [--best-of <n>] [--book <file>]
+/// [--config <file>] [--set <key>=<value>]...`
+///
+/// Continues a game saved by entering `save <file>` during a game, with the clocks of the game if it had some. The
+/// names come from the saved game, the config still picks engines by the player numbers: player 1 plays white.
+pub fn resume(args: &[String]) {
+    let Some(path) = args.first() else {
+        exit_with_error("The saved game to resume is missing, e.g. 'muehle resume game.txt'.");
+    };
+    // The clocks continue from the saved game
+    let flags: Vec<&str> = GAME_FLAGS.into_iter().filter(|flag| *flag != "--time").collect();
+    if let Some(unknown) = unknown_game_flag(&args[1..], &flags) {
+        exit_with_error(&format!("Unknown argument '{unknown}' for resume."));
+    }
+
+    let config = config(args);
+    let book = config.book.clone();
+    match GameCoordinator::resume(Path::new(path), config, io::stdin().lock(), io::stdout()) {
+        Ok(coordinator) => start_logged(coordinator, book, args),
+        Err(message) => exit_with_error(&message),
+    }
+}
+
+/// Starts the game, with the engines playing from the book if there is one & the events logged to the file given by
+/// `--log` & streamed to the spectators of `--spectators <port>` anywhere in the arguments
+fn start_logged<I: BufRead, O: Write>(mut coordinator: GameCoordinator<I, O>, book: Option<PathBuf>, args: &[String]) {
+    if let Some(path) = book {
+        coordinator.set_book(load_book(&path));
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "--log") {
+        let path = parse_value::<PathBuf>("--log", args.get(index + 1));
+        match File::create(&path) {
+            Ok(file) => coordinator.observe(EventLog::new(file)),
+            Err(error) => exit_with_error(&format!("Creating '{}' failed: {error}", path.display())),
+        }
+    }
+    if let Some(index) = args.iter().position(|arg| arg == "--spectators") {
+        coordinator.observe(spectators(parse_value("--spectators", args.get(index + 1))));
+    }
+    coordinator.start_game();
+}
+
+/// Loads the opening book given by `--book` or the config file
+fn load_book(path: &Path) -> OpeningBook {
+    match OpeningBook::load(path) {
+        Ok(book) => book,
+        Err(error) => exit_with_error(&format!("Loading the book '{}' failed: {error}", path.display())),
+    }
+}
+
+/// Accepts spectators on the port, see [muehle::network::spectators]
+fn spectators(port: u16) -> Spectators {
+    match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => {
+            println!("> Spectators can watch on port {}.", HIGHLIGHT.paint(port));
+            Spectators::listen(listener)
+        }
+        Err(error) => exit_with_error(&format!("Listening for spectators on port {port} failed: {error}")),
+    }
+}
+
+/// Usage: `muehle replay <file>`
+///
+/// Steps through a saved game move by move.
+pub fn replay(args: &[String]) {
+    let Some(path) = args.first() else {
+        exit_with_error("The game to replay is missing, e.g. 'muehle replay game.txt'.");
+    };
+
+    match Replay::load(Path::new(path)) {
+        Ok(mut replay) => replay.run(),
+        Err(message) => exit_with_error(&message),
+    }
+}
+
+/// Usage: `muehle host [--port <n>] [--name <name>] [--color w|b] [--spectators <port>]`
+///
+/// Waits for a guest to join a game over the network. The host checks the moves of both sides & plays the given color,
+/// otherwise the guest chooses & the host plays white if it doesn't.
+pub fn host(args: &[String]) {
+    let mut port = DEFAULT_PORT;
+    let mut name = String::from("Host");
+    let mut color = None;
+    let mut spectator_port = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--port" => port = parse_value(flag, args.next()),
+            "--name" => name = parse_value(flag, args.next()),
+            "--color" => color = Some(parse_color(flag, args.next())),
+            "--spectators" => spectator_port = Some(parse_value(flag, args.next())),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for host.")),
+        }
+    }
+    let spectators = spectator_port.map(spectators);
+
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(error) => exit_with_error(&format!("Listening on port {port} failed: {error}")),
+    };
+    println!("> Waiting for a guest on port {}.", HIGHLIGHT.paint(port));
+    match NetworkGame::host(&listener, &name, color) {
+        Ok(game) => remote::run(game, spectators),
+        Err(error) => exit_with_error(&error.to_string()),
+    }
+}
+
+/// Usage: `muehle join <host>[:<port>] [--name <name>] [--color w|b] [--spectators <port>]`
+///
+/// Joins a game hosted by `muehle host`. The color is a request, which the host grants unless it chose its own color.
+pub fn join(args: &[String]) {
+    let Some(address) = args.first() else {
+        exit_with_error("The host to join is missing, e.g. 'muehle join 192.168.0.2'.");
+    };
+    let address = if address.contains(':') {
+        address.clone()
+    } else {
+        format!("{address}:{DEFAULT_PORT}")
+    };
+    let mut name = String::from("Guest");
+    let mut color = None;
+    let mut spectator_port = None;
+
+    let mut args = args[1..].iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--name" => name = parse_value(flag, args.next()),
+            "--color" => color = Some(parse_color(flag, args.next())),
+            "--spectators" => spectator_port = Some(parse_value(flag, args.next())),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for join.")),
+        }
+    }
+    let spectators = spectator_port.map(spectators);
+
+    match NetworkGame::join(address.as_str(), &name, color) {
+        Ok(game) => remote::run(game, spectators),
+        Err(error) => exit_with_error(&format!("Joining '{address}' failed: {error}")),
+    }
+}
+
+/// Usage: `muehle serve [--port <n>] [--book <file>]`
+///
+/// Answers the JSON API of [muehle::http] on localhost, for web front-ends. The hints are played from the book if
+/// it knows the position.
+#[cfg(feature = "http")]
+pub fn serve(args: &[String]) {
+    use muehle::http::{Server, DEFAULT_PORT};
+
+    let mut port = DEFAULT_PORT;
+    let mut engine = EnginePlayer::default();
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--port" => port = parse_value(flag, args.next()),
+            "--book" => engine.book = Some(load_book(&parse_value::<PathBuf>(flag, args.next()))),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for serve.")),
+        }
+    }
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(error) => exit_with_error(&format!("Listening on port {port} failed: {error}")),
+    };
+    println!("> Serving on {}.", EMP.paint(format!("http://localhost:{port}")));
+    if let Err(error) = Server::new(engine).serve(&listener) {
+        exit_with_error(&format!("Serving failed: {error}"));
+    }
+}
+
+#[cfg(not(feature = "http"))]
+pub fn serve(_: &[String]) {
+    exit_with_error("The HTTP server isn't built in, build with 'cargo build --features http'.");
+}
+
+/// Usage: `muehle start "<position>" [--log <file>] [--spectators <port>] [--time <control>] [--best-of <n>]
+/// [--book <file>] [--config <file>] [--set <key>=<value>]...`
+///
+/// Starts an interactive game from a position in the extended position notation, like
+/// `muehle start "WWEEEEEEBBEEEEEEEEEEEEEE w 7 7"`.
+pub fn start(args: &[String]) {
+    let Some(text) = args.first() else {
+        exit_with_error(
+            "The position to start from is missing, e.g. 'muehle start \"WWEEEEEEBBEEEEEEEEEEEEEE w 7 7\"'.",
+        );
+    };
+    let start = match text.parse::<ExtendedPosition>() {
+        Ok(start) => start,
+        Err(error) => exit_with_error(&format!("Malformed position '{text}': {error}.")),
+    };
+    if let Some(unknown) = unknown_game_flag(&args[1..], &GAME_FLAGS) {
+        exit_with_error(&format!("Unknown argument '{unknown}' for start."));
+    }
+
+    let config = config(args);
+    let book = config.book.clone();
+    match GameCoordinator::setup_from(start, config, io::stdin().lock(), io::stdout()) {
+        Ok(coordinator) => start_logged(coordinator, book, args),
+        Err(message) => exit_with_error(&message),
+    }
+}
+
+/// Usage: `muehle solve [--threads <n>] [--database <dir>] [--max-stones <n>]`
+///
+/// Runs the retrograde analysis of the move phase. An interrupted run continues from its last checkpoint.
+pub fn solve(args: &[String]) {
+    let mut config = SolverConfig::default();
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--threads" => config.threads = parse_value(flag, args.next()),
+            "--database" => config.database_directory = parse_value::<PathBuf>(flag, args.next()),
+            "--max-stones" => config.max_stones = parse_value(flag, args.next()),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for solve.")),
+        }
+    }
+    if !(3..=12).contains(&config.max_stones) {
+        exit_with_error("The maximum amount of stones must be between 3 and 12.");
+    }
+
+    println!(
+        "> Solving up to {} stones per player with {} threads into '{}'.",
+        HIGHLIGHT.paint(config.max_stones),
+        HIGHLIGHT.paint(config.threads),
+        EMP.paint(config.database_directory.display())
+    );
+
+    if let Err(error) = driver::solve_all(&config, |progress| eprintln!("> {progress}")) {
+        exit_with_error(&format!("Solving failed: {error}"));
+    }
+    println!("> {}", EMP.paint("All classes are solved."));
+}
+
+/// Usage: `muehle verify [--threads <n>] [--database <dir>] [--max-stones <n>] [--report <n>]`
+///
+/// Re-checks every position of a solved database against its successors. Exits with 1 if it's inconsistent.
+pub fn verify(args: &[String]) {
+    let mut config = SolverConfig::default();
+    let mut max_reported = 10;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--threads" => config.threads = parse_value(flag, args.next()),
+            "--database" => config.database_directory = parse_value::<PathBuf>(flag, args.next()),
+            "--max-stones" => config.max_stones = parse_value(flag, args.next()),
+            "--report" => max_reported = parse_value(flag, args.next()),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for verify.")),
+        }
+    }
+
+    let report = match verify::verify_database(&config, max_reported) {
+        Ok(report) => report,
+        Err(error) => exit_with_error(&format!("Verifying failed: {error}")),
+    };
+
+    for (_, message) in &report.class_errors {
+        println!("> {}", ERROR.paint(message));
+    }
+    for inconsistency in &report.first_inconsistencies {
+        println!("> {inconsistency}");
+    }
+    println!(
+        "> Checked {} positions, {} of them are inconsistent.",
+        HIGHLIGHT.paint(report.positions_checked),
+        HIGHLIGHT.paint(report.inconsistent_positions)
+    );
+
+    if report.is_consistent() {
+        println!("> {}", EMP.paint("The database is consistent."));
+    } else {
+        process::exit(1);
+    }
+}
+
+/// Usage: `muehle solve-set [--database <dir>] [--stones <n>]`
+///
+/// Solves the set phase on top of a solved move phase database & prints the value of the game. Only small games fit
+/// into memory, see [set_phase::MAX_STONES_PER_PLAYER].
+pub fn solve_set(args: &[String]) {
+    let mut directory = PathBuf::from("database");
+    let mut stones = 3;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--database" => directory = parse_value(flag, args.next()),
+            "--stones" => stones = parse_value(flag, args.next()),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for solve-set.")),
+        }
+    }
+    if !(3..=set_phase::MAX_STONES_PER_PLAYER).contains(&stones) {
+        exit_with_error(&format!(
+            "The amount of stones must be between 3 and {}.",
+            set_phase::MAX_STONES_PER_PLAYER
+        ));
+    }
+
+    println!(
+        "> Solving the set phase with {} stones per player using '{}'.",
+        HIGHLIGHT.paint(stones),
+        EMP.paint(directory.display())
+    );
+    match set_phase::solve_set_phase(&directory, stones) {
+        Ok(value) => println!(
+            "> The game is a {} for white.",
+            EMP.paint(format!("{value:?}").to_lowercase())
+        ),
+        Err(error) => exit_with_error(&format!("Solving the set phase failed: {error}")),
+    }
+}
+
+/// Usage: `muehle book --output <file> [--games <n>] [--depth <n>] [--exploration <n>] [--seed <n>]`
+///
+/// Builds an opening book for the set phase by self-play. An existing book at the output path is extended.
+pub fn book(args: &[String]) {
+    let mut output = None;
+    let mut games = 100;
+    let mut depth = 2;
+    let mut exploration = 4;
+    let mut seed = 0x5eed;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--output" => output = Some(parse_value::<PathBuf>(flag, args.next())),
+            "--games" => games = parse_value(flag, args.next()),
+            "--depth" => depth = parse_value(flag, args.next()),
+            "--exploration" => exploration = parse_value(flag, args.next()),
+            "--seed" => seed = parse_value(flag, args.next()),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for book.")),
+        }
+    }
+    let Some(output) = output else {
+        exit_with_error("The book needs an output file: --output <file>");
+    };
+
+    let mut book = if output.exists() {
+        match OpeningBook::load(&output) {
+            Ok(book) => book,
+            Err(error) => exit_with_error(&format!("Loading '{}' failed: {error}", output.display())),
+        }
+    } else {
+        OpeningBook::default()
+    };
+    let known_positions = book.len();
+
+    println!(
+        "> Playing {} games with search depth {}.",
+        HIGHLIGHT.paint(games),
+        HIGHLIGHT.paint(depth)
+    );
+    book.build_from_self_play(games, depth, exploration, seed);
+
+    if let Err(error) = book.store(&output) {
+        exit_with_error(&format!("Storing '{}' failed: {error}", output.display()));
+    }
+    println!(
+        "> {} The book knows {} positions ({} new).",
+        EMP.paint("Done."),
+        HIGHLIGHT.paint(book.len()),
+        HIGHLIGHT.paint(book.len() - known_positions)
+    );
+}
+
+/// Usage: `muehle analyse <code> [--to-move w|b] [--white-hand <n>] [--black-hand <n>] [--depth <n>]
+/// [--database <dir>] [--order <order>]`
+///
+/// Prints a report about a single position given as 24 character code or as whole position string like
+/// `"WWEEEEEEBBEEEEEEEEEEEEEE b 7 6"`, which replaces the flags. Without stones in hand the move phase is analysed,
+/// the database is only consulted if one is given. `--order` selects the order of the fields in the board code.
+pub fn analyse(args: &[String]) {
+    let Some(code) = args.first() else {
+        exit_with_error("The position to analyse is missing, e.g. 'muehle analyse WWEEEEEEBBEEEEEEEEEEEEEE'.");
+    };
+    let order = board_order(args);
+    // Either a board code with the rest given by flags, or a whole position string
+    let mut position = if code.contains(char::is_whitespace) {
+        match code.parse::<ExtendedPosition>() {
+            // The stones counted by the parser don't depend on the order
+            Ok(extended) => Position {
+                board: order.import(code.split_whitespace().next().unwrap()).unwrap(),
+                ..extended.position
+            },
+            Err(error) => exit_with_error(&format!("Malformed position '{code}': {error}.")),
+        }
+    } else {
+        match order.import(code) {
+            Ok(board) => Position {
+                board,
+                side_to_move: PlayerColor::White,
+                stones_in_hand: (0, 0),
+            },
+            Err(error) => exit_with_error(&format!("Malformed position '{code}': {error}.")),
+        }
+    };
+    let mut depth = 4;
+    let mut database = None;
+
+    let mut args = args[1..].iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--to-move" => position.side_to_move = parse_color(flag, args.next()),
+            "--white-hand" => position.stones_in_hand.0 = parse_value(flag, args.next()),
+            "--black-hand" => position.stones_in_hand.1 = parse_value(flag, args.next()),
+            "--depth" => depth = parse_value(flag, args.next()),
+            "--database" => database = Some(parse_value::<PathBuf>(flag, args.next())),
+            // Read by board_order already
+            "--order" => _ = args.next(),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for analyse.")),
+        }
+    }
+    if let Err(message) = position.check_stone_counts() {
+        exit_with_error(&format!("Impossible position: {message}."));
+    }
+
+    let database = match database {
+        Some(directory) if !directory.is_dir() => {
+            exit_with_error(&format!("The database '{}' doesn't exist.", directory.display()))
+        }
+        Some(directory) => match Database::open(&directory) {
+            Ok(database) => Some(database),
+            Err(error) => exit_with_error(&format!("Opening the database failed: {error}")),
+        },
+        None => None,
+    };
+
+    match analysis::analyse(&position, Some(depth), database.as_ref()) {
+        Ok(analysis) => print!("{analysis}"),
+        Err(error) => exit_with_error(&format!("Reading the database failed: {error}")),
+    }
+}
+
+/// Usage: `muehle svg <code> [--output <file>] [--last-move <move>] [--mills] [--suggest <move>]... [--order <order>]`
+///
+/// Writes an SVG image of the board given as 24 character code, `board.svg` by default. Closed mills are only marked
+/// with `--mills`, `--suggest` can be repeated.
+pub fn svg(args: &[String]) {
+    let Some(code) = args.first() else {
+        exit_with_error("The board to draw is missing, e.g. 'muehle svg WWEEEEEEBBEEEEEEEEEEEEEE'.");
+    };
+    let board = match board_order(args).import(code) {
+        Ok(board) => board,
+        Err(error) => exit_with_error(&format!("Malformed board '{code}': {error}.")),
+    };
+
+    let mut output = PathBuf::from("board.svg");
+    let mut highlights = Highlights::default();
+
+    let mut args = args[1..].iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--output" => output = parse_value(flag, args.next()),
+            "--last-move" => highlights.last_move = Some(parse_move(flag, args.next())),
+            "--mills" => highlights.mills = Highlights::closed_mills(&board),
+            "--suggest" => highlights.suggested_moves.push(parse_move(flag, args.next())),
+            // Read by board_order already
+            "--order" => _ = args.next(),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for svg.")),
+        }
+    }
+
+    match fs::write(&output, board.to_svg(&highlights)) {
+        Ok(_) => println!("> Wrote {}.", EMP.paint(output.display())),
+        Err(error) => exit_with_error(&format!("Writing '{}' failed: {error}", output.display())),
+    }
+}
+
+/// Usage: `muehle canon [<input>] [<output>] [--order <order>]`
+///
+/// Writes for every board code of the input the number of the first line with an equivalent board, see
+/// [batch::canonical_forms]. Input & output are stdin & stdout if they are missing or `-`.
+pub fn canon(args: &[String]) {
+    let (input, output, order, _) = batch_arguments("canon", args, false);
+    report_line_errors(batch::canonical_forms(input, output, &order));
+}
+
+/// Usage: `muehle triple [<input>] [<output>] [--color w|b] [--order <order>]`
+///
+/// Writes for every board code of the input the amount of moves, of moves closing a mill & of stones to take after
+/// one, see [batch::move_triples]. The moves of white are counted by default.
+pub fn triple(args: &[String]) {
+    let (input, output, order, color) = batch_arguments("triple", args, true);
+    report_line_errors(batch::move_triples(input, output, &order, color));
+}
+
+/// The input & output files or stdin & stdout, the board order & the color of the batch subcommands
+fn batch_arguments(
+    subcommand: &str,
+    args: &[String],
+    with_color: bool,
+) -> (Box<dyn BufRead>, Box<dyn Write>, FieldOrder, PlayerColor) {
+    let order = board_order(args);
+    let mut color = PlayerColor::White;
+    let mut paths = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--color" if with_color => color = parse_color(arg, args.next()),
+            // Read by board_order already
+            "--order" => _ = args.next(),
+            flag if flag.starts_with("--") => exit_with_error(&format!("Unknown argument '{flag}' for {subcommand}.")),
+            path if paths.len() < 2 => paths.push(path),
+            path => exit_with_error(&format!("Unexpected argument '{path}' for {subcommand}.")),
+        }
+    }
+
+    let input: Box<dyn BufRead> = match paths.first() {
+        None | Some(&"-") => Box::new(io::stdin().lock()),
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(error) => exit_with_error(&format!("Opening '{path}' failed: {error}")),
+        },
+    };
+    let output: Box<dyn Write> = match paths.get(1) {
+        None | Some(&"-") => Box::new(io::stdout().lock()),
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => exit_with_error(&format!("Creating '{path}' failed: {error}")),
+        },
+    };
+    (input, output, order, color)
+}
+
+/// Prints the malformed lines to stderr, exits with 1 if there are any
+fn report_line_errors(result: io::Result<Vec<LineError>>) {
+    let errors = match result {
+        Ok(errors) => errors,
+        Err(error) => exit_with_error(&format!("Processing the boards failed: {error}")),
+    };
+    for error in &errors {
+        eprintln!("> {}", ERROR.paint(error));
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Usage: `muehle csv <file>... [--output <file>]`
+///
+/// Exports saved games as CSV with one row per ply, see [csv]. The games are identified by their paths, the rows are
+/// written to stdout without `--output`.
+pub fn export_csv(args: &[String]) {
+    let mut paths = Vec::new();
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(parse_value::<PathBuf>(arg, args.next())),
+            flag if flag.starts_with("--") => exit_with_error(&format!("Unknown argument '{flag}' for csv.")),
+            path => paths.push(path),
+        }
+    }
+    if paths.is_empty() {
+        exit_with_error("The games to export are missing, e.g. 'muehle csv game.txt'.");
+    }
+
+    let records: Vec<GameRecord> = paths
+        .iter()
+        .map(|path| match GameRecord::load(Path::new(path)) {
+            Ok(record) => record,
+            Err(error) => exit_with_error(&format!("Loading '{path}' failed: {error}")),
+        })
+        .collect();
+    let games = paths.iter().copied().zip(&records);
+
+    let result = match &output {
+        Some(path) => File::create(path).and_then(|file| csv::write_games(games, BufWriter::new(file))),
+        None => csv::write_games(games, io::stdout().lock()),
+    };
+    match (result, output) {
+        (Err(error), _) => exit_with_error(&format!("Exporting failed: {error}")),
+        (Ok(_), Some(path)) => println!(
+            "> Wrote {} plies of {} games to {}.",
+            HIGHLIGHT.paint(records.iter().map(|record| record.moves.len()).sum::<usize>()),
+            HIGHLIGHT.paint(records.len()),
+            EMP.paint(path.display())
+        ),
+        (Ok(_), None) => {}
+    }
+}
+
+/// Usage: `muehle engine [--book <file>]`
+///
+/// Speaks the line based engine protocol of [protocol] on stdin & stdout, for graphical front-ends. Positions known
+/// by the book are answered with its move.
+pub fn engine(args: &[String]) {
+    let mut engine = EnginePlayer::default();
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--book" => engine.book = Some(load_book(&parse_value::<PathBuf>(flag, args.next()))),
+            _ => exit_with_error(&format!("Unknown argument '{flag}' for engine.")),
+        }
+    }
+    if let Err(error) = protocol::run(&engine, io::stdin().lock(), io::stdout()) {
+        exit_with_error(&format!("Engine protocol failed: {error}"));
+    }
+}
+
+/// Called for unknown subcommands
+pub fn print_usage(subcommand: &str) {
+    eprintln!("> Unknown subcommand '{}'. Usage:", ERROR.paint(subcommand));
+    eprintln!(">   muehle                  Starts an interactive game for two players");
+    eprintln!(">                           The interactive games take --log <file> to log the events of the game");
+    eprintln!(">                           & new ones --time <total>+<increment>[/<per move>] in seconds for clocks");
+    eprintln!(
+        ">                           --best-of <n> plays a match, rematches are offered after each game otherwise"
+    );
+    eprintln!(">                           --config <file> & --set <key>=<value> override the config file");
+    eprintln!(">                           --spectators <port> streams the game to spectators, like host & join do");
+    eprintln!(
+        ">                           --book <file> lets the engines play from an opening book, like engine & serve"
+    );
+    eprintln!(">   muehle resume <file>    Continues a saved game");
+    eprintln!(">   muehle replay <file>    Steps through a saved game: n(ext), p(revious), j(ump) <move>, q(uit)");
+    eprintln!(">   muehle host [options]   Hosts a game over the network: --port <n>, --name <name>, --color w|b");
+    eprintln!(
+        ">   muehle join <host>      Joins a hosted game, the host may end in :<port>: --name <name>, --color w|b"
+    );
+    eprintln!(">   muehle serve [options]  Serves the JSON API on localhost: --port <n>, --book <file>, needs the http feature");
+    eprintln!(">   muehle start <position> Starts a game from a position like \"WWEEEEEEBBEEEEEEEEEEEEEE w 7 7\"");
+    eprintln!(">   muehle solve [options]  Solves the move phase: --threads <n>, --database <dir>, --max-stones <n>");
+    eprintln!(">   muehle verify [options] Checks a solved database, same options as solve & --report <n>");
+    eprintln!(">   muehle solve-set        Solves the set phase on a solved database: --database <dir>, --stones 3-5");
+    eprintln!(">   muehle analyse <code>   Reports about a board code or position string: --to-move w|b,");
+    eprintln!(">                           --white-hand <n>, --black-hand <n>, --depth <n>, --database <dir>, --order <order>");
+    eprintln!(">   muehle svg <code>       Draws a board as SVG: --output <file>, --last-move <move>, --mills,");
+    eprintln!(">                           --suggest <move> (repeatable), --order <order>");
+    eprintln!(">                           Board codes are in ring order, --order row reads them row by row from A1");
+    eprintln!(">                           & 24 comma separated fields like a7,d7,g7,... give any other order");
+    eprintln!(">   muehle canon [files]    Numbers each board code by its first equivalent line: --order <order>");
+    eprintln!(">   muehle triple [files]   Counts moves, mill moves & takeable stones: --color w|b, --order <order>");
+    eprintln!(">                           Files are <input> <output>, stdin & stdout if missing or -");
+    eprintln!(">   muehle csv <file>...    Exports saved games with one row per ply: --output <file>");
+    eprintln!(">   muehle engine [options] Speaks the engine protocol (mep, position, go, stop): --book <file>");
+    eprintln!(">   muehle book [options]   Builds an opening book by self-play: --output <file>, --games <n>,");
+    eprintln!(">                           --depth <n>, --exploration <n> (one in n placements is random), --seed <n>");
+    process::exit(1)
+}
+
+/// Parses the value following a flag, exits with an error message if it's missing or malformed
+fn parse_value<T: FromStr>(flag: &str, value: Option<&String>) -> T {
+    match value.map(|value| value.parse::<T>()) {
+        Some(Ok(value)) => value,
+        Some(Err(_)) => exit_with_error(&format!("Malformed value for '{flag}'.")),
+        None => exit_with_error(&format!("Missing value for '{flag}'.")),
+    }
+}
+
+/// Like [parse_value] for `w` or `b`
+fn parse_color(flag: &str, value: Option<&String>) -> PlayerColor {
+    match parse_value::<String>(flag, value).to_lowercase().as_str() {
+        "w" | "white" => PlayerColor::White,
+        "b" | "black" => PlayerColor::Black,
+        _ => exit_with_error(&format!("The value of '{flag}' must be 'w' or 'b'.")),
+    }
+}
+
+/// The order of the board code given by `--order` anywhere in the arguments, ring-clockwise by default
+fn board_order(args: &[String]) -> FieldOrder {
+    match args.iter().position(|arg| arg == "--order") {
+        None => FieldOrder::RingClockwise,
+        Some(index) => match args.get(index + 1).map(|value| value.parse::<FieldOrder>()) {
+            Some(Ok(order)) => order,
+            Some(Err(error)) => exit_with_error(&format!("Malformed value for '--order': {error}.")),
+            None => exit_with_error("Missing value for '--order'."),
+        },
+    }
+}
+
+/// Like [parse_value], but reports the notation error
+fn parse_move(flag: &str, value: Option<&String>) -> Move {
+    match value.map(|value| value.parse::<Move>()) {
+        Some(Ok(parsed)) => parsed,
+        Some(Err(error)) => exit_with_error(&format!("Malformed move for '{flag}': {error}.")),
+        None => exit_with_error(&format!("Missing value for '{flag}'.")),
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("> {}", ERROR.paint(message));
+    process::exit(1)
+}
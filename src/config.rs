@@ -0,0 +1,283 @@
+//! Defaults for the interactive games, read from a file of `key = value` lines. Empty lines & everything after a `#`
+//! are skipped:
+//!
+//! ```text
+//! player1.name = Alice
+//! player2.name = Bob
+//! player2.type = engine      # human or engine
+//! player2.level = 4          # search depth of the engine, 1 - 10
+//! book = book.txt            # opening book of the engines, see muehle::engine::book
+//! white = 1                  # the player playing white, asked if missing
+//! time = 300+5               # time control, see muehle::game::clock
+//! best_of = 5                # plays a match of up to 5 games, rematches are asked for otherwise
+//! theme = plain              # colored or plain
+//! variant = nine             # nine for Nine Men's Morris, the only variant so far
+//! draw_after = 100           # draws games in the move phase after 100 plies without a take
+//! language = en              # the language of the prompts, en is the only one so far
+//! ```
+//!
+//! The file is named by `MUEHLE_CONFIG`, otherwise `muehle/config` in `$XDG_CONFIG_HOME` or `~/.config` is read if it
+//! exists. Unknown keys & values are errors, files written for later versions don't silently play something else.
+
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use muehle::game::clock::TimeControl;
+
+/// Names the config file instead of the default path
+pub const CONFIG_VARIABLE: &str = "MUEHLE_CONFIG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlayerKind {
+    #[default]
+    Human,
+    /// Searches `level` plies deep
+    Engine { level: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PlayerConfig {
+    /// Asked for if missing, engines are called `Engine <number>` then
+    pub name: Option<String>,
+    pub kind: PlayerKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Colored,
+    /// Without any colors, for terminals which don't support them
+    Plain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Variant {
+    /// Nine Men's Morris, with jumps for players down to 3 stones
+    #[default]
+    Nine,
+}
+
+/// Selects the strings of the prompts, see [crate::coordination::strings]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Config {
+    /// Player 1 & 2
+    pub players: [PlayerConfig; 2],
+    /// True if player 2 plays white, asked at the start of the game if missing
+    pub player_2_white: Option<bool>,
+    pub time_control: Option<TimeControl>,
+    /// The number of games of a match, which ends early once a player has won more than half of them
+    pub best_of: Option<u32>,
+    /// The opening book the engines play from in the set phase
+    pub book: Option<PathBuf>,
+    pub theme: Theme,
+    pub variant: Variant,
+    /// Draws games in the move phase once this many plies were played without a take, the set phase included
+    pub draw_after: Option<u32>,
+    pub language: Language,
+}
+
+/// A malformed line of the config file, `line` counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/// The engine level of players switched to engines without one
+const DEFAULT_LEVEL: u8 = 3;
+
+impl Config {
+    /// The file named by [CONFIG_VARIABLE], or the default path if the file exists
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_VARIABLE) {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("muehle").join("config")).filter(|path| path.exists())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Config> {
+        Config::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ConfigError {
+                line: index + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("Expected a line like key = value".to_string()))?;
+            config.set(key.trim(), value.trim()).map_err(error)?;
+        }
+        Ok(config)
+    }
+
+    /// Sets a key like in the config file, used for overrides on the command line too
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if let Some((player, property)) = key.split_once('.') {
+            let player = match player {
+                "player1" => &mut self.players[0],
+                "player2" => &mut self.players[1],
+                _ => return Err(format!("Unknown key '{key}', players are player1 & player2")),
+            };
+            match property {
+                "name" if value.is_empty() => return Err("The name can't be empty".to_string()),
+                "name" => player.name = Some(value.to_string()),
+                "type" => {
+                    player.kind = match value {
+                        "human" => PlayerKind::Human,
+                        "engine" => match player.kind {
+                            PlayerKind::Engine { level } => PlayerKind::Engine { level },
+                            PlayerKind::Human => PlayerKind::Engine { level: DEFAULT_LEVEL },
+                        },
+                        _ => return Err(format!("Unknown player type '{value}', expected human or engine")),
+                    }
+                }
+                "level" => {
+                    let level = value
+                        .parse()
+                        .ok()
+                        .filter(|level| (1..=10).contains(level))
+                        .ok_or_else(|| format!("The level must be a number from 1 to 10, not '{value}'"))?;
+                    player.kind = PlayerKind::Engine { level };
+                }
+                _ => return Err(format!("Unknown key '{key}', players have a name, type & level")),
+            }
+            return Ok(());
+        }
+
+        match key {
+            "white" => {
+                self.player_2_white = match value {
+                    "1" => Some(false),
+                    "2" => Some(true),
+                    _ => return Err(format!("The player playing white must be 1 or 2, not '{value}'")),
+                }
+            }
+            "time" => self.time_control = Some(value.parse()?),
+            "best_of" => {
+                let games = value
+                    .parse()
+                    .ok()
+                    .filter(|games| 0 < *games)
+                    .ok_or_else(|| format!("The number of games must be a positive number, not '{value}'"))?;
+                self.best_of = Some(games);
+            }
+            "book" if value.is_empty() => return Err("The book file can't be empty".to_string()),
+            "book" => self.book = Some(PathBuf::from(value)),
+            "theme" => {
+                self.theme = match value {
+                    "colored" => Theme::Colored,
+                    "plain" => Theme::Plain,
+                    _ => return Err(format!("Unknown theme '{value}', expected colored or plain")),
+                }
+            }
+            "variant" => {
+                self.variant = match value {
+                    "nine" => Variant::Nine,
+                    _ => return Err(format!("Unknown variant '{value}', only nine is supported")),
+                }
+            }
+            "draw_after" => {
+                let plies = value
+                    .parse()
+                    .ok()
+                    .filter(|plies| 0 < *plies)
+                    .ok_or_else(|| format!("The plies without a take must be a positive number, not '{value}'"))?;
+                self.draw_after = Some(plies);
+            }
+            "language" => {
+                self.language = match value {
+                    "en" => Language::English,
+                    _ => return Err(format!("Unknown language '{value}', only en is supported")),
+                }
+            }
+            _ => return Err(format!("Unknown key '{key}'")),
+        }
+        Ok(())
+    }
+
+    /// The name of player 1 or 2 if configured, engines are named by their number otherwise
+    pub fn name_of(&self, player: usize) -> Option<String> {
+        let config = &self.players[player - 1];
+        match config.kind {
+            PlayerKind::Engine { .. } if config.name.is_none() => Some(format!("Engine {player}")),
+            _ => config.name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_override() {
+        let mut config = Config::parse(
+            "# Defaults\nplayer1.name = Alice\n\nplayer2.level = 5 # deep\nwhite=2\ntime = 300+5\nbest_of = 3\ntheme = plain\nbook = openings.txt\nvariant = nine\ndraw_after = 50\nlanguage = en\n",
+        )
+        .unwrap();
+        assert_eq!(config.name_of(1), Some("Alice".to_string()));
+        assert_eq!(config.name_of(2), Some("Engine 2".to_string()));
+        assert_eq!(config.players[1].kind, PlayerKind::Engine { level: 5 });
+        assert_eq!(config.player_2_white, Some(true));
+        assert_eq!(config.time_control, Some("300+5".parse().unwrap()));
+        assert_eq!(config.best_of, Some(3));
+        assert_eq!(config.theme, Theme::Plain);
+        assert_eq!(config.book, Some(PathBuf::from("openings.txt")));
+        assert_eq!(config.variant, Variant::Nine);
+        assert_eq!(config.draw_after, Some(50));
+        assert_eq!(config.language, Language::English);
+
+        config.set("player2.type", "engine").unwrap();
+        assert_eq!(config.players[1].kind, PlayerKind::Engine { level: 5 });
+        config.set("player2.type", "human").unwrap();
+        assert_eq!(config.name_of(2), None);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for (text, line) in [
+            ("player3.name = Carol", 1),
+            ("\nplayer1.level = 11", 2),
+            ("white 1", 1),
+            ("theme = dark", 1),
+            ("language = de", 1),
+            ("variant = twelve", 1),
+            ("draw_after = 0", 1),
+            ("time = 5 minutes", 1),
+            ("best_of = 0", 1),
+            ("player1.name =", 1),
+            ("book =", 1),
+        ] {
+            assert_eq!(Config::parse(text).unwrap_err().line, line, "{text}");
+        }
+    }
+}
@@ -1,15 +1,96 @@
 //! Contains the setup method for the [GameCoordinator] struct, which is meant to modify the [PlayField] state, receive & handle player input, set things up, enforce the play phases etc.
 //! This module holds the game loop & some auxiliary helper functions.
+//!
+//! The coordinator reads the input of the players from any [BufRead] & writes everything into any [Write], usually
+//! stdin & stdout. Scripted games run in the tests, with their transcripts compared to the files in `golden/`, & the
+//! scenarios of `scenarios/` check the end of scripted games, see `scenarios.rs`.
+//!
+//! Observers registered with [GameCoordinator::observe] are notified of the [GameEvent]s as the game goes on.
+//!
+//! After each game a rematch is offered, see [match_play] for matches of several games.
+//!
+//! Players configured as engines enter their moves through the same prompts as humans, see [Config].
+//!
+//! Games with a [TimeControl] charge the time from the turn header until the move is finished, takes included. The
+//! input isn't interrupted, so a flag fall is noticed when the move is entered & the move isn't played then.
 
-use muehle::game::{painting::*, Field, PlayerColor};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Write},
+    path::Path,
+    sync::atomic::AtomicBool,
+    time::Instant,
+};
+
+use muehle::engine::{self, book::OpeningBook, EnginePlayer, SearchLimits};
+use muehle::game::{
+    clock::{Clock, FlagFall, TimeControl},
+    efficient_state::moves::{coded_index_of, takeable_stones, CODED_FIELDS},
+    events::{GameEvent, GameObserver},
+    notation::Move,
+    painting::*,
+    position::{ExtendedPosition, Phase, Position},
+    record::{GameRecord, GameResult, RecordError, Termination},
+    Field, PlayerColor,
+};
 
 use muehle::game::state::PlayField;
 
 use smallvec::SmallVec;
 use smartstring::alias::CompactString;
 
+use crate::config::{Config, PlayerKind};
+
 mod game_phases;
+mod match_play;
+#[cfg(test)]
+mod scenarios;
 mod setup;
+mod strings;
+
+use match_play::Score;
+use strings::Strings;
+
+/// Writing to the terminal or a buffer isn't expected to fail
+const OUTPUT_FAILED: &str = "Writing the output of the game failed";
+
+/// Like [print!] into the output of the coordinator, flushed for prompts
+macro_rules! out {
+    ($coordinator:expr, $($arg:tt)*) => {{
+        write!($coordinator.output, $($arg)*).expect($crate::coordination::OUTPUT_FAILED);
+        $coordinator.output.flush().expect($crate::coordination::OUTPUT_FAILED);
+    }};
+}
+
+/// Like [println!] into the output of the coordinator
+macro_rules! outln {
+    ($coordinator:expr) => {
+        writeln!($coordinator.output).expect($crate::coordination::OUTPUT_FAILED)
+    };
+    ($coordinator:expr, $($arg:tt)*) => {
+        writeln!($coordinator.output, $($arg)*).expect($crate::coordination::OUTPUT_FAILED)
+    };
+}
+
+use {out, outln};
+
+/// The input ended before the game did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndOfInput;
+
+/// Why a game stopped before it was over
+#[derive(Debug)]
+enum Interruption {
+    EndOfInput,
+    /// The play field accepted a move which isn't legal in the position of the record
+    Inconsistent(RecordError),
+}
+
+impl From<EndOfInput> for Interruption {
+    fn from(_: EndOfInput) -> Self {
+        Interruption::EndOfInput
+    }
+}
 
 #[derive(Clone, Copy)]
 pub enum GamePhase {
@@ -19,62 +100,191 @@ pub enum GamePhase {
     Terminated,
 }
 
-pub struct GameCoordinator {
+pub struct GameCoordinator<I, O> {
     play_field: PlayField,
+    /// The same stones as the play field, with the side to move & the stones in hand. Follows the recorded moves.
+    position: Position,
     // 0 = Player 1, 1 = Player 2
     player_names: (CompactString, CompactString),
     round: u32,
     game_phase: GamePhase,
     // false -> Player 1, true -> Player 2
     turn: bool,
+    // The player playing white, indexed like the turn
+    player_2_white: bool,
     error_state: bool,
+    /// Names by color & the moves played so far, used for saving the game
+    record: GameRecord,
+    input: I,
+    output: O,
+    observers: Vec<Box<dyn GameObserver>>,
+    /// None for games without time control
+    clock: Option<Clock>,
+    /// When the player on turn started their move
+    turn_started: Instant,
+    config: Config,
+    /// The fields still to enter for the move of an engine
+    engine_input: VecDeque<Field>,
+    /// Chooses the moves of the players configured as engines
+    engine: EnginePlayer,
+    /// The finished games of the match so far
+    score: Score,
 }
 
-impl GameCoordinator {
-    // TODO Refactor in game-loop.rs
+impl<I: BufRead, O: Write> GameCoordinator<I, O> {
+    /// Plays games until the match is over or the input ends
     pub fn start_game(&mut self) {
-        let mut player_won = false;
+        // Rematches start from the same position
+        let start = self.record.start;
+        loop {
+            if let Err(interruption) = self.play_game() {
+                match interruption {
+                    Interruption::EndOfInput => outln!(self, "\n> The input ended, stopping the game."),
+                    Interruption::Inconsistent(error) => self.print_error(&format!(
+                        "The move doesn't fit the record of the game, stopping: {error}"
+                    )),
+                }
+                self.notify(GameEvent::Ended {
+                    result: GameResult::Ongoing,
+                    termination: Termination::Unterminated,
+                });
+                break;
+            }
+            self.count_game();
+            if !self.rematch(start) {
+                break;
+            }
+        }
+        self.print_match_summary();
+    }
+
+    /// Registers an observer for the events of the game, notified in the order of registration
+    pub fn observe(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Plays with clocks, the time control is saved with the game
+    pub fn set_time_control(&mut self, control: TimeControl) {
+        self.clock = Some(Clock::new(control));
+        self.record.time_control = Some(control);
+    }
+
+    /// Lets the engines play from the book as long as it knows the position
+    pub fn set_book(&mut self, book: OpeningBook) {
+        self.engine.book = Some(book);
+    }
+
+    fn notify(&mut self, event: GameEvent) {
+        for observer in &mut self.observers {
+            observer.notify(&event);
+        }
+    }
+
+    // TODO Refactor in game-loop.rs
+    fn play_game(&mut self) -> Result<(), Interruption> {
+        // Indexed like the turn: false -> Player 1 won, true -> Player 2 won
+        let mut winner = false;
+
+        // Resumed games skip the start
+        if !matches!(self.game_phase, GamePhase::Start) {
+            self.notify(GameEvent::Started {
+                white: self.record.white.clone(),
+                black: self.record.black.clone(),
+                position: self.current_extended_position(),
+            });
+        }
 
         while let GamePhase::Start = self.game_phase {
-            println!("> Starting the game!");
-            let playing_white_id = self.setup_player_colors();
+            outln!(self, "{}", self.strings().starting_game);
+            let playing_white_id = self.setup_player_colors()?;
+            self.player_2_white = playing_white_id;
 
-            // White begins: if player id is 2, set turn to 1 for player 2 to start
-            if playing_white_id {
-                self.turn = true;
-            }
-            self.round = 1;
+            let (white, black) = match playing_white_id {
+                false => (&self.player_names.0, &self.player_names.1),
+                true => (&self.player_names.1, &self.player_names.0),
+            };
+            // Set by setup_from for games not starting on the empty play field
+            let start = self.record.start.take();
+            self.record = GameRecord::new(white, black);
+            self.record.time_control = self.clock.map(|clock| clock.control);
 
-            println!(
+            outln!(
+                self,
                 "> {} plays {}.",
-                EMP.paint(format!("Player {}", self.which_players_turn())),
+                EMP.paint(format!("Player {}", playing_white_id as u32 + 1)),
                 HIGHLIGHT.paint("white")
             );
+            outln!(
+                self,
+                "> Enter {} at any prompt to save the game.",
+                HIGHLIGHT.paint("save <file>")
+            );
+
+            let mut start = start.unwrap_or_default();
+            // White begins: if player id is 2, set turn to 1 for player 2 to start. Starting positions can let black begin
+            self.turn = playing_white_id ^ (start.position.side_to_move == PlayerColor::Black);
+            self.round = first_round_of(&start);
+            self.notify(GameEvent::Started {
+                white: self.record.white.clone(),
+                black: self.record.black.clone(),
+                position: start,
+            });
 
-            self.game_phase = GamePhase::Set;
+            if start.pending_capture {
+                let (player_name, player_color) = self.get_current_turns_attributes();
+                let player_name = CompactString::from(player_name);
+                outln!(
+                    self,
+                    "> {} closed a mill and takes a stone first.",
+                    EMP.paint(&player_name)
+                );
+                self.print_play_highlighted(None);
+                self.queue_engine_take(&start);
+
+                let taken_field = self.take_stone_interaction(player_color)?;
+                start = start.after_take(coded_index_of(taken_field).unwrap() as u8);
+                self.round += 1;
+                self.turn = !self.turn;
+            }
+            if start != ExtendedPosition::default() {
+                self.record.start = Some(start);
+            }
+            self.position = start.position;
+
+            self.game_phase = phase_of(&start.position);
+        }
+        if let GamePhase::Set = self.game_phase {
+            outln!(self, "\n> Starting with {}!", EMP.paint("Set-Phase"));
         }
-        println!("\n> Starting with {}!", EMP.paint("Set-Phase"));
 
         let mut changes_to_highlight = SmallVec::<[Field; 3]>::new();
-        let mut set_rounds_done = 0;
+        // Non zero for resumed games & games starting from a position
+        let mut set_rounds_done = 9 - self.position.stones_in_hand.1 as u32;
 
         while set_rounds_done < 9 {
             let (player_color, player_name) =
                 self.print_turn_header(self.game_phase, Some(set_rounds_done), &changes_to_highlight);
 
             changes_to_highlight.clear();
-            let input_field = self.get_field_coord_input("> Enter a field a stone should be placed on: ");
+            let input_field = self.get_field_coord_input(self.strings().place_prompt)?;
             changes_to_highlight.push(input_field);
 
             match self.play_field.try_set(input_field, player_color) {
-                Ok(_) => println!(
-                    "> Successfully placed {} on {} for {}.",
-                    HIGHLIGHT.paint(player_color),
-                    HIGHLIGHT.paint(format!("{}{}", input_field.0, input_field.1)),
-                    EMP.paint(player_name)
-                ),
+                Ok(_) => {
+                    outln!(
+                        self,
+                        "> Successfully placed {} on {} for {}.",
+                        HIGHLIGHT.paint(player_color),
+                        HIGHLIGHT.paint(format!("{}{}", input_field.0, input_field.1)),
+                        EMP.paint(&player_name)
+                    );
+                    self.notify(GameEvent::StonePlaced {
+                        color: player_color,
+                        field: input_field,
+                    });
+                }
                 Err(err) => {
-                    print_error(&format!("{}", err));
+                    self.print_error(&format!("{}", err));
 
                     self.error_state = true;
                     continue;
@@ -83,13 +293,28 @@ impl GameCoordinator {
 
             // If a mill ocurred & a stone was stolen, print info message & set game states according to the
             // left amount of stones on the field. Only the opponents amount of stones changes
-            if let Some(mut mills) = self.do_mills_interaction(input_field, player_color) {
+            let mut take = None;
+            if let Some((mut mills, taken_field)) = self.do_mills_interaction(input_field, player_color)? {
                 changes_to_highlight.append(&mut mills);
+                take = Some(taken_field);
                 //for mill in mills { TODO ?
                 //if changes_to_highlight.contains(&mill) {
                 //}
                 //}
             };
+            if self.flag_fell(player_color) {
+                winner = !self.turn;
+                break;
+            }
+            self.record_move(
+                Move {
+                    start: None,
+                    target: input_field,
+                    take,
+                },
+                player_color,
+            )
+            .map_err(Interruption::Inconsistent)?;
 
             self.error_state = false;
             self.round += 1;
@@ -100,72 +325,150 @@ impl GameCoordinator {
             }
         }
 
-        self.game_phase = GamePhase::MoveAndJump;
-        println!("\n> Starting with {}!", EMP.paint("Move-Phase"));
+        if let GamePhase::Set = self.game_phase {
+            self.game_phase = GamePhase::MoveAndJump;
+            outln!(self, "\n> Starting with {}!", EMP.paint("Move-Phase"));
+
+            for (color, stones) in [
+                (PlayerColor::White, self.play_field.amount_of_stones.0),
+                (PlayerColor::Black, self.play_field.amount_of_stones.1),
+            ] {
+                let phase = if stones == 3 { Phase::Jump } else { Phase::Move };
+                self.notify(GameEvent::PhaseChanged { color, phase });
+            }
+        }
 
         while let GamePhase::MoveAndJump = self.game_phase {
+            // A player who can't move any stone has lost, before being asked for a move
+            if self.position.legal_moves().is_empty() {
+                let (player_name, player_color) = self.get_current_turns_attributes();
+                let player_name = CompactString::from(player_name);
+                self.print_play_highlighted(Some(&changes_to_highlight));
+                outln!(
+                    self,
+                    ">\n> {} can't move any stone. Terminating game.\n>",
+                    EMP.paint(&player_name)
+                );
+
+                winner = !self.turn;
+                self.record.result = GameResult::won_by(!player_color);
+                self.record.termination = Termination::NoMovesLeft;
+                self.game_phase = GamePhase::Terminated;
+                break;
+            }
             let (player_color, player_name) = self.print_turn_header(self.game_phase, None, &changes_to_highlight);
 
             changes_to_highlight.clear();
-            let start_field = self.get_field_coord_input("> Enter the stone you want to move: ");
+            let start_field = self.get_field_coord_input(self.strings().move_start_prompt)?;
             changes_to_highlight.push(start_field);
-            let target_field = self.get_field_coord_input("> Enter it's target position: ");
+            let target_field = self.get_field_coord_input(self.strings().move_target_prompt)?;
             changes_to_highlight.push(target_field);
 
             // Print out the coords if move was successful, else continue loop
             match self.play_field.try_move(start_field, target_field, player_color) {
-                Ok(_) => println!(
-                    "> {} successfully moved a {} stone from {} to {}.",
-                    EMP.paint(player_name),
-                    HIGHLIGHT.paint(player_color),
-                    HIGHLIGHT.paint(format!("{}{}", start_field.0, start_field.1)),
-                    HIGHLIGHT.paint(format!("{}{}", target_field.0, target_field.1))
-                ),
+                Ok(_) => {
+                    outln!(
+                        self,
+                        "> {} successfully moved a {} stone from {} to {}.",
+                        EMP.paint(&player_name),
+                        HIGHLIGHT.paint(player_color),
+                        HIGHLIGHT.paint(format!("{}{}", start_field.0, start_field.1)),
+                        HIGHLIGHT.paint(format!("{}{}", target_field.0, target_field.1))
+                    );
+                    self.notify(GameEvent::StoneMoved {
+                        color: player_color,
+                        from: start_field,
+                        to: target_field,
+                    });
+                }
                 Err(err) => {
-                    print_error(&format!("{}", err));
+                    self.print_error(&format!("{}", err));
 
                     self.error_state = true;
                     continue;
                 }
             }
 
-            if let Some(mut mills) = self.do_mills_interaction(target_field, player_color) {
+            let mut take = None;
+            if let Some((mut mills, taken_field)) = self.do_mills_interaction(target_field, player_color)? {
                 changes_to_highlight.append(&mut mills);
+                take = Some(taken_field);
+            }
+            if self.flag_fell(player_color) {
+                winner = !self.turn;
+                break;
             }
+            self.record_move(
+                Move {
+                    start: Some(start_field),
+                    target: target_field,
+                    take,
+                },
+                player_color,
+            )
+            .map_err(Interruption::Inconsistent)?;
 
             // The opponent of the current play might have lost a stone:
-            let player_and_amount_of_stones = match player_color {
-                PlayerColor::White => (&self.player_names.1, self.play_field.amount_of_stones.1),
-                PlayerColor::Black => (&self.player_names.0, self.play_field.amount_of_stones.0),
+            let opponents_stones = match player_color {
+                PlayerColor::White => self.play_field.amount_of_stones.1,
+                PlayerColor::Black => self.play_field.amount_of_stones.0,
             };
+            // Player 2 plays white in some games, so the name is looked up by the color
+            let player_and_amount_of_stones = (
+                CompactString::from(self.record.name_of(!player_color)),
+                opponents_stones,
+            );
 
             // One player has less than 2 stones and has lost the game. Mutates self.phase
             if player_and_amount_of_stones.1 <= 2 {
-                println!(
+                outln!(
+                    self,
                     ">\n> {} only has {} stones left. Terminating game.\n>",
-                    EMP.paint(player_and_amount_of_stones.0),
+                    EMP.paint(&player_and_amount_of_stones.0),
                     HIGHLIGHT.paint(player_and_amount_of_stones.1)
                 );
 
-                player_won = player_and_amount_of_stones.0 != &self.player_names.0;
+                winner = self.turn;
                 self.game_phase = GamePhase::Terminated;
             // Info message, allowing jumps for player with only 3 stones left
             } else if player_and_amount_of_stones.1 == 3 {
-                println!(
+                outln!(
+                    self,
                     ">\n> {} only has {} stones left. Starting with {}!\n>",
-                    EMP.paint(player_and_amount_of_stones.0),
+                    EMP.paint(&player_and_amount_of_stones.0),
                     HIGHLIGHT.paint(player_and_amount_of_stones.1),
                     EMP.paint("Jump-Phase")
                 );
+                // The message repeats while the opponent has 3 stones, the phase only changes with the take
+                if take.is_some() {
+                    self.notify(GameEvent::PhaseChanged {
+                        color: !player_color,
+                        phase: Phase::Jump,
+                    });
+                }
             // Normal info message printing out new amount of stones on the playfield
             } else {
-                println!(
+                outln!(
+                    self,
                     ">\n> {} only has {} stones left.\n>",
-                    EMP.paint(player_and_amount_of_stones.0),
+                    EMP.paint(&player_and_amount_of_stones.0),
                     HIGHLIGHT.paint(player_and_amount_of_stones.1),
                 );
             }
 
+            // Games won by the last move aren't drawn, takes reset the count anyway
+            let plies_since_take = self.current_extended_position().plies_since_take;
+            if matches!(self.game_phase, GamePhase::MoveAndJump)
+                && self.config.draw_after.is_some_and(|plies| plies <= plies_since_take)
+            {
+                let message = self.strings().drawn_by_move_limit;
+                outln!(self, ">\n{message}\n>");
+
+                self.record.result = GameResult::Draw;
+                self.record.termination = Termination::MoveLimit;
+                self.game_phase = GamePhase::Terminated;
+            }
+
             self.error_state = false;
             self.round += 1;
             self.turn = !self.turn;
@@ -173,20 +476,247 @@ impl GameCoordinator {
 
         assert!(matches!(self.game_phase, GamePhase::Terminated));
 
-        let winners_name = match player_won {
-            true => &self.player_names.0,
-            false => &self.player_names.1,
+        // Draws were announced when the game ended
+        if self.record.result != GameResult::Draw {
+            let winners_name = match winner {
+                false => self.player_names.0.clone(),
+                true => self.player_names.1.clone(),
+            };
+            outln!(self, "> {}", EMP.paint(format!("{} won the game!", winners_name)));
+        }
+
+        if let Err(error) = self.record.update_result() {
+            self.print_error(&format!("The record of the game is inconsistent: {error}"));
+        }
+        self.notify(GameEvent::Ended {
+            result: self.record.result,
+            termination: self.record.termination,
+        });
+        self.offer_to_save();
+        Ok(())
+    }
+
+    /// Continues a game saved with the `save` command. The record is validated by replaying all of its moves.
+    /// Player 1 is the one playing white.
+    pub fn resume(path: &Path, config: Config, input: I, mut output: O) -> Result<Self, String> {
+        let record = GameRecord::load(path).map_err(|error| format!("Loading '{}' failed: {error}", path.display()))?;
+        if record.result != GameResult::Ongoing {
+            return Err(format!(
+                "The game is already over: {} ({}).",
+                record.result, record.termination
+            ));
+        }
+        let position = record.validate().map_err(|error| error.to_string())?;
+        let play_field = PlayField::try_from(position.board).map_err(|error| error.to_string())?;
+
+        let plies = record.moves.len() as u32;
+        let clock = record.clock();
+        writeln!(
+            output,
+            "> Resuming the game of {} against {} after {} moves.",
+            EMP.paint(&record.white),
+            EMP.paint(&record.black),
+            HIGHLIGHT.paint(plies)
+        )
+        .expect(OUTPUT_FAILED);
+
+        Ok(GameCoordinator {
+            play_field,
+            position,
+            player_names: (record.white.as_str().into(), record.black.as_str().into()),
+            round: first_round_of(&record.start.unwrap_or_default()) + plies,
+            game_phase: phase_of(&position),
+            // Player 2 plays black
+            turn: position.side_to_move == PlayerColor::Black,
+            player_2_white: false,
+            error_state: false,
+            record,
+            input,
+            output,
+            observers: Vec::new(),
+            clock,
+            turn_started: Instant::now(),
+            config,
+            engine_input: VecDeque::new(),
+            engine: EnginePlayer::default(),
+            score: Score::default(),
+        })
+    }
+
+    /// Sets up a game starting from a position in the extended position notation instead of the empty play field. The
+    /// placements of both players have to be in step, like in a game started on the empty play field. The position is
+    /// checked before the players are asked for their names.
+    pub fn setup_from(start: ExtendedPosition, config: Config, input: I, output: O) -> Result<Self, String> {
+        let position = start.position;
+        start.check_stone_counts().map_err(|message| format!("{message}."))?;
+
+        let opponent = !position.side_to_move;
+        let opponents_stones = position.stones_on_board_of(opponent) + position.stones_in_hand_of(opponent);
+        if start.pending_capture && opponents_stones <= 3 {
+            return Err("The game is over after the pending capture.".to_string());
+        }
+        if let (false, Some(winner)) = (start.pending_capture, position.winner()) {
+            return Err(format!("The game is over already, {winner:?} has won."));
+        }
+
+        let play_field = PlayField::try_from(position.board).map_err(|error| error.to_string())?;
+        let mut coordinator = GameCoordinator::setup(config, input, output)
+            .map_err(|_| "The input ended before the game started.".to_string())?;
+        coordinator.play_field = play_field;
+        coordinator.record.start = Some(start);
+        Ok(coordinator)
+    }
+
+    /// The next field entered by the engine on turn, whose move is searched when its turn starts. None for humans.
+    fn engine_input(&mut self) -> Option<Field> {
+        let PlayerKind::Engine { level } = self.config.players[self.which_players_turn() as usize - 1].kind else {
+            return None;
+        };
+        if self.engine_input.is_empty() {
+            let (_, color) = self.get_current_turns_attributes();
+            let limits = SearchLimits {
+                depth: Some(level),
+                time: self.clock.map(|clock| clock.budget(color)),
+            };
+            let result = self
+                .engine
+                .choose_move(&self.position, limits, &AtomicBool::new(false), |_| {});
+            // Blocked players lose before their turn, in the set phase there is always a free field
+            let best = Move::from(result.best_move.expect("The engine has a legal move on its turn"));
+            // Records keep the evaluations from the view of white
+            let evaluation = match color {
+                PlayerColor::White => result.score,
+                PlayerColor::Black => -result.score,
+            };
+            self.record.evaluations.insert(self.record.moves.len() + 1, evaluation);
+            self.engine_input.extend(best.start);
+            self.engine_input.push_back(best.target);
+            self.engine_input.extend(best.take);
+        }
+        self.engine_input.pop_front()
+    }
+
+    /// Picks the stone to take for an engine, which starts with a pending capture
+    fn queue_engine_take(&mut self, start: &ExtendedPosition) {
+        if !matches!(
+            self.config.players[self.which_players_turn() as usize - 1].kind,
+            PlayerKind::Engine { .. }
+        ) {
+            return;
+        }
+        let (_, opponents_stones) = start.position.board.get_masks_of(start.position.side_to_move);
+        let takeable = takeable_stones(opponents_stones);
+        // The evaluation is from the view of the opponent moving next
+        let best = (0..CODED_FIELDS.len())
+            .filter(|index| takeable & (1 << index) != 0)
+            .min_by_key(|index| engine::evaluate(&start.after_take(*index as u8).position));
+        self.engine_input.extend(best.map(|index| CODED_FIELDS[index]));
+    }
+
+    /// Charges the time of the finished move. If the flag fell, the game is lost & over.
+    fn flag_fell(&mut self, color: PlayerColor) -> bool {
+        let Some(clock) = &mut self.clock else {
+            return false;
+        };
+        let Err(FlagFall(color)) = clock.charge(color, self.turn_started.elapsed()) else {
+            return false;
         };
-        println!(
-            "> {}",
-            EMP.paint(format!("{} won the match! Congratulations!", winners_name))
+
+        outln!(
+            self,
+            ">\n> {} ran out of time, the move doesn't count. Terminating game.\n>",
+            EMP.paint(self.record.name_of(color))
         );
+        self.record.result = GameResult::won_by(!color);
+        self.record.termination = Termination::TimeForfeit;
+        self.game_phase = GamePhase::Terminated;
+        true
+    }
+
+    /// Keeps the move & the clock of the player after it for saving & plays the move in the position. Fails if the move
+    /// isn't legal in the position, which is left unchanged then.
+    fn record_move(&mut self, played: Move, color: PlayerColor) -> Result<(), RecordError> {
+        let legal_move = played
+            .to_efficient()
+            .filter(|efficient_move| self.position.legal_moves().contains(efficient_move))
+            .ok_or(RecordError::IllegalMove {
+                ply: self.record.moves.len() + 1,
+                played,
+            })?;
+        self.position.play(legal_move);
+
+        self.record.moves.push(played);
+        if let Some(clock) = self.clock {
+            self.record
+                .clocks
+                .insert(self.record.moves.len(), clock.remaining_of(color));
+        }
+        Ok(())
+    }
+
+    /// The position after the moves played so far with its counters
+    fn current_extended_position(&self) -> ExtendedPosition {
+        let start = self.record.start.unwrap_or_default();
+        let plies_since_take = match self.record.moves.iter().rposition(|played| played.take.is_some()) {
+            Some(index) => self.record.moves.len() - index - 1,
+            None => start.plies_since_take as usize + self.record.moves.len(),
+        };
+        ExtendedPosition {
+            position: self.position,
+            pending_capture: false,
+            plies_since_take: plies_since_take as u32,
+            move_number: self.round.div_ceil(2),
+        }
+    }
+
+    /// Saves the moves played so far, an unfinished move isn't saved
+    fn save_game(&mut self, path: &Path) {
+        match self.record.store(path) {
+            Ok(_) => outln!(
+                self,
+                "> Saved the game after {} moves to {}.",
+                HIGHLIGHT.paint(self.record.moves.len()),
+                EMP.paint(path.display())
+            ),
+            Err(error) => self.print_error(&format!("Saving the game failed: {error}")),
+        }
+    }
+
+    /// Asks for a file to save the finished game to, the end of the input skips it
+    fn offer_to_save(&mut self) {
+        if let Ok(path) = self.prompt(self.strings().save_prompt) {
+            if !path.is_empty() {
+                self.save_game(Path::new(&path));
+            }
+        }
+    }
+
+    /// Prints the message & reads the next line without surrounding whitespace. Failed reads are reported & retried.
+    fn prompt(&mut self, message: &str) -> Result<String, EndOfInput> {
+        loop {
+            out!(self, "{message}");
+
+            let mut input_buffer = String::new();
+            match self.input.read_line(&mut input_buffer) {
+                Ok(0) => return Err(EndOfInput),
+                Ok(_) => return Ok(input_buffer.trim().to_string()),
+                Err(error) => self.print_error(&format!("Error occurred processing input: {error}")),
+            }
+        }
+    }
 
-        // TODO Ask for another round
+    /// The prompts & messages in the configured language
+    fn strings(&self) -> &'static Strings {
+        strings::of(self.config.language)
+    }
+
+    /// Shorthand for equal error printing
+    fn print_error(&mut self, message: &str) {
+        outln!(self, "> {}\n <", ERROR.paint(message))
     }
 }
 
-impl GameCoordinator {
+impl<I: BufRead, O: Write> GameCoordinator<I, O> {
     /// Returns the (real, \in [0,1]) player number which currently is on turn
     /// Turn is initially set to the player who choose the white color.
     fn which_players_turn(&self) -> u32 {
@@ -215,14 +745,287 @@ impl GameCoordinator {
 
     /// Wrapper for [print_plain] method of [PlayField], adding line breaks around it's output
     /// It is able to highlight the game field on specified points - by using the ^2 rt complexity :(
-    fn print_play_highlighted(&self, to_highlight: Option<&[Field]>) {
-        println!("\n");
-        self.play_field.print_highlighted(to_highlight);
-        println!("\n");
+    fn print_play_highlighted(&mut self, to_highlight: Option<&[Field]>) {
+        outln!(self, "\n");
+        self.play_field
+            .write_highlighted(&mut self.output, to_highlight)
+            .expect(OUTPUT_FAILED);
+        outln!(self, "\n");
     }
 }
 
-/// Shorthand for equal error printing
-fn print_error(message: &str) {
-    println!("> {}\n <", ERROR.paint(message))
+/// The round of the first move from the position, odd rounds are played by white
+fn first_round_of(start: &ExtendedPosition) -> u32 {
+    2 * (start.move_number - 1) + 1 + (start.position.side_to_move == PlayerColor::Black) as u32
+}
+
+fn phase_of(position: &Position) -> GamePhase {
+    if position.stones_in_hand == (0, 0) {
+        GamePhase::MoveAndJump
+    } else {
+        GamePhase::Set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::BTreeMap, env, fs, io, rc::Rc};
+
+    use super::*;
+
+    /// Sets up a game from the position or the empty play field with the config file text & plays it
+    fn play_script(start: Option<&str>, config: &str, script: &str, output: &mut Vec<u8>) {
+        let config = Config::parse(config).unwrap();
+        let coordinator = match start {
+            Some(start) => GameCoordinator::setup_from(start.parse().unwrap(), config, script.as_bytes(), output).ok(),
+            None => GameCoordinator::setup(config, script.as_bytes(), output).ok(),
+        };
+        coordinator.expect("The script contains the names").start_game();
+    }
+
+    /// Runs a scripted game & compares its transcript without colors to `golden/<name>.txt`. With `UPDATE_GOLDEN=1` the
+    /// golden file is written instead.
+    fn assert_transcript(name: &str, start: Option<&str>, config: &str, script: &str) {
+        let mut output = Vec::new();
+        play_script(start, config, script, &mut output);
+
+        let transcript = strip_colors(&String::from_utf8(output).unwrap());
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("src/coordination/golden/{name}.txt"));
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, &transcript).unwrap();
+        }
+        let golden = fs::read_to_string(&path).unwrap();
+        assert!(
+            transcript == golden,
+            "The transcript differs from {}:\n{transcript}",
+            path.display()
+        );
+    }
+
+    /// Removes the ANSI escape sequences of the painted output
+    pub(super) fn strip_colors(text: &str) -> String {
+        let mut stripped = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            } else {
+                stripped.push(c);
+            }
+        }
+        stripped
+    }
+
+    #[test]
+    fn mill_ends_the_game() {
+        // White closes the top row, black is left with two stones after the take
+        assert_transcript(
+            "mill_ends_the_game",
+            Some("EWEWEEEWWEEEEEEEBEBEBEEE w 0 0"),
+            "",
+            "Alice\nBob\n1\nd6\nd7\nz9\ng7\nd5\n\n",
+        );
+    }
+
+    #[test]
+    fn invalid_input_and_end_of_input() {
+        assert_transcript(
+            "invalid_input_and_end_of_input",
+            None,
+            "",
+            "\nAlice\nAlice\nBob\n3\nBob\nd7\nd7\nb1\nd6\n",
+        );
+    }
+
+    #[test]
+    fn flag_fall() {
+        // Without any time the first move is too late
+        assert_transcript("flag_fall", None, "time = 0+0", "Alice\nBob\n1\nd7\n\n");
+    }
+
+    #[test]
+    fn engine_closes_the_mill() {
+        // The engine plays white & takes a stone of the last three, Alice only enters her name
+        assert_transcript(
+            "engine_closes_the_mill",
+            Some("EWEWEEEWWEEEEEEEBEBEBEEE w 0 0"),
+            "player1.type = engine\nplayer1.level = 2\nwhite = 1",
+            "Alice\n\n",
+        );
+    }
+
+    #[test]
+    fn engine_evaluations_are_recorded() {
+        // The engine closes a mill & wins with its first move, as white or after a move of Alice as black
+        for (start, white, script, evaluations) in [
+            (
+                "EWEWEEEWWEEEEEEEBEBEBEEE w 0 0",
+                1,
+                "Alice\n\n",
+                [(1, engine::WIN_SCORE - 1)],
+            ),
+            (
+                "EBEBEEEBBEEEEEEEWEWEWEEE w 0 0",
+                2,
+                "Alice\nd3\nd2\n\n",
+                [(2, 1 - engine::WIN_SCORE)],
+            ),
+        ] {
+            let config = Config::parse(&format!("player1.type = engine\nplayer1.level = 2\nwhite = {white}")).unwrap();
+            let mut coordinator =
+                GameCoordinator::setup_from(start.parse().unwrap(), config, script.as_bytes(), io::sink()).unwrap();
+            coordinator.start_game();
+
+            assert_eq!(coordinator.record.evaluations, BTreeMap::from(evaluations), "{start}");
+        }
+    }
+
+    #[test]
+    fn blocked_engines_lose() {
+        // Bob places the last stone & blocks every stone of the engine, which isn't asked for a move then
+        let config = Config::parse("player1.type = engine\nplayer1.level = 1\nwhite = 1").unwrap();
+        let start = "EWBWBWBWEEEEEEEEEEEEEEEE b 0 1".parse().unwrap();
+        let mut coordinator = GameCoordinator::setup_from(start, config, "Bob\nd7\n\n".as_bytes(), io::sink()).unwrap();
+        coordinator.start_game();
+
+        assert_eq!(coordinator.record.result, GameResult::BlackWins);
+        assert_eq!(coordinator.record.termination, Termination::NoMovesLeft);
+    }
+
+    #[test]
+    fn best_of_three() {
+        // White wins each game from the position, so the colors swap & the third game decides
+        assert_transcript(
+            "best_of_three",
+            Some("EWEWEEEWWEEEEEEEBEBEBEEE w 0 0"),
+            "best_of = 3",
+            &format!("Alice\nBob\n1\n{}", "d6\nd7\nd5\n\n".repeat(3)),
+        );
+    }
+
+    #[test]
+    fn rematch_swaps_colors() {
+        let start = "EWEWEEEWWEEEEEEEBEBEBEEE w 0 0".parse().unwrap();
+        let script = "Alice\nBob\n1\nd6\nd7\nd5\n\ny\nd6\nd7\nd5\n\nn\n";
+        let mut coordinator =
+            GameCoordinator::setup_from(start, Config::default(), script.as_bytes(), io::sink()).unwrap();
+        coordinator.start_game();
+
+        assert_eq!(coordinator.record.white, "Bob");
+        assert_eq!(coordinator.record.start, Some(start));
+        assert_eq!(coordinator.score, Score { wins: (1, 1), draws: 0 });
+    }
+
+    #[test]
+    fn configured_names() {
+        // The configured names are the same, so player 2 is asked
+        let config = Config::parse("player1.name = Alice\nplayer2.name = Alice\nwhite = 2").unwrap();
+        let mut coordinator = GameCoordinator::setup(config, "Bob\n".as_bytes(), io::sink()).unwrap();
+        assert_eq!(coordinator.player_names, ("Alice".into(), "Bob".into()));
+        assert_eq!(coordinator.setup_player_colors(), Ok(true));
+    }
+
+    #[test]
+    fn clocks_are_recorded() {
+        let script = "Alice\nBob\n2\nd7\nd6\n";
+        let mut coordinator = GameCoordinator::setup(Config::default(), script.as_bytes(), io::sink()).unwrap();
+        coordinator.set_time_control("600+5/60".parse().unwrap());
+        coordinator.start_game();
+
+        let record = &coordinator.record;
+        assert_eq!(record.white, "Bob");
+        assert_eq!(record.time_control, Some("600+5/60".parse().unwrap()));
+        assert_eq!(record.clocks.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        // The increment is more than the time used by the script
+        assert!(record.clocks.values().all(|remaining| remaining.as_secs() == 604));
+        assert_eq!(coordinator.clock, record.clock());
+    }
+
+    #[test]
+    fn observed_events() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let start = "EWEWEEEWWEEEEEEEBEBEBEEE w 0 0".parse().unwrap();
+        let script = "Alice\nBob\n2\nd6\nd7\nd5\n\n";
+        let mut coordinator =
+            GameCoordinator::setup_from(start, Config::default(), script.as_bytes(), io::sink()).unwrap();
+        let observed = Rc::clone(&events);
+        coordinator.observe(move |event: &GameEvent| observed.borrow_mut().push(event.to_string()));
+        coordinator.start_game();
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                "Bob (white) against Alice (black) from EWEWEEEWWEEEEEEEBEBEBEEE w 0 0 - 0 1",
+                "White moved d6-d7",
+                "White closed a mill on a7, d7, g7",
+                "White took d5",
+                "Game over: 1-0 by too few stones",
+            ]
+        );
+
+        // Placements, the end of the set phase & input ending early
+        let start = "WWEEEEEEBBEEEEEEEEEEEEEE b 1 2".parse().unwrap();
+        let script = "Alice\nBob\n1\na1\nd1\ng1\n";
+        let mut coordinator =
+            GameCoordinator::setup_from(start, Config::default(), script.as_bytes(), io::sink()).unwrap();
+        let observed = Rc::clone(&events);
+        events.borrow_mut().clear();
+        coordinator.observe(move |event: &GameEvent| observed.borrow_mut().push(event.to_string()));
+        coordinator.start_game();
+
+        assert_eq!(
+            *events.borrow(),
+            [
+                "Alice (white) against Bob (black) from WWEEEEEEBBEEEEEEEEEEEEEE b 1 2 - 0 1",
+                "Black placed on a1",
+                "White placed on d1",
+                "Black placed on g1",
+                "White is in the jump phase",
+                "Black is in the move phase",
+                "Game over: * by unterminated",
+            ]
+        );
+    }
+
+    #[test]
+    fn start_positions() {
+        // Black moves first in the move phase, white takes with its third stone before black places
+        for (start, script, moves, position) in [
+            (
+                "WWWWEEEEBBBBEEEEEEEEEEEE b 0 0",
+                "Alice\nBob\n1\nd6\nd5\n",
+                1,
+                "WWWWEEEEBBBBEEEEEEEEEEEE b 0 0 - 0 1",
+            ),
+            (
+                "WWEEEEEWBBEEEEEEEEEEEEEE w 6 7 x 0 3",
+                "Alice\nBob\n1\nd6\n",
+                0,
+                "WWEEEEEWEBEEEEEEEEEEEEEE b 6 7 - 0 3",
+            ),
+        ] {
+            let mut coordinator =
+                GameCoordinator::setup_from(start.parse().unwrap(), Config::default(), script.as_bytes(), io::sink())
+                    .unwrap();
+            coordinator.start_game();
+
+            assert_eq!(coordinator.record.moves.len(), moves, "{start}");
+            assert_eq!(coordinator.record.start.unwrap().to_string(), position, "{start}");
+        }
+
+        // The hands of the pending capture are those before white placed the stone closing the mill
+        let start = "WWEEEEEWBBEEEEEEEEEEEEEE w 6 6 x 0 3".parse().unwrap();
+        let error = GameCoordinator::setup_from(start, Config::default(), "".as_bytes(), io::sink()).err();
+        assert!(error.unwrap().contains("stones in hand"));
+    }
+
+    #[test]
+    fn input_ends_during_setup() {
+        let mut output = Vec::new();
+        assert!(GameCoordinator::setup(Config::default(), "Alice\n".as_bytes(), &mut output).is_err());
+
+        let start = "WWEEEEEEBBEEEEEEEEEEEEEE b 6 7".parse().unwrap();
+        let error = GameCoordinator::setup_from(start, Config::default(), "".as_bytes(), &mut output).err();
+        assert_eq!(error.as_deref(), Some("The input ended before the game started."));
+    }
 }
@@ -1,71 +1,57 @@
-use std::io::{self, Write};
+use std::{
+    io::{BufRead, Write},
+    path::Path,
+    time::Instant,
+};
 
-use muehle::game::{painting::*, Field, PlayerColor};
+use muehle::game::{clock::format_clock, events::GameEvent, notation, painting::*, Field, PlayerColor};
 use smallvec::SmallVec;
 use smartstring::alias::CompactString;
 
-use super::{print_error, GamePhase};
+use super::{out, outln, EndOfInput, GamePhase};
 
-impl super::GameCoordinator {
+/// The closed mills & the taken stone
+type MillsAndTake = (SmallVec<[Field; 3]>, Field);
+
+impl<I: BufRead, O: Write> super::GameCoordinator<I, O> {
     /// Returns valid coordinates of the game field in A_G, 1-7 mapping. The coordinate is requested after printing out the message argument
     /// Loops & requests input until the provided input is valid. Handles ALL error cases.
     ///
     /// Handled extreme cases:
     /// - Input fails
     /// - Input is to short or to long
-    /// - first char is not \in 'A'-'G'
+    /// - first char is not \in 'A'-'G' (or 'a'-'g')
     /// - second char is not \in 1-7
-    pub fn get_field_coord_input(&self, message: &str) -> Field {
-        return loop {
-            print!("{}", message);
-            io::stdout().flush().unwrap();
-
-            let mut input_buffer = String::new();
+    /// - the field doesn't exist, like B1
+    ///
+    /// Entering `save <file>` saves the game instead, which defaults to `game.txt`. Engines enter their fields right away.
+    pub fn get_field_coord_input(&mut self, message: &str) -> Result<Field, EndOfInput> {
+        loop {
+            if let Some(field) = self.engine_input() {
+                outln!(self, "{message}{}", HIGHLIGHT.paint(format!("{}{}", field.0, field.1)));
+                break Ok(field);
+            }
+            let input_buffer = self.prompt(message)?;
+
+            if let Some(path) = input_buffer.strip_prefix("save") {
+                let path = match path.trim() {
+                    "" => "game.txt",
+                    path => path,
+                };
+                self.save_game(Path::new(path));
+                continue;
+            }
 
-            match io::stdin().read_line(&mut input_buffer) {
-                Ok(_) => {
-                    let input_buffer = input_buffer.trim();
-
-                    if input_buffer.len() < 2 {
-                        print_error("Provided input is to short.");
-                        continue;
-                    } else if 3 <= input_buffer.len() {
-                        print_error("Provided input is longer than 2 characters.")
-                    }
-
-                    // Parsing checks
-                    let row_char = match input_buffer[0..1].parse::<char>() {
-                        Ok(c) if ('A'..='G').contains(&c) => c.to_uppercase().next().unwrap(),
-                        Ok(_) => {
-                            print_error("Provided input character isn't between A - G.");
-                            continue;
-                        }
-                        Err(_) => {
-                            print_error("Input does't start with a letter representing a column.");
-                            continue;
-                        }
-                    };
-                    let column_char = match input_buffer[1..2].parse::<u8>() {
-                        Ok(n) if (1..=7).contains(&n) => n,
-                        Ok(_) => {
-                            print_error("Second input char is not 1 <= && < 8.");
-                            continue;
-                        }
-                        Err(_) => {
-                            print_error("Second input char is not a number. Input is ill formatted.");
-                            continue;
-                        }
-                    };
-
-                    break (row_char, column_char);
-                }
-                Err(error) => print_error(&format!("Error occurred processing input: {error}",)),
+            // Parsing checks, lower case columns are accepted too
+            match notation::parse_field(&input_buffer, 0) {
+                Ok(field) => break Ok(field),
+                Err(error) => self.print_error(&format!("{error}.")),
             }
-        };
+        }
     }
 
     /// Returns if mills were detected & returns them if so and prints them out
-    pub fn check_for_and_get_mils(&self, last_updated_field: Field) -> Option<SmallVec<[Field; 3]>> {
+    pub fn check_for_and_get_mils(&mut self, last_updated_field: Field) -> Option<SmallVec<[Field; 3]>> {
         let mills = self.play_field.get_mill_crossing(last_updated_field);
 
         // This hurts. And I'm not sure how to do better.
@@ -75,7 +61,8 @@ impl super::GameCoordinator {
             let field_1 = mills[0];
             let field_2 = mills[1];
             let field_3 = mills[2];
-            print!(
+            out!(
+                self,
                 "\n> Detected a mill for fields: {}!",
                 EMP.paint(format!(
                     "({}{}, {}{}, {}{})",
@@ -92,7 +79,8 @@ impl super::GameCoordinator {
             let field_4 = mills[3];
             let field_5 = mills[4];
             let field_6 = mills[5];
-            print!(
+            out!(
+                self,
                 "\n> Detected {} mills on {} and {}!!\n> Your opponent must be sleeping, be a 3 year old, or you must be testing extreme cases ;)",
                 EMP.paint("TWO"),
                 EMP.paint(format!(
@@ -110,65 +98,58 @@ impl super::GameCoordinator {
 
     /// Handles the mill cross-check of the last field a stone was set upon.
     /// Includes the user interaction part for selecting a valid field on the [PlayField].
+    /// Closing two mills at once takes only one stone, like in the engine & the solver.
     /// Handled extreme cases:
-    /// - ~~All stones on the play field are element of mills~~
-    /// TODO This is to weak. If the player e.g. has 3 stones & all are in a mill, it must be skipped too...
+    /// - All stones of the opponent are element of mills, one of them can be taken then
     ///
-    /// Returns true if a mill was detected for the [GamePhase] cases to trigger coordinative behavior.
+    /// Returns the detected mills & the taken stone for the [GamePhase] cases to trigger coordinative behavior.
     pub fn do_mills_interaction(
         &mut self,
         input_field: (char, u8),
         player_color: PlayerColor,
-    ) -> Option<SmallVec<[Field; 3]>> {
+    ) -> Result<Option<MillsAndTake>, EndOfInput> {
         if let Some(mills) = self.check_for_and_get_mils(input_field) {
+            self.notify(GameEvent::MillClosed {
+                color: player_color,
+                fields: mills.clone(),
+            });
             self.print_play_highlighted(Some(&mills));
+            let taken_field = self.take_stone_interaction(player_color)?;
+
+            Ok(Some((mills, taken_field)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Prompts to take a stone of the opponent until a valid one was chosen & returns it
+    pub fn take_stone_interaction(&mut self, player_color: PlayerColor) -> Result<Field, EndOfInput> {
+        loop {
+            let field_to_take = self.get_field_coord_input(self.strings().take_prompt)?;
 
-            // A piece of a extreme case:
-            let mut amount_of_mills = mills.len() / 3;
-            //let stones_in_mills = 0;
-            //for coord in FIELD_LUT {
-            // Every mill should be detected exactly 3 times
-            //stones_in_mills += self.play_field.get_mill_crossing(coord).len() / 3;
-            //}
-
-            //let (white_stones, black_stones) = self.play_field.amount_of_stones;
-            //let all_stones_in_mills = stones_in_mills as u32 == (white_stones + black_stones);
-
-            // While here are mill on the last set position left & not all stones are element of a mill: Prompt to take stones
-            while 0 < amount_of_mills
-            /*&& !all_stones_in_mills*/
-            {
-                let field_to_take = self.get_field_coord_input("> Enter the stone do you want to take: ");
-
-                match self.play_field.try_take(field_to_take, player_color) {
-                    Ok(_) => println!(
+            match self.play_field.try_take(field_to_take, player_color) {
+                Ok(_) => {
+                    outln!(
+                        self,
                         "> Successfully took stone on {}",
                         EMP.paint(format!("{}{}", field_to_take.0, field_to_take.1))
-                    ),
-                    Err(err) => {
-                        print_error(&format!("> Error occured taking stone: {}", err));
-                        continue;
-                    }
+                    );
+                    self.notify(GameEvent::StoneTaken {
+                        color: player_color,
+                        field: field_to_take,
+                    });
+                    break Ok(field_to_take);
                 }
-
-                amount_of_mills -= 1;
+                Err(err) => self.print_error(&format!("> Error occured taking stone: {}", err)),
             }
-
-            /*if all_stones_in_mills {
-                println!("> Detected as many stones on the play field as mills. There is nothing to take.");
-            }*/
-
-            Some(mills)
-        } else {
-            None
         }
     }
 
     /// Prints (depending of the state of [GameCoordinator]) out the current round, the state of the play field and messages for some phases of [GamePhase].
-    /// Also skips this print outs, if the provided [error_occurred] is true.
+    /// Also skips this print outs, if the provided [error_occurred] is true. Otherwise the clock of the player starts.
     /// Returns some convenient values needed in the game phases for coordination of the [PlayField].
     pub fn print_turn_header(
-        &self,
+        &mut self,
         phase: GamePhase,
         black_rounds_done: Option<u32>,
         highlight: &[Field],
@@ -178,41 +159,72 @@ impl super::GameCoordinator {
 
         // Print out the round and game field info, if no error occurred
         if !self.error_state {
-            println!("\n\n\t\t  ===============");
-            println!("\t\t  === {} ===", HIGHLIGHT.paint(format!("Round {}", self.round)));
-            println!("\t\t  ===============\n");
+            outln!(self, "\n\n\t\t  ===============");
+            outln!(
+                self,
+                "\t\t  === {} ===",
+                HIGHLIGHT.paint(format!("Round {}", self.round))
+            );
+            outln!(self, "\t\t  ===============\n");
 
             if let GamePhase::Set = phase {
-                println!(
+                outln!(
+                    self,
                     "> {}, it's your turn placing a {} stone!",
                     EMP.paint(player_name.as_str()),
                     HIGHLIGHT.paint(player_color)
                 );
                 let (stones_white, stones_black) = self.play_field.amount_of_stones;
-                println!(
+                outln!(
+                    self,
                     "\n> Amount of stones on the playfield: {}: {}, {}: {}",
-                    EMP.paint(&self.player_names.0),
+                    EMP.paint(&self.record.white),
                     HIGHLIGHT.paint(stones_white),
-                    EMP.paint(&self.player_names.1),
+                    EMP.paint(&self.record.black),
                     HIGHLIGHT.paint(stones_black)
                 );
-                println!(
+                outln!(
+                    self,
                     "> Stones left to set: {}",
                     HIGHLIGHT.paint(9 - black_rounds_done.unwrap())
                 );
             } else if let GamePhase::MoveAndJump = phase {
-                println!(
+                outln!(
+                    self,
                     "> {}, it's your turn making a move with {}!",
                     EMP.paint(player_name.as_str()),
                     HIGHLIGHT.paint(player_color)
                 );
             }
 
+            if let Some(clock) = self.clock {
+                outln!(
+                    self,
+                    "> Time left: {} {}, {} {}",
+                    EMP.paint(&self.record.white),
+                    HIGHLIGHT.paint(format_clock(clock.remaining.0)),
+                    EMP.paint(&self.record.black),
+                    HIGHLIGHT.paint(format_clock(clock.remaining.1))
+                );
+                if let Some(per_move) = clock
+                    .control
+                    .per_move
+                    .filter(|per_move| *per_move < clock.available(player_color))
+                {
+                    outln!(
+                        self,
+                        "> At most {} for this move.",
+                        HIGHLIGHT.paint(format_clock(per_move))
+                    );
+                }
+            }
+
             if !highlight.is_empty() {
                 self.print_play_highlighted(Some(highlight));
             } else {
                 self.print_play_highlighted(None);
             }
+            self.turn_started = Instant::now();
         }
         (player_color, player_name)
     }
@@ -0,0 +1,146 @@
+//! Several games of the same players as a match. After each game the score is shown & a rematch with swapped colors is
+//! offered, matches configured as best of N go on by themselves until they are decided. Rematches start from the same
+//! position as the first game, resumed games included.
+
+use std::{
+    cmp::Ordering,
+    io::{BufRead, Write},
+};
+
+use muehle::game::{
+    clock::Clock,
+    painting::*,
+    position::ExtendedPosition,
+    record::{GameRecord, GameResult},
+    state::PlayField,
+};
+
+use super::{outln, GameCoordinator, GamePhase};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    /// The games won by player 1 & 2
+    pub wins: (u32, u32),
+    pub draws: u32,
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins.0 + self.wins.1 + self.draws
+    }
+
+    /// Counts the result of a finished game, unfinished ones don't count
+    pub fn count(&mut self, result: GameResult, player_2_white: bool) {
+        match (result, !player_2_white) {
+            (GameResult::WhiteWins, true) | (GameResult::BlackWins, false) => self.wins.0 += 1,
+            (GameResult::WhiteWins, false) | (GameResult::BlackWins, true) => self.wins.1 += 1,
+            (GameResult::Draw, _) => self.draws += 1,
+            (GameResult::Ongoing, _) => {}
+        }
+    }
+
+    /// True once a player won more than half of the games or all games are played
+    pub fn decides(&self, best_of: u32) -> bool {
+        let needed = best_of / 2 + 1;
+        needed <= self.wins.0 || needed <= self.wins.1 || best_of <= self.games()
+    }
+}
+
+impl<I: BufRead, O: Write> GameCoordinator<I, O> {
+    /// Counts the finished game & prints the score
+    pub(super) fn count_game(&mut self) {
+        self.score.count(self.record.result, self.player_2_white);
+        let Score { wins, draws } = self.score;
+        outln!(
+            self,
+            "> Score: {} {} - {} {}, {} draws.",
+            EMP.paint(&self.player_names.0),
+            HIGHLIGHT.paint(wins.0),
+            HIGHLIGHT.paint(wins.1),
+            EMP.paint(&self.player_names.1),
+            HIGHLIGHT.paint(draws)
+        );
+    }
+
+    /// Sets up the next game if the match goes on: decided best of N matches end, otherwise the players are asked. The
+    /// end of the input ends the match too.
+    pub(super) fn rematch(&mut self, start: Option<ExtendedPosition>) -> bool {
+        match self.config.best_of {
+            Some(best_of) if self.score.decides(best_of) => return false,
+            Some(best_of) => outln!(
+                self,
+                "\n> Game {} of the best of {} match.",
+                HIGHLIGHT.paint(self.score.games() + 1),
+                HIGHLIGHT.paint(best_of)
+            ),
+            None => {
+                let Ok(answer) = self.prompt(self.strings().rematch_prompt) else {
+                    return false;
+                };
+                if !matches!(answer.to_lowercase().as_str(), "y" | "yes") {
+                    return false;
+                }
+                outln!(self);
+            }
+        }
+
+        // Player 2 plays white if player 1 did
+        self.config.player_2_white = Some(!self.player_2_white);
+        self.play_field = start.map_or_else(PlayField::default, |start| {
+            PlayField::try_from(start.position.board).expect("The start was checked before the first game")
+        });
+        self.record = GameRecord::new("", "");
+        self.record.start = start;
+        self.game_phase = GamePhase::Start;
+        self.error_state = false;
+        self.engine_input.clear();
+        if let Some(clock) = self.clock {
+            self.clock = Some(Clock::new(clock.control));
+        }
+        true
+    }
+
+    /// Announces the winner of the match, if any game was finished
+    pub(super) fn print_match_summary(&mut self) {
+        let Score { wins, .. } = self.score;
+        if self.score.games() == 0 {
+            return;
+        }
+        let summary = match wins.0.cmp(&wins.1) {
+            Ordering::Greater => format!(
+                "{} won the match {}-{}! Congratulations!",
+                self.player_names.0, wins.0, wins.1
+            ),
+            Ordering::Less => format!(
+                "{} won the match {}-{}! Congratulations!",
+                self.player_names.1, wins.1, wins.0
+            ),
+            Ordering::Equal => format!("The match ended in a tie at {}-{}.", wins.0, wins.1),
+        };
+        outln!(self, "> {}", EMP.paint(summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn best_of() {
+        let score = Score { wins: (2, 0), draws: 0 };
+        assert!(score.decides(3));
+        assert!(!score.decides(5));
+
+        // Draws count as games
+        let score = Score { wins: (1, 1), draws: 1 };
+        assert!(score.decides(3));
+        assert!(!Score::default().decides(1));
+
+        // Player 2 playing white won
+        let mut score = Score::default();
+        score.count(GameResult::WhiteWins, true);
+        assert_eq!(score.wins, (0, 1));
+        score.count(GameResult::WhiteWins, false);
+        assert_eq!(score.wins, (1, 1));
+    }
+}
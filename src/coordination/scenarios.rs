@@ -0,0 +1,132 @@
+//! Scenario tests of the interactive game. Each file in `scenarios/` feeds a prepared sequence of inputs into the game
+//! loop & states what has to come out of it. Lines start with a keyword, lines starting with `#` are comments:
+//!
+//! ```text
+//! # White closes a mill & black is left with two stones
+//! start EWEWEEEWWEEEEEEEBEBEBEEE w 0 0     optional, the game starts on the empty play field otherwise
+//! config white = 1                         a line of the config file, repeatable
+//! input Alice                              a line of input, `input` alone enters an empty line
+//! expect Successfully took stone on D5     printed in this order, colors aside
+//! board WWWEEEEWEEEEEEEEEEEEBEEE           the board code of the play field at the end
+//! winner Alice                             the winner of the last game, none for unfinished games
+//! ```
+//!
+//! The input may end before the game does, the game stops then like with a closed stdin.
+
+use std::{fs, path::Path};
+
+use muehle::game::{
+    efficient_state::moves::CODED_FIELDS,
+    position::ExtendedPosition,
+    record::GameResult,
+    state::{representation::types::FieldState, PlayField},
+};
+
+use super::{tests::strip_colors, GameCoordinator};
+use crate::config::Config;
+
+#[derive(Default)]
+struct Scenario {
+    start: Option<ExtendedPosition>,
+    config: String,
+    input: String,
+    expected: Vec<String>,
+    board: Option<String>,
+    winner: Option<String>,
+}
+
+impl Scenario {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut scenario = Scenario::default();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim();
+            match keyword {
+                "start" => scenario.start = Some(rest.parse().map_err(|error| format!("Line {}: {error}", index + 1))?),
+                "config" => scenario.config += &format!("{rest}\n"),
+                "input" => scenario.input += &format!("{rest}\n"),
+                "expect" => scenario.expected.push(rest.to_string()),
+                "board" => scenario.board = Some(rest.to_string()),
+                "winner" => scenario.winner = Some(rest.to_string()),
+                _ => return Err(format!("Line {}: Unknown keyword '{keyword}'", index + 1)),
+            }
+        }
+        Ok(scenario)
+    }
+
+    /// Plays the scenario & returns the first expectation which wasn't met
+    fn run(&self) -> Result<(), String> {
+        let config = Config::parse(&self.config).map_err(|error| error.to_string())?;
+        let mut output = Vec::new();
+        let coordinator = match self.start {
+            Some(start) => GameCoordinator::setup_from(start, config, self.input.as_bytes(), &mut output),
+            None => GameCoordinator::setup(config, self.input.as_bytes(), &mut output)
+                .map_err(|_| "The input ended before the game started.".to_string()),
+        };
+        let mut coordinator = coordinator?;
+        coordinator.start_game();
+
+        let board = board_code(&coordinator.play_field);
+        let record = &coordinator.record;
+        let winner = match record.result {
+            GameResult::WhiteWins => record.white.as_str(),
+            GameResult::BlackWins => record.black.as_str(),
+            GameResult::Draw => "draw",
+            GameResult::Ongoing => "none",
+        }
+        .to_string();
+        drop(coordinator);
+
+        let transcript = strip_colors(&String::from_utf8(output).unwrap());
+        let mut rest = transcript.as_str();
+        for expected in &self.expected {
+            match rest.find(expected.as_str()) {
+                Some(index) => rest = &rest[index + expected.len()..],
+                None => return Err(format!("'{expected}' wasn't printed (in this order):\n{transcript}")),
+            }
+        }
+        if let Some(expected) = self.board.as_ref().filter(|expected| **expected != board) {
+            return Err(format!("The board is {board}, not {expected}:\n{transcript}"));
+        }
+        if let Some(expected) = self.winner.as_ref().filter(|expected| **expected != winner) {
+            return Err(format!("The winner is {winner}, not {expected}:\n{transcript}"));
+        }
+        Ok(())
+    }
+}
+
+/// The board code of the play field, in the order of [CODED_FIELDS]
+fn board_code(play_field: &PlayField) -> String {
+    CODED_FIELDS
+        .iter()
+        .map(|field| match play_field.get_status_of(*field).unwrap() {
+            FieldState::White => 'W',
+            FieldState::Black => 'B',
+            FieldState::Free => 'E',
+        })
+        .collect()
+}
+
+#[test]
+fn scenarios() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/coordination/scenarios");
+    let mut paths: Vec<_> = fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let failures: Vec<String> = paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy();
+            let result = Scenario::parse(&fs::read_to_string(path).unwrap()).and_then(|scenario| scenario.run());
+            result.err().map(|error| format!("Scenario {name}: {error}"))
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
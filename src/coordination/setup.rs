@@ -1,108 +1,119 @@
-use std::io::{self, Write};
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Write},
+    time::Instant,
+};
 
-use muehle::game::{painting::*, state::PlayField};
+use muehle::engine::EnginePlayer;
+use muehle::game::{painting::*, position::Position, record::GameRecord, state::PlayField};
+use smartstring::alias::CompactString;
 
-use crate::coordination::print_error;
+use super::{outln, EndOfInput, GameCoordinator, GamePhase, Score};
+use crate::config::Config;
 
-use super::{GameCoordinator, GamePhase};
-
-impl GameCoordinator {
-    pub fn setup() -> Self {
-        let mut player_1: Option<String> = None;
-        let player_2: Option<String>;
-        let mut current_player_assigned_to = 1;
-
-        loop {
-            print!(
-                "> Ok {}, please enter your username: ",
-                EMP.paint(format!("Player {}", current_player_assigned_to))
-            );
-            io::stdout().flush().unwrap();
-
-            let mut input_buffer = String::new();
-            match io::stdin().read_line(&mut input_buffer) {
-                Ok(_) => {
-                    let input_buffer = input_buffer.trim();
-
-                    if input_buffer.is_empty() {
-                        print_error("Please enter a name which actually holds some characters.");
-                        continue;
-                    }
-
-                    if player_1.is_none() {
-                        player_1 = Some(input_buffer.to_string());
-                        current_player_assigned_to += 1;
-
-                        // .clone().unwrap() looks like bad library design for me...
-                        println!("> Here we go, {}!", EMP.paint(player_1.clone().unwrap()));
-                    } else {
-                        if player_1.clone().unwrap() == input_buffer {
-                            print_error("Player are the same.");
-                            continue;
-                        }
-                        player_2 = Some(input_buffer.to_string());
-
-                        println!("> Here we go, {}!", EMP.paint(player_2.clone().unwrap()));
-                        break;
-                    }
-                }
-                Err(e) => print_error(&format!("Error evaluating your input: {}", e)),
-            }
-        }
-        println!();
-
-        GameCoordinator {
+impl<I: BufRead, O: Write> GameCoordinator<I, O> {
+    /// Asks for the names, unless they are configured
+    pub fn setup(config: Config, input: I, output: O) -> Result<Self, EndOfInput> {
+        let time_control = config.time_control;
+        let mut coordinator = GameCoordinator {
             play_field: PlayField::default(),
-            player_names: (
-                smartstring::alias::CompactString::from(player_1.unwrap()),
-                smartstring::alias::CompactString::from(player_2.unwrap()),
-            ),
+            // Set when the game starts
+            position: Position::default(),
+            // Asked for right below
+            player_names: Default::default(),
             round: 0,
             game_phase: GamePhase::Start,
             turn: false,
+            // Chosen when the game starts
+            player_2_white: false,
             error_state: false,
+            // The colors are chosen when the game starts
+            record: GameRecord::new("", ""),
+            input,
+            output,
+            observers: Vec::new(),
+            clock: None,
+            turn_started: Instant::now(),
+            config,
+            engine_input: VecDeque::new(),
+            engine: EnginePlayer::default(),
+            score: Score::default(),
+        };
+        if let Some(control) = time_control {
+            coordinator.set_time_control(control);
         }
+
+        let player_1 = coordinator.setup_player_name(1, None)?;
+        let player_2 = coordinator.setup_player_name(2, Some(&player_1))?;
+        outln!(coordinator);
+
+        coordinator.player_names = (CompactString::from(player_1), CompactString::from(player_2));
+        Ok(coordinator)
+    }
+
+    /// The configured name of player 1 or 2, otherwise asks until a name different from the other one is entered
+    fn setup_player_name(&mut self, player: usize, other_name: Option<&str>) -> Result<String, EndOfInput> {
+        let configured = self
+            .config
+            .name_of(player)
+            .filter(|name| Some(name.as_str()) != other_name);
+
+        let name = match configured {
+            Some(name) => name,
+            None => loop {
+                let input_buffer = self.prompt(&format!(
+                    "> Ok {}, please enter your username: ",
+                    EMP.paint(format!("Player {}", player))
+                ))?;
+
+                if input_buffer.is_empty() {
+                    self.print_error("Please enter a name which actually holds some characters.");
+                } else if Some(input_buffer.as_str()) == other_name {
+                    self.print_error("Player are the same.");
+                } else {
+                    break input_buffer;
+                }
+            },
+        };
+        outln!(self, "> Here we go, {}!", EMP.paint(&name));
+        Ok(name)
     }
 
     /// Returns the player playing white, coded as 0 for player 1, 1 for player 2
-    pub fn setup_player_colors(&self) -> bool {
+    pub fn setup_player_colors(&mut self) -> Result<bool, EndOfInput> {
+        if let Some(player_2_white) = self.config.player_2_white {
+            return Ok(player_2_white);
+        }
         let error_message = "Input must either be 1, 2 or a players name. Please try again.";
 
-        return loop {
-            println!(
+        loop {
+            outln!(
+                self,
                 "> Which player wants to play with the {} >>{}<<?",
                 HIGHLIGHT.paint("white stones"),
                 HIGHLIGHT.paint(muehle::game::PlayerColor::White)
             );
-            print!(
+            let input_buffer = self.prompt(&format!(
                 "> Please enter a {} or the {}: ",
                 EMP.paint("players name"),
                 EMP.paint("player's number")
-            );
-            io::stdout().flush().unwrap();
+            ))?;
 
-            let mut input_buffer = String::new();
-            match io::stdin().read_line(&mut input_buffer) {
-                Ok(_) => {
-                    let input_buffer = input_buffer.trim();
-                    // Player 0/ 1 shall play white
-                    if input_buffer == self.player_names.0 {
-                        break false;
-                    // Player 1/ 2 shall play white
-                    } else if input_buffer == self.player_names.1 {
-                        break true;
-                    } else if let Ok(int) = input_buffer.parse::<i32>() {
-                        if !(1..3).contains(&int) {
-                            print_error(error_message);
-                        } else {
-                            break int != 1;
-                        }
-                    } else {
-                        print_error(error_message);
-                    }
+            // Player 0/ 1 shall play white
+            if input_buffer == self.player_names.0 {
+                break Ok(false);
+            // Player 1/ 2 shall play white
+            } else if input_buffer == self.player_names.1 {
+                break Ok(true);
+            } else if let Ok(int) = input_buffer.parse::<i32>() {
+                if !(1..3).contains(&int) {
+                    self.print_error(error_message);
+                } else {
+                    break Ok(int != 1);
                 }
-                Err(error) => print_error(&format!("> Error processing input: {}\n", error)),
+            } else {
+                self.print_error(error_message);
             }
-        };
+        }
     }
 }
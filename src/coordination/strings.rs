@@ -0,0 +1,32 @@
+//! The fixed prompts & messages of the game, one table per [Language]. Messages with names or fields in them are still
+//! written in the game loop.
+
+use crate::config::Language;
+
+pub struct Strings {
+    pub starting_game: &'static str,
+    pub place_prompt: &'static str,
+    pub move_start_prompt: &'static str,
+    pub move_target_prompt: &'static str,
+    pub take_prompt: &'static str,
+    pub save_prompt: &'static str,
+    pub rematch_prompt: &'static str,
+    pub drawn_by_move_limit: &'static str,
+}
+
+const ENGLISH: Strings = Strings {
+    starting_game: "> Starting the game!",
+    place_prompt: "> Enter a field a stone should be placed on: ",
+    move_start_prompt: "> Enter the stone you want to move: ",
+    move_target_prompt: "> Enter it's target position: ",
+    take_prompt: "> Enter the stone do you want to take: ",
+    save_prompt: "> Enter a file name to save the game, or nothing to skip: ",
+    rematch_prompt: "> Play a rematch with swapped colors? (y/n): ",
+    drawn_by_move_limit: "> No stone was taken for too long. The game is drawn.",
+};
+
+pub fn of(language: Language) -> &'static Strings {
+    match language {
+        Language::English => &ENGLISH,
+    }
+}
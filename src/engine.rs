@@ -0,0 +1,301 @@
+//! A computer player searching the game tree of [Position]s with alpha-beta pruning. The leaves are judged by
+//! [evaluate], which is based on [EfficientPlayField::get_move_triple] of both players.
+
+pub mod book;
+pub mod protocol;
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::game::{efficient_state::moves::EfficientMove, position::Position};
+
+use self::book::OpeningBook;
+
+/// Score of a won position, reduced by the amount of plies it takes to win
+pub const WIN_SCORE: i32 = 1_000_000;
+
+/// Judges the position from the view of the player on turn, the higher the better
+pub fn evaluate(position: &Position) -> i32 {
+    let color = position.side_to_move;
+    let mut board = position.board;
+
+    let (moves, moves_to_mill, stones_to_take) = board.get_move_triple(color);
+    let (opponents_moves, opponents_moves_to_mill, opponents_stones_to_take) = board.get_move_triple(!color);
+
+    let stones = (position.stones_on_board_of(color) + position.stones_in_hand_of(color)) as i32;
+    let opponents_stones = (position.stones_on_board_of(!color) + position.stones_in_hand_of(!color)) as i32;
+
+    1000 * (stones - opponents_stones)
+        + 10 * (moves as i32 - opponents_moves as i32)
+        + 50 * (moves_to_mill as i32 - opponents_moves_to_mill as i32)
+        + 5 * (stones_to_take as i32 - opponents_stones_to_take as i32)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchResult {
+    /// None if the player on turn can't move anymore
+    pub best_move: Option<EfficientMove>,
+    pub score: i32,
+    pub nodes: u64,
+}
+
+/// Bounds of [search_with_limits], which ends at the first one reached. Without any it searches until it's stopped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub time: Option<Duration>,
+}
+
+/// Reported after every finished depth of [search_with_limits]
+#[derive(Debug, Clone, Copy)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub result: SearchResult,
+    /// Since the start of the search
+    pub time: Duration,
+}
+
+/// The deepest depth [search_with_limits] goes without depth limit
+pub const MAX_DEPTH: u8 = 64;
+
+/// Searches `depth` plies ahead and returns the best move for the player on turn
+pub fn search(position: &Position, depth: u8) -> SearchResult {
+    let never_stopped = AtomicBool::new(false);
+    let mut bounds = Bounds {
+        stop: &never_stopped,
+        deadline: None,
+        nodes: 0,
+    };
+    search_depth(position, depth, &mut bounds).unwrap()
+}
+
+/// Searches deeper & deeper until a limit is reached or `stop` is set & returns the result of the deepest finished
+/// depth. If not even depth 1 finished, the first move which would have been searched is returned.
+pub fn search_with_limits(
+    position: &Position,
+    limits: SearchLimits,
+    stop: &AtomicBool,
+    mut on_depth_finished: impl FnMut(&SearchInfo),
+) -> SearchResult {
+    let start = Instant::now();
+    let mut bounds = Bounds {
+        stop,
+        deadline: limits.time.map(|time| start + time),
+        nodes: 0,
+    };
+    let mut best = SearchResult {
+        best_move: ordered_moves(position).first().copied(),
+        score: evaluate(position),
+        nodes: 0,
+    };
+    if best.best_move.is_none() {
+        // Lost already
+        best.score = -WIN_SCORE;
+        return best;
+    }
+
+    for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH) {
+        match search_depth(position, depth, &mut bounds) {
+            Some(result) => best = result,
+            None => break,
+        }
+        on_depth_finished(&SearchInfo {
+            depth,
+            result: best,
+            time: start.elapsed(),
+        });
+
+        // The end of the game is in sight
+        if WIN_SCORE - (MAX_DEPTH as i32) < best.score.abs() {
+            break;
+        }
+    }
+    best.nodes = bounds.nodes;
+    best
+}
+
+/// When a search has to give up
+struct Bounds<'a> {
+    stop: &'a AtomicBool,
+    deadline: Option<Instant>,
+    nodes: u64,
+}
+
+impl Bounds<'_> {
+    fn reached(&self) -> bool {
+        // The clock is only read every few nodes, it's slow compared to a node
+        self.stop.load(Ordering::Relaxed)
+            || (self.nodes.is_multiple_of(1024) && self.deadline.is_some_and(|deadline| deadline <= Instant::now()))
+    }
+}
+
+/// None if the bounds were reached before the depth was searched completely
+fn search_depth(position: &Position, depth: u8, bounds: &mut Bounds) -> Option<SearchResult> {
+    let nodes_before = bounds.nodes;
+    let mut best_move = None;
+    let mut alpha = -WIN_SCORE - 1;
+
+    for legal_move in ordered_moves(position) {
+        let score = -negamax(
+            &position.after(legal_move),
+            depth.saturating_sub(1),
+            -WIN_SCORE - 1,
+            -alpha,
+            1,
+            bounds,
+        )?;
+        if best_move.is_none() || alpha < score {
+            alpha = score;
+            best_move = Some(legal_move);
+        }
+    }
+
+    Some(SearchResult {
+        best_move,
+        score: alpha,
+        nodes: bounds.nodes - nodes_before,
+    })
+}
+
+fn negamax(position: &Position, depth: u8, mut alpha: i32, beta: i32, ply: i32, bounds: &mut Bounds) -> Option<i32> {
+    bounds.nodes += 1;
+    if bounds.reached() {
+        return None;
+    }
+
+    if position.winner().is_some() {
+        // The player on turn lost, the earlier the worse
+        return Some(-(WIN_SCORE - ply));
+    } else if depth == 0 {
+        return Some(evaluate(position));
+    }
+
+    for legal_move in ordered_moves(position) {
+        let score = -negamax(&position.after(legal_move), depth - 1, -beta, -alpha, ply + 1, bounds)?;
+        if alpha < score {
+            alpha = score;
+            if beta <= alpha {
+                break;
+            }
+        }
+    }
+    Some(alpha)
+}
+
+/// Moves taking a stone are tried first, they cause the most cutoffs
+fn ordered_moves(position: &Position) -> Vec<EfficientMove> {
+    let mut moves = position.legal_moves();
+    moves.sort_by_key(|legal_move| legal_move.take.is_none());
+    moves
+}
+
+/// A computer player, which plays from its opening book as long as it knows the position & searches otherwise. All
+/// engine moves of the game, the protocol & the HTTP hints are chosen by it.
+#[derive(Debug, Clone, Default)]
+pub struct EnginePlayer {
+    pub book: Option<OpeningBook>,
+}
+
+impl EnginePlayer {
+    pub fn new(book: Option<OpeningBook>) -> Self {
+        EnginePlayer { book }
+    }
+
+    /// The book move of the position, scored by [evaluate] as nothing was searched
+    fn book_move(&self, position: &Position) -> Option<SearchResult> {
+        let book_move = self.book.as_ref()?.lookup(position)?;
+        Some(SearchResult {
+            best_move: Some(book_move),
+            score: evaluate(position),
+            nodes: 0,
+        })
+    }
+
+    /// Like [search_with_limits], but the book move is played without searching if there is one
+    pub fn choose_move(
+        &self,
+        position: &Position,
+        limits: SearchLimits,
+        stop: &AtomicBool,
+        on_depth_finished: impl FnMut(&SearchInfo),
+    ) -> SearchResult {
+        self.book_move(position)
+            .unwrap_or_else(|| search_with_limits(position, limits, stop, on_depth_finished))
+    }
+
+    /// Like [search], but the book move is played without searching if there is one
+    pub fn choose_move_at_depth(&self, position: &Position, depth: u8) -> SearchResult {
+        self.book_move(position).unwrap_or_else(|| search(position, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{efficient_state::EfficientPlayField, PlayerColor};
+
+    #[test]
+    fn takes_the_last_stone() {
+        // White jumps into a mill, black has only 3 stones left
+        let position = Position {
+            board: EfficientPlayField::from_coded("WEEEEEEWEEEEEEEWBBBEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (0, 0),
+        };
+
+        let result = search(&position, 2);
+        let best_move = result.best_move.unwrap();
+        assert_eq!(best_move.target, 1);
+        assert!(best_move.take.is_some());
+        assert_eq!(result.score, WIN_SCORE - 1);
+    }
+
+    #[test]
+    fn search_with_limits_deepens() {
+        let position = Position::default();
+        let stop = AtomicBool::new(false);
+        let mut depths = Vec::new();
+
+        let limits = SearchLimits {
+            depth: Some(3),
+            time: None,
+        };
+        let result = search_with_limits(&position, limits, &stop, |info| depths.push(info.depth));
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(result.score, search(&position, 3).score);
+
+        // Stopped before the first depth finished, still a legal move is returned
+        stop.store(true, Ordering::Relaxed);
+        let result = search_with_limits(&position, SearchLimits::default(), &stop, |_| {
+            panic!("nothing finishes")
+        });
+        assert!(position.legal_moves().contains(&result.best_move.unwrap()));
+
+        let stop = AtomicBool::new(false);
+        let limits = SearchLimits {
+            depth: None,
+            time: Some(Duration::from_millis(50)),
+        };
+        let start = Instant::now();
+        search_with_limits(&position, limits, &stop, |_| {});
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn closes_mill_in_set_phase() {
+        let position = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBEEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (7, 7),
+        };
+
+        let chosen = EnginePlayer::default()
+            .choose_move_at_depth(&position, 1)
+            .best_move
+            .unwrap();
+        assert_eq!(chosen.target, 7);
+        assert!(chosen.take.is_some());
+    }
+}
@@ -0,0 +1,477 @@
+//! An opening book for the set phase: it maps canonical set-phase positions to recommended placements with the
+//! statistics of the games they were played in. All symmetric positions share one entry, the moves are stored in the
+//! orientation of the canonical form.
+//!
+//! Text format, one placement per line, `#` starts a comment:
+//!
+//! ```text
+//! # board                  side  in hand  move   wins draws losses
+//! EEEEEEEEEEEEEEEEEEEEEEEE W     9 9      d7     12   30    8
+//! WWEEEEEEBBEEEEEEEEEEEEEE W     7 7      a7xd6  3    0     0
+//! ```
+//!
+//! The statistics are counted from the view of the side to move. Boards don't need to be canonical when edited by hand,
+//! they are converted to their canonical form while parsing.
+
+use std::{collections::HashMap, fmt::Display, fs, io, path::Path};
+
+use crate::game::{
+    efficient_state::{moves::EfficientMove, symmetry::Symmetry, EfficientPlayField},
+    notation::Move,
+    position::Position,
+    PlayerColor,
+};
+
+use super::search;
+
+/// Games of the self-play longer than this are counted as draw
+pub const MAX_SELF_PLAY_PLIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BookKey {
+    /// Always in canonical form
+    pub board: EfficientPlayField,
+    pub side_to_move: PlayerColor,
+    pub stones_in_hand: (u8, u8),
+}
+
+/// A placement in the orientation of the canonical board & how the games went on after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    pub target: u8,
+    pub take: Option<u8>,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl BookMove {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of the possible points, a draw counting half
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+}
+
+/// The result of a game from the view of one player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Debug)]
+pub struct BookParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for BookParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error in opening book line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OpeningBook {
+    entries: HashMap<BookKey, Vec<BookMove>>,
+}
+
+impl OpeningBook {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the key of the position & the symmetry mapping the position onto it
+    pub fn key_of(position: &Position) -> (BookKey, Symmetry) {
+        let (board, symmetry) = position.board.get_canonical_form_with_symmetry();
+        let key = BookKey {
+            board,
+            side_to_move: position.side_to_move,
+            stones_in_hand: position.stones_in_hand,
+        };
+        (key, symmetry)
+    }
+
+    /// Counts the outcome of a game for the placement played in the position
+    pub fn record(&mut self, position: &Position, placement: EfficientMove, outcome: Outcome) {
+        self.record_n(position, placement, outcome, 1);
+    }
+
+    /// Counts the outcome of `amount` games at once, like calling [OpeningBook::record] `amount` times. The counts stop
+    /// at [u32::MAX] instead of overflowing.
+    pub fn record_n(&mut self, position: &Position, placement: EfficientMove, outcome: Outcome, amount: u32) {
+        assert!(placement.start.is_none(), "The opening book only holds placements");
+
+        // Symmetric boards like the empty one are mapped onto their canonical form by several symmetries, the
+        // placements they map onto each other must share an entry
+        let (key, _) = Self::key_of(position);
+        let (target, take) = Symmetry::all()
+            .filter(|symmetry| symmetry.apply(&position.board) == key.board)
+            .map(|symmetry| {
+                (
+                    symmetry.map_field(placement.target as usize) as u8,
+                    placement.take.map(|take| symmetry.map_field(take as usize) as u8),
+                )
+            })
+            .min()
+            .unwrap();
+
+        let book_moves = self.entries.entry(key).or_default();
+        let book_move = match book_moves
+            .iter_mut()
+            .find(|book_move| book_move.target == target && book_move.take == take)
+        {
+            Some(book_move) => book_move,
+            None => {
+                book_moves.push(BookMove {
+                    target,
+                    take,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                });
+                book_moves.last_mut().unwrap()
+            }
+        };
+
+        match outcome {
+            Outcome::Win => book_move.wins = book_move.wins.saturating_add(amount),
+            Outcome::Draw => book_move.draws = book_move.draws.saturating_add(amount),
+            Outcome::Loss => book_move.losses = book_move.losses.saturating_add(amount),
+        }
+    }
+
+    /// Returns the book moves of the position, mapped onto the orientation of the position
+    pub fn moves_of(&self, position: &Position) -> Vec<(EfficientMove, BookMove)> {
+        let (key, symmetry) = Self::key_of(position);
+
+        self.entries
+            .get(&key)
+            .map(|book_moves| {
+                book_moves
+                    .iter()
+                    .map(|book_move| {
+                        let placement = EfficientMove {
+                            start: None,
+                            target: symmetry.unmap_field(book_move.target as usize) as u8,
+                            take: book_move.take.map(|take| symmetry.unmap_field(take as usize) as u8),
+                        };
+                        (placement, *book_move)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the recommended placement: the one with the best score, more games played break ties
+    pub fn lookup(&self, position: &Position) -> Option<EfficientMove> {
+        if !position.is_set_phase() {
+            return None;
+        }
+
+        self.moves_of(position)
+            .into_iter()
+            .filter(|(placement, _)| position.legal_moves().contains(placement))
+            .max_by(|(_, a), (_, b)| a.score().total_cmp(&b.score()).then(a.games().cmp(&b.games())))
+            .map(|(placement, _)| placement)
+    }
+
+    /// Plays `games` games of the engine against itself & records all placements. To explore different openings, every
+    /// placement is chosen randomly with a chance of one in `exploration`.
+    pub fn build_from_self_play(&mut self, games: usize, depth: u8, exploration: u32, seed: u64) {
+        let mut random = XorShift(seed.max(1));
+
+        for _ in 0..games {
+            let mut position = Position::default();
+            let mut placements = Vec::new();
+
+            let mut winner = None;
+            for _ in 0..MAX_SELF_PLAY_PLIES {
+                if let Some(color) = position.winner() {
+                    winner = Some(color);
+                    break;
+                }
+
+                let chosen = if position.is_set_phase() && random.next().is_multiple_of(exploration.max(1) as u64) {
+                    let legal_moves = position.legal_moves();
+                    legal_moves[random.next() as usize % legal_moves.len()]
+                } else {
+                    search(&position, depth).best_move.unwrap()
+                };
+
+                if position.is_set_phase() {
+                    placements.push((position, chosen));
+                }
+                position.play(chosen);
+            }
+
+            for (position, placement) in placements {
+                let outcome = match winner {
+                    None => Outcome::Draw,
+                    Some(color) if color == position.side_to_move => Outcome::Win,
+                    Some(_) => Outcome::Loss,
+                };
+                self.record(&position, placement, outcome);
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, BookParseError> {
+        let mut book = OpeningBook::default();
+
+        for (line_index, line) in text.lines().enumerate() {
+            let error = |message: String| BookParseError {
+                line: line_index + 1,
+                message,
+            };
+
+            let content = line.split('#').next().unwrap().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let columns: Vec<&str> = content.split_whitespace().collect();
+            if columns.len() != 8 {
+                return Err(error(format!("Expected 8 columns, found {}", columns.len())));
+            }
+
+            if columns[0].len() != 24 || !columns[0].chars().all(|c| matches!(c, 'E' | 'W' | 'B')) {
+                return Err(error("The board must consist of 24 characters E, W or B".to_string()));
+            }
+            let board = EfficientPlayField::from_coded(columns[0]);
+
+            let side_to_move = match columns[1] {
+                "W" | "w" => PlayerColor::White,
+                "B" | "b" => PlayerColor::Black,
+                other => return Err(error(format!("Unknown side to move '{other}'"))),
+            };
+
+            let mut numbers = [0u32; 5];
+            for (number, column) in numbers.iter_mut().zip([2, 3, 5, 6, 7]) {
+                *number = columns[column]
+                    .parse()
+                    .map_err(|_| error(format!("'{}' is not a number", columns[column])))?;
+            }
+            if 9 < numbers[0] || 9 < numbers[1] {
+                return Err(error("At most 9 stones can be in hand".to_string()));
+            }
+
+            let position = Position {
+                board,
+                side_to_move,
+                stones_in_hand: (numbers[0] as u8, numbers[1] as u8),
+            };
+            position
+                .check_stone_counts()
+                .map_err(|message| error(format!("Impossible position: {message}")))?;
+            let placement = match columns[4].parse::<Move>() {
+                Ok(parsed) if parsed.start.is_none() => parsed.to_efficient().unwrap(),
+                Ok(_) => return Err(error(format!("'{}' isn't a placement", columns[4]))),
+                Err(notation_error) => return Err(error(notation_error.to_string())),
+            };
+            if !position.legal_moves().contains(&placement) {
+                return Err(error(format!("Move '{}' isn't legal in this position", columns[4])));
+            }
+
+            for (outcome, amount) in [Outcome::Win, Outcome::Draw, Outcome::Loss]
+                .into_iter()
+                .zip(&numbers[2..])
+            {
+                if *amount != 0 {
+                    book.record_n(&position, placement, outcome, *amount);
+                }
+            }
+        }
+        Ok(book)
+    }
+
+    /// Returns the book in its text format, sorted so the output is stable
+    pub fn to_text(&self) -> String {
+        let mut lines: Vec<String> = self
+            .entries
+            .iter()
+            .flat_map(|(key, book_moves)| {
+                book_moves.iter().map(move |book_move| {
+                    format!(
+                        "{} {} {} {} {} {} {} {}",
+                        key.board.to_string_representation(),
+                        match key.side_to_move {
+                            PlayerColor::White => 'W',
+                            PlayerColor::Black => 'B',
+                        },
+                        key.stones_in_hand.0,
+                        key.stones_in_hand.1,
+                        EfficientMove {
+                            start: None,
+                            target: book_move.target,
+                            take: book_move.take
+                        },
+                        book_move.wins,
+                        book_move.draws,
+                        book_move.losses
+                    )
+                })
+            })
+            .collect();
+
+        // Positions with more stones in hand first, so the book reads from the start of the game on
+        lines.sort_by(|a, b| {
+            let in_hand =
+                |line: &str| -> u32 { line.split(' ').skip(2).take(2).map(|n| n.parse::<u32>().unwrap()).sum() };
+            in_hand(b).cmp(&in_hand(a)).then(a.cmp(b))
+        });
+
+        let mut text = String::from("# board                  side in-hand move wins draws losses\n");
+        for line in lines {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        OpeningBook::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+    }
+
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+/// Small pseudo random number generator for the self-play, so no dependency is needed for it
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symmetric_positions_share_entries() {
+        let mut book = OpeningBook::default();
+        let start = Position::default();
+
+        // The four outer corners are symmetric to each other
+        for corner in [1, 3, 5, 7] {
+            let placement = EfficientMove {
+                start: None,
+                target: corner,
+                take: None,
+            };
+            book.record(&start, placement, Outcome::Draw);
+        }
+
+        let book_moves = book.moves_of(&start);
+        assert_eq!(book.len(), 1);
+        assert_eq!(book_moves.len(), 1);
+        assert_eq!(book_moves[0].1.draws, 4);
+    }
+
+    #[test]
+    fn lookup_maps_moves_back() {
+        let position = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBEEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (7, 7),
+        };
+        let closing_mill = EfficientMove {
+            start: None,
+            target: 7,
+            take: Some(8),
+        };
+
+        let mut book = OpeningBook::default();
+        book.record(&position, closing_mill, Outcome::Win);
+
+        // The same position mirrored: white on D7 & A7, black on D6 & B6
+        let mirrored = Position {
+            board: EfficientPlayField::from_coded("WEEEEEEWBEEEEEEBEEEEEEEE"),
+            ..position
+        };
+        let expected = EfficientMove {
+            start: None,
+            target: 1,
+            take: Some(8),
+        };
+        assert_eq!(book.lookup(&position), Some(closing_mill));
+        assert_eq!(book.lookup(&mirrored), Some(expected));
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let text = "\
+            # comment\n\
+            EEEEEEEEEEEEEEEEEEEEEEEE W 9 9 D7 1 2 3\n\
+            WWEEEEEEBBEEEEEEEEEEEEEE W 7 7 a7xd6 4 0 0 # closes a mill\n";
+        let book = OpeningBook::parse(text).unwrap();
+        assert_eq!(book.len(), 2);
+
+        let reparsed = OpeningBook::parse(&book.to_text()).unwrap();
+        assert_eq!(reparsed.to_text(), book.to_text());
+
+        // Sorted from the start of the game on, the moves are in the orientation of the canonical board
+        let text = book.to_text();
+        let moves: Vec<&str> = text
+            .lines()
+            .skip(1)
+            .map(|line| line.split(' ').nth(4).unwrap())
+            .collect();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0], "d7");
+        assert!(moves[1].contains('x'));
+
+        // Counts are added at once, not game by game
+        let book = OpeningBook::parse("EEEEEEEEEEEEEEEEEEEEEEEE W 9 9 D7 3000000000 0 1").unwrap();
+        let book_moves = book.moves_of(&Position::default());
+        assert_eq!((book_moves[0].1.wins, book_moves[0].1.losses), (3_000_000_000, 1));
+
+        // Duplicate lines add up, but don't overflow
+        let line = "EEEEEEEEEEEEEEEEEEEEEEEE W 9 9 D7 3000000000 0 1\n";
+        let book = OpeningBook::parse(&line.repeat(2)).unwrap();
+        let book_moves = book.moves_of(&Position::default());
+        assert_eq!((book_moves[0].1.wins, book_moves[0].1.losses), (u32::MAX, 2));
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let error = OpeningBook::parse("EEEEEEEEEEEEEEEEEEEEEEEE W 9 9 D7 1 2 3\nEEEE W 9 9 D7 1 2 3").unwrap_err();
+        assert_eq!(error.line, 2);
+
+        let error = OpeningBook::parse("WEEEEEEEEEEEEEEEEEEEEEEE B 8 9 D7 1 0 0").unwrap_err();
+        assert!(error.message.contains("isn't legal"));
+
+        let error = OpeningBook::parse("WEEEEEEEEEEEEEEEEEEEEEEE W 8 9 D6 1 0 0").unwrap_err();
+        assert!(error.message.starts_with("Impossible position"));
+    }
+
+    #[test]
+    fn engine_plays_book_moves() {
+        let mut book = OpeningBook::default();
+        book.build_from_self_play(2, 1, 2, 7);
+        assert!(!book.is_empty());
+
+        let start = Position::default();
+        let book_move = book.lookup(&start).unwrap();
+
+        let engine = super::super::EnginePlayer::new(Some(book));
+        assert_eq!(engine.choose_move_at_depth(&start, 1).best_move, Some(book_move));
+    }
+}
@@ -0,0 +1,357 @@
+//! A line based protocol for graphical front-ends & test harnesses, modelled after UCI of chess engines. The front-end
+//! sends commands on stdin, the engine answers on stdout:
+//!
+//! ```text
+//! > mep                                      < id name muehle 0.1.0
+//!                                            < id author ...
+//!                                            < mepok
+//! > isready                                  < readyok
+//! > position startpos moves d7 d6 g7 f6
+//! > go depth 4                               < info depth 1 score 50 nodes 19 time 0 pv a7xd6
+//!                                            < ...
+//!                                            < bestmove a7xd6
+//! > quit
+//! ```
+//!
+//! - `position startpos [moves <move>...]` starts on the empty play field, `position board <code> [<w|b> <white hand>
+//!   <black hand>] [moves <move>...]` on a board code in ring order. Without side & hands it's white's turn in the
+//!   move phase.
+//! - `go [depth <plies>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [infinite]` searches in
+//!   the background until the first limit is reached or `stop` is sent. Without limits it searches until `stop`. With
+//!   the clock of the side to move the engine picks its time like [crate::game::clock::move_budget].
+//! - `newgame` resets to the empty play field.
+//!
+//! Scores are from the view of the side to move in the units of [super::evaluate]. Malformed commands are answered
+//! with `info string error <message>` & otherwise ignored.
+
+use std::{
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread::{self, ScopedJoinHandle},
+    time::Duration,
+};
+
+use super::{EnginePlayer, SearchLimits};
+use crate::game::{
+    clock::move_budget,
+    efficient_state::ordering::FieldOrder,
+    notation::Move,
+    position::{ExtendedPosition, Position},
+    PlayerColor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Handshake,
+    IsReady,
+    NewGame,
+    Position(Position),
+    Go(SearchLimits, ClockTimes),
+    Stop,
+    Quit,
+}
+
+impl Command {
+    /// None for empty lines
+    fn parse(line: &str) -> Result<Option<Command>, String> {
+        let mut tokens = line.split_whitespace();
+        let Some(name) = tokens.next() else {
+            return Ok(None);
+        };
+
+        let command = match name {
+            "mep" => Command::Handshake,
+            "isready" => Command::IsReady,
+            "newgame" => Command::NewGame,
+            "position" => Command::Position(parse_position(tokens)?),
+            "go" => {
+                let (limits, clocks) = parse_limits(tokens)?;
+                Command::Go(limits, clocks)
+            }
+            "stop" => Command::Stop,
+            "quit" => Command::Quit,
+            _ => return Err(format!("Unknown command '{name}'")),
+        };
+        Ok(Some(command))
+    }
+}
+
+fn parse_position<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Position, String> {
+    let mut position = match tokens.next() {
+        Some("startpos") => Position::default(),
+        Some("board") => {
+            let fields: Vec<&str> = tokens.by_ref().take_while(|token| *token != "moves").collect();
+            let board = match fields.first() {
+                Some(code) => FieldOrder::RingClockwise
+                    .import(code)
+                    .map_err(|error| format!("Malformed board '{code}': {error}"))?,
+                None => return Err("The board code is missing".to_string()),
+            };
+            if fields.len() == 1 {
+                Position {
+                    board,
+                    side_to_move: PlayerColor::White,
+                    stones_in_hand: (0, 0),
+                }
+            } else {
+                let text = fields.join(" ");
+                let extended: ExtendedPosition = text
+                    .parse()
+                    .map_err(|error| format!("Malformed position '{text}': {error}"))?;
+                if extended.pending_capture {
+                    return Err("Positions with a pending capture aren't supported".to_string());
+                }
+                extended.position
+            }
+        }
+        _ => return Err("Expected 'startpos' or 'board <code>' after 'position'".to_string()),
+    };
+
+    // Only `startpos` leaves the `moves` keyword in the tokens
+    for token in tokens.skip_while(|token| *token == "moves") {
+        let played: Move = token
+            .parse()
+            .map_err(|error| format!("Malformed move '{token}': {error}"))?;
+        let legal_move = played
+            .to_efficient()
+            .filter(|efficient_move| position.legal_moves().contains(efficient_move))
+            .ok_or_else(|| format!("{played} isn't a legal move"))?;
+        position.play(legal_move);
+    }
+    Ok(position)
+}
+
+/// The clocks sent with `go`, the remaining times & increments of white & black
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ClockTimes {
+    remaining: (Option<Duration>, Option<Duration>),
+    increment: (Duration, Duration),
+}
+
+impl ClockTimes {
+    /// The time to use for the move, if the clock of the side to move was sent
+    fn budget(&self, color: PlayerColor) -> Option<Duration> {
+        let (remaining, increment) = match color {
+            PlayerColor::White => (self.remaining.0, self.increment.0),
+            PlayerColor::Black => (self.remaining.1, self.increment.1),
+        };
+        remaining.map(|remaining| move_budget(remaining, increment))
+    }
+}
+
+fn parse_limits<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<(SearchLimits, ClockTimes), String> {
+    let mut limits = SearchLimits::default();
+    let mut clocks = ClockTimes::default();
+    while let Some(token) = tokens.next() {
+        let mut milliseconds = || {
+            let time = tokens.next().and_then(|time| time.parse().ok());
+            time.map(Duration::from_millis)
+                .ok_or(format!("Expected the time in milliseconds after {token}"))
+        };
+        match token {
+            "depth" => {
+                let depth = tokens.next().and_then(|depth| depth.parse().ok());
+                limits.depth = Some(
+                    depth
+                        .filter(|depth| 0 < *depth)
+                        .ok_or("Expected a depth of at least 1")?,
+                );
+            }
+            "movetime" => limits.time = Some(milliseconds()?),
+            "wtime" => clocks.remaining.0 = Some(milliseconds()?),
+            "btime" => clocks.remaining.1 = Some(milliseconds()?),
+            "winc" => clocks.increment.0 = milliseconds()?,
+            "binc" => clocks.increment.1 = milliseconds()?,
+            "infinite" => {}
+            _ => return Err(format!("Unknown option '{token}' for go")),
+        }
+    }
+    Ok((limits, clocks))
+}
+
+/// Reads commands until `quit` or the end of the input, the moves are chosen by `engine`. At the end of the input a
+/// search with limits is finished, one without limits is stopped.
+pub fn run(engine: &EnginePlayer, input: impl BufRead, output: impl Write + Send) -> io::Result<()> {
+    let output = Mutex::new(output);
+    let stop = AtomicBool::new(false);
+    let mut position = Position::default();
+
+    thread::scope(|scope| {
+        // The running search & whether it has limits
+        let mut searching: Option<(ScopedJoinHandle<io::Result<()>>, bool)> = None;
+        let finish =
+            |searching: &mut Option<(ScopedJoinHandle<io::Result<()>>, bool)>, stopped: bool| match searching.take() {
+                Some((search, _)) => {
+                    stop.store(stopped, Ordering::Relaxed);
+                    search.join().unwrap()
+                }
+                None => Ok(()),
+            };
+
+        for line in input.lines() {
+            let line = line?;
+            if searching.as_ref().is_some_and(|(search, _)| search.is_finished()) {
+                finish(&mut searching, false)?;
+            }
+
+            let command = match Command::parse(&line) {
+                Ok(Some(command)) => command,
+                Ok(None) => continue,
+                Err(message) => {
+                    send(&output, &format!("info string error {message}"))?;
+                    continue;
+                }
+            };
+            match command {
+                Command::Handshake => {
+                    send(&output, &format!("id name muehle {}", env!("CARGO_PKG_VERSION")))?;
+                    send(&output, &format!("id author {}", env!("CARGO_PKG_AUTHORS")))?;
+                    send(&output, "mepok")?;
+                }
+                Command::IsReady => send(&output, "readyok")?,
+                Command::Stop => finish(&mut searching, true)?,
+                Command::Quit => return finish(&mut searching, true),
+                _ if searching.is_some() => {
+                    send(&output, "info string error Still searching, send stop first")?;
+                }
+                Command::NewGame => position = Position::default(),
+                Command::Position(new_position) => position = new_position,
+                Command::Go(mut limits, clocks) => {
+                    if let Some(budget) = clocks.budget(position.side_to_move) {
+                        limits.time = Some(limits.time.map_or(budget, |time| time.min(budget)));
+                    }
+                    stop.store(false, Ordering::Relaxed);
+                    let (output, stop) = (&output, &stop);
+                    let search = scope.spawn(move || {
+                        let mut result = Ok(());
+                        let best = engine.choose_move(&position, limits, stop, |info| {
+                            let pv = info
+                                .result
+                                .best_move
+                                .map_or("".to_string(), |best| format!(" pv {}", Move::from(best)));
+                            let line = format!(
+                                "info depth {} score {} nodes {} time {}{pv}",
+                                info.depth,
+                                info.result.score,
+                                info.result.nodes,
+                                info.time.as_millis()
+                            );
+                            if result.is_ok() {
+                                result = send(output, &line);
+                            }
+                        });
+                        result?;
+
+                        match best.best_move {
+                            Some(best) => send(output, &format!("bestmove {}", Move::from(best))),
+                            None => send(output, "bestmove none"),
+                        }
+                    });
+                    searching = Some((search, limits != SearchLimits::default()));
+                }
+            }
+        }
+
+        let has_limits = searching.as_ref().is_some_and(|(_, has_limits)| *has_limits);
+        finish(&mut searching, !has_limits)
+    })
+}
+
+/// Writes a line & flushes, so the front-end sees it immediately
+fn send(output: &Mutex<impl Write>, line: &str) -> io::Result<()> {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{line}")?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_script(script: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run(&EnginePlayer::default(), script.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap().lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn handshake_and_search() {
+        let lines = run_script("mep\nisready\nposition startpos moves d7 d6 g7 f6\ngo depth 2\n");
+        assert!(lines[0].starts_with("id name muehle "));
+        assert_eq!(lines[2], "mepok");
+        assert_eq!(lines[3], "readyok");
+        assert!(lines[4].starts_with("info depth 1 score "));
+        assert!(lines[5].starts_with("info depth 2 score "));
+        // Closing the mill on the top row is the obvious move
+        assert_eq!(lines[5].split(" pv ").nth(1), Some("a7xd6"));
+        assert_eq!(lines[6], "bestmove a7xd6");
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn positions_from_board_codes() {
+        // All four black stones are blocked by white
+        let lines = run_script("position board BBWWBBWWWWEEWEEEEEEEEEEE b 0 0 moves\ngo depth 3\n");
+        assert_eq!(lines, vec!["bestmove none"]);
+
+        let command = Command::parse("position board WWEEEEEEBBEEEEEEEEEEEEEE w 7 7 moves a7xd6").unwrap();
+        let Some(Command::Position(position)) = command else {
+            panic!("expected a position, got {command:?}");
+        };
+        assert_eq!(position.side_to_move, PlayerColor::Black);
+        assert_eq!(position.stones_in_hand, (6, 7));
+        assert_eq!(position.board.to_string_representation(), "WWEEEEEWEBEEEEEEEEEEEEEE");
+    }
+
+    #[test]
+    fn clocks() {
+        let command = Command::parse("go wtime 50000 btime 1000 binc 500").unwrap();
+        let Some(Command::Go(limits, clocks)) = command else {
+            panic!("expected go, got {command:?}");
+        };
+        assert_eq!(limits, SearchLimits::default());
+        assert_eq!(clocks.budget(PlayerColor::White), Some(Duration::from_secs(2)));
+        assert_eq!(clocks.budget(PlayerColor::Black), Some(Duration::from_millis(415)));
+        assert_eq!(
+            Command::parse("go wtime"),
+            Err("Expected the time in milliseconds after wtime".to_string())
+        );
+
+        // A clock alone is a limit, the search finishes by itself
+        let lines = run_script("position startpos\ngo wtime 100 btime 100\n");
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn errors_and_stop() {
+        let lines = run_script("hello\nposition startpos moves d7 d7\ngo depth 0\ngo\nstop\ngo movetime 20\nquit\n");
+        assert_eq!(lines[0], "info string error Unknown command 'hello'");
+        assert_eq!(lines[1], "info string error d7 isn't a legal move");
+        assert_eq!(lines[2], "info string error Expected a depth of at least 1");
+        // Every go is answered by a best move, even when stopped right away
+        assert_eq!(lines.iter().filter(|line| line.starts_with("bestmove ")).count(), 2);
+        assert!(lines.iter().all(|line| !line.contains("error Still searching")));
+    }
+
+    #[test]
+    fn book_moves_skip_the_search() {
+        let book = super::super::book::OpeningBook::parse("EEEEEEEEEEEEEEEEEEEEEEEE W 9 9 a1 5 0 0").unwrap();
+        let mut output = Vec::new();
+        run(
+            &EnginePlayer::new(Some(book)),
+            "position startpos\ngo depth 3\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        // The corners are symmetric, the book move is played in one of them without any info of a search
+        let output = String::from_utf8(output).unwrap();
+        assert!(matches!(
+            output.as_str(),
+            "bestmove a1\n" | "bestmove a7\n" | "bestmove g1\n" | "bestmove g7\n"
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+//! Time controls & the clocks of both players.
+//!
+//! A time control is written as `<total>+<increment>`, optionally followed by `/<per move>`, all in seconds:
+//!
+//! ```text
+//! 300+5      5 minutes for the game, 5 seconds added after each move
+//! 180+0/20   3 minutes for the game, but at most 20 seconds per move
+//! 0.5+0.1    Fractions of seconds are fine too
+//! ```
+//!
+//! The clock of a player only runs while it's their turn. A player whose time is used up, or who exceeds the limit per
+//! move, loses the game.
+
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use super::PlayerColor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimeControl {
+    /// The time of each player for the whole game
+    pub total: Duration,
+    /// Added to the remaining time after each move
+    pub increment: Duration,
+    pub per_move: Option<Duration>,
+}
+
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+{}", format_seconds(self.total), format_seconds(self.increment))?;
+        if let Some(per_move) = self.per_move {
+            write!(f, "/{}", format_seconds(per_move))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let malformed = || format!("Malformed time control '{text}', expected <total>+<increment>[/<per move>]");
+
+        let (times, per_move) = match text.split_once('/') {
+            Some((times, per_move)) => (times, Some(parse_seconds(per_move).ok_or_else(malformed)?)),
+            None => (text, None),
+        };
+        let (total, increment) = times.split_once('+').ok_or_else(malformed)?;
+        if per_move == Some(Duration::ZERO) {
+            return Err("The time per move must be more than 0".to_string());
+        }
+
+        Ok(TimeControl {
+            total: parse_seconds(total).ok_or_else(malformed)?,
+            increment: parse_seconds(increment).ok_or_else(malformed)?,
+            per_move,
+        })
+    }
+}
+
+/// Parses seconds like `295` or `295.125` exactly, fractions up to nanoseconds
+pub fn parse_seconds(text: &str) -> Option<Duration> {
+    let (seconds, fraction) = text.trim().split_once('.').unwrap_or((text.trim(), ""));
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if seconds.is_empty() || !all_digits(seconds) || !all_digits(fraction) || 9 < fraction.len() {
+        return None;
+    }
+
+    let nanos = format!("{fraction:0<9}").parse().ok()?;
+    Some(Duration::new(seconds.parse().ok()?, nanos))
+}
+
+/// The inverse of [parse_seconds] in milliseconds, without trailing zeros
+pub fn format_seconds(duration: Duration) -> String {
+    match duration.subsec_millis() {
+        0 => duration.as_secs().to_string(),
+        millis => format!(
+            "{}.{}",
+            duration.as_secs(),
+            format!("{millis:03}").trim_end_matches('0')
+        ),
+    }
+}
+
+/// Formats a remaining time like a clock: `4:55`, or `0:09.3` in the last 10 seconds
+pub fn format_clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 10 {
+        format!("0:{seconds:02}.{}", duration.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// The time a player without a limit per move should spend on the next move: a share of the remaining time plus most
+/// of the increment. It's always less than the remaining time, so an engine using it doesn't lose on time.
+pub fn move_budget(remaining: Duration, increment: Duration) -> Duration {
+    (remaining / 25 + increment * 3 / 4).min(remaining * 4 / 5)
+}
+
+/// The player ran out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagFall(pub PlayerColor);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub control: TimeControl,
+    /// The remaining time of white & black
+    pub remaining: (Duration, Duration),
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Clock {
+            control,
+            remaining: (control.total, control.total),
+        }
+    }
+
+    pub fn remaining_of(&self, color: PlayerColor) -> Duration {
+        match color {
+            PlayerColor::White => self.remaining.0,
+            PlayerColor::Black => self.remaining.1,
+        }
+    }
+
+    fn remaining_mut(&mut self, color: PlayerColor) -> &mut Duration {
+        match color {
+            PlayerColor::White => &mut self.remaining.0,
+            PlayerColor::Black => &mut self.remaining.1,
+        }
+    }
+
+    /// The time the player may use for the current move before the flag falls
+    pub fn available(&self, color: PlayerColor) -> Duration {
+        let remaining = self.remaining_of(color);
+        self.control
+            .per_move
+            .map_or(remaining, |per_move| per_move.min(remaining))
+    }
+
+    /// The time an engine should use for its current move, see [move_budget]
+    pub fn budget(&self, color: PlayerColor) -> Duration {
+        let budget = move_budget(self.remaining_of(color), self.control.increment);
+        // Keeps some margin to the limit per move too
+        self.control
+            .per_move
+            .map_or(budget, |per_move| budget.min(per_move * 4 / 5))
+    }
+
+    /// Charges the time used for a finished move & adds the increment. Using all of the available time lets the flag
+    /// fall, the remaining time is 0 then.
+    pub fn charge(&mut self, color: PlayerColor, used: Duration) -> Result<(), FlagFall> {
+        let available = self.available(color);
+        let increment = self.control.increment;
+        let remaining = self.remaining_mut(color);
+
+        if available <= used {
+            *remaining = remaining.saturating_sub(used);
+            return Err(FlagFall(color));
+        }
+        *remaining = *remaining - used + increment;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_controls() {
+        let control: TimeControl = "300+5".parse().unwrap();
+        assert_eq!(control.total, Duration::from_secs(300));
+        assert_eq!(control.increment, Duration::from_secs(5));
+        assert_eq!(control.per_move, None);
+
+        let control: TimeControl = "0.5+0.125/20".parse().unwrap();
+        assert_eq!(control.total, Duration::from_millis(500));
+        assert_eq!(control.increment, Duration::from_millis(125));
+        assert_eq!(control.per_move, Some(Duration::from_secs(20)));
+        assert_eq!(control.to_string(), "0.5+0.125/20");
+
+        for malformed in ["300", "300+", "+5", "5 min+5", "300+5/", "300+5/0", "1.0000000001+0"] {
+            assert!(malformed.parse::<TimeControl>().is_err(), "{malformed}");
+        }
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_seconds(Duration::from_millis(295_200)), "295.2");
+        assert_eq!(parse_seconds("295.2"), Some(Duration::from_millis(295_200)));
+        assert_eq!(format_clock(Duration::from_millis(295_200)), "4:55");
+        assert_eq!(format_clock(Duration::from_millis(9_350)), "0:09.3");
+    }
+
+    #[test]
+    fn charging() {
+        let mut clock = Clock::new("60+2/10".parse().unwrap());
+        assert_eq!(clock.available(PlayerColor::White), Duration::from_secs(10));
+
+        clock.charge(PlayerColor::White, Duration::from_secs(8)).unwrap();
+        assert_eq!(clock.remaining, (Duration::from_secs(54), Duration::from_secs(60)));
+        assert!(clock.budget(PlayerColor::Black) < Duration::from_secs(8));
+
+        // Over the limit per move
+        assert_eq!(
+            clock.charge(PlayerColor::Black, Duration::from_secs(11)),
+            Err(FlagFall(PlayerColor::Black))
+        );
+
+        // Without any time left the flag falls right away
+        let mut clock = Clock::new("0+1".parse().unwrap());
+        assert_eq!(
+            clock.charge(PlayerColor::White, Duration::ZERO),
+            Err(FlagFall(PlayerColor::White))
+        );
+    }
+}
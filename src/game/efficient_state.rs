@@ -1,18 +1,18 @@
 //! This module holds the representation part of the more efficient variant of the [PlayField] struct with some low-level
 //! functions for accessing and modifying it's state & convert it to a canonical form, which is needed in the later parts
 //! of the project.
-//! It also holds some tests cases (I was to lazy to implement asserts on) & the assignment 4's test case.
+//! It also holds some tests cases (I was to lazy to implement asserts on), the assignments' test cases moved to
+//! [crate::batch].
 
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader, BufWriter, Write},
-};
-
-use super::{state, PlayerColor};
+use super::PlayerColor;
 
 mod de_encode;
+pub mod moves;
+pub mod ordering;
 mod printing;
+pub mod symmetry;
+
+pub use de_encode::CodedError;
 
 /// Efficient representation of [PlayField] using a [u16; 3] for it's internal representation.
 /// Start counting from the top middle mill field on the LSB of each u16 field for each of the 3 rectangle rings
@@ -23,7 +23,7 @@ mod printing;
 /// - 01: white
 /// - 10: black
 /// - 11: undefined -> assert panic!
-#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Default)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Default)]
 pub struct EfficientPlayField {
     state: [u16; 3],
 }
@@ -198,7 +198,7 @@ impl EfficientPlayField {
                 }
 
                 // In this branch the current colors possible moves & => movements into a mill should be figured out
-                if current_field_state == player_color.into() {
+                if current_field_state == u16::from(player_color) {
                     let ring_neighbors_indices = [(field_index + 14) % 16, (field_index + 18) % 16];
 
                     for neighbor_index in ring_neighbors_indices {
@@ -352,7 +352,7 @@ impl EfficientPlayField {
         // Resetting the in-place simulation
         self.state[start_ring_index] = start_ring_backup;
 
-        return mills_possible;
+        mills_possible
     }
 
     /// Checks for mills on the specified field & returns it.
@@ -389,7 +389,7 @@ impl EfficientPlayField {
 
         // If index is located in an edge, two triples must be checked for mill occurrence
         if field_index == 2 || field_index == 6 || field_index == 10 || field_index == 14 {
-            let state_triple = self.state[ring_index].rotate_right(field_index) & 0b000_00000_0011_1111u16;
+            let state_triple = self.state[ring_index].rotate_right(field_index) & 0b0000_0000_0011_1111u16;
             /* 010101 | 101010 */
             if state_triple == 21u16 || state_triple == 42u16 {
                 mill_counter += 1;
@@ -400,7 +400,7 @@ impl EfficientPlayField {
         if let DirectionToCheck::OnAndAcrossRings { player_color } = direction {
             //assert!(color < 3);
 
-            if field_index % 4 == 0 {
+            if field_index.is_multiple_of(4) {
                 //assert!(((self.state[ring_index] >> field_index) & 3u16) != 0);
 
                 let next_indexs_field_state = (self.state[(ring_index + 1) % 3] & (3u16 << field_index)) >> field_index;
@@ -430,69 +430,10 @@ enum DirectionToCheck {
     OnAndAcrossRings { player_color: u16 },
 }
 
-/// Used by the [process_input_felder] method
-pub enum ToWhatToProcess {
-    CanonicalForm,
-    MoveTripel,
-}
-
-pub fn process_input_felder(outputs_contents: ToWhatToProcess) {
-    let input_felder_txt =
-        File::open("input_felder.txt").expect("The 'input_felder.txt' file was not found in the projects root...");
-    let reader = BufReader::new(input_felder_txt);
-
-    let output_text = File::create("output.txt").expect("Could not create ro 'output.txt' to write results into");
-    let mut writer = BufWriter::new(output_text);
-
-    if let ToWhatToProcess::CanonicalForm = outputs_contents {
-        let mut h_map: HashMap<EfficientPlayField, usize> = HashMap::new();
-
-        for (line_index, line_content) in reader.lines().enumerate() {
-            // Idk why but the reference output.txt starts counting on 1...
-            let line_index = line_index + 1;
-
-            let mut playfield = EfficientPlayField::from_coded(&line_content.unwrap());
-            let canonical_form = playfield.get_canonical_form();
-
-            if let Some(previous_canonical_match) = h_map.get(&canonical_form) {
-                writeln!(writer, "{}", previous_canonical_match).unwrap();
-            } else {
-                h_map.insert(canonical_form, line_index);
-                writeln!(writer, "{}", line_index).unwrap();
-            }
-        }
-    } else {
-        for (line_index, line_content) in reader.lines().enumerate() {
-            let line_content = line_content.unwrap();
-            let mut playfield = EfficientPlayField::from_coded(&line_content);
-
-            let (x, y, z) = playfield.get_move_triple(PlayerColor::White);
-
-            assert!({
-                println!("Input {line_index}: {line_content}\n{playfield}");
-                println!("Moves: {x}\nMoves->Mill: {y}\nTo Take: {z}");
-                true
-            });
-
-            writeln!(writer, "{x} {y} {z}").unwrap();
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::EfficientPlayField;
 
-    #[test]
-    fn assignment4() {
-        super::process_input_felder(super::ToWhatToProcess::CanonicalForm);
-    }
-
-    #[test]
-    fn assignment5() {
-        super::process_input_felder(super::ToWhatToProcess::MoveTripel);
-    }
-
     #[test]
     fn assignment5_dbg() {
         let mut test_epf = EfficientPlayField::from_coded("BEEEWEWBEEWWEEWEWEEWWWBB");
@@ -584,6 +525,12 @@ mod tests {
 
             println!("Output: {}", epf.to_string_representation());
         }
+
+        #[test]
+        fn coded_round_trip() {
+            let test = "BBEEEEEBEEEEWEWWBWWEEEBE";
+            assert_eq!(EfficientPlayField::from_coded(test).to_string_representation(), test);
+        }
     }
 
     mod extreme {
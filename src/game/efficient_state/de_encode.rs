@@ -1,6 +1,57 @@
+use std::fmt::Display;
+
 use super::EfficientPlayField;
 
+/// Why a string isn't a valid coding for [EfficientPlayField::try_from_coded]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodedError {
+    WrongLength(usize),
+    InvalidChar { index: usize, found: char },
+}
+
+impl Display for CodedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodedError::WrongLength(length) => write!(f, "Expected 24 characters, found {length}"),
+            CodedError::InvalidChar { index, found } => {
+                write!(
+                    f,
+                    "Invalid character '{found}' at position {}, expected E, W or B",
+                    index + 1
+                )
+            }
+        }
+    }
+}
+
 impl EfficientPlayField {
+    /// Like [from_coded], but returns an error instead of panicking on malformed input
+    pub fn try_from_coded(input: &str) -> Result<EfficientPlayField, CodedError> {
+        if let Some((index, found)) = input.chars().enumerate().find(|(_, c)| !matches!(c, 'E' | 'W' | 'B')) {
+            return Err(CodedError::InvalidChar { index, found });
+        } else if input.len() != 24 {
+            return Err(CodedError::WrongLength(input.len()));
+        }
+        Ok(EfficientPlayField::from_coded(input))
+    }
+
+    /// The 48 bits of the state, two per field in the order of [from_coded] starting at the lowest bits
+    pub fn to_bits(&self) -> u64 {
+        (self.state[2] as u64) | ((self.state[1] as u64) << 16) | ((self.state[0] as u64) << 32)
+    }
+
+    /// The inverse of [to_bits], bits above the lowest 48 are ignored. Fails with the ring & bit index of the first
+    /// field coded as `11`, which the invariant of the state forbids.
+    pub fn try_from_bits(bits: u64) -> Result<EfficientPlayField, (usize, u8)> {
+        let play_field = EfficientPlayField {
+            state: [(bits >> 32) as u16, (bits >> 16) as u16, bits as u16],
+        };
+        match play_field.assert_state_invariant() {
+            Some(invalid_field) => Err(invalid_field),
+            None => Ok(play_field),
+        }
+    }
+
     /// Reads a input str containing 24 chars consisting of 'E' = empty = 0b00, 'W' = white = 0b01 or 'B' = black = 0b10
     /// and constructs a [EfficientPlayField] instance from it.
     /// The input string starts coding the outest rings middle top field state and then circles right from outer to inner rect rings
@@ -33,7 +84,8 @@ impl EfficientPlayField {
     pub fn to_string_representation(&self) -> String {
         let mut state_formatted = String::with_capacity(24);
 
-        for ring_index in 0..3 {
+        // Like in [from_coded], the outest ring comes first
+        for ring_index in (0..3).rev() {
             let ring_state = self.state[ring_index];
             for i in (0..16).step_by(2) {
                 // Mask out the last 2 bits for each field of the ring
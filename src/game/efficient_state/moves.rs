@@ -0,0 +1,403 @@
+//! Bitboard helpers generating the successors of a [EfficientPlayField].
+//!
+//! The fields are addressed by their "coded index" in `0..24`, which is the position of the field in the string read by
+//! [EfficientPlayField::from_coded]: the outer ring comes first, then the middle and the inner ring, each starting at the
+//! top middle field and circling clockwise. A set of fields is stored as a `u32` mask using the lower 24 bits.
+
+use std::fmt::Display;
+
+use super::EfficientPlayField;
+use crate::game::{notation::Move, Field, PlayerColor};
+
+pub const FIELD_COUNT: usize = 24;
+pub const FULL_MASK: u32 = (1 << FIELD_COUNT) - 1;
+
+/// The neighbors of each field a stone can slide to, indexed by the coded index
+pub const ADJACENT: [u32; FIELD_COUNT] = adjacency_table();
+
+/// All 16 mills of the play field: 4 on each ring and 4 crossing the rings on the middle fields
+pub const MILLS: [u32; 16] = mill_table();
+
+const fn adjacency_table() -> [u32; FIELD_COUNT] {
+    let mut table = [0u32; FIELD_COUNT];
+
+    let mut field = 0;
+    while field < FIELD_COUNT {
+        let ring_offset = field - field % 8;
+        let index = field % 8;
+
+        table[field] |= 1 << (ring_offset + (index + 1) % 8);
+        table[field] |= 1 << (ring_offset + (index + 7) % 8);
+
+        // Middle fields are connected to the fields on the same index of the neighboring rings
+        if index % 2 == 0 {
+            if 8 <= field {
+                table[field] |= 1 << (field - 8);
+            }
+            if field < 16 {
+                table[field] |= 1 << (field + 8);
+            }
+        }
+        field += 1;
+    }
+    table
+}
+
+const fn mill_table() -> [u32; 16] {
+    let mut table = [0u32; 16];
+
+    let mut ring = 0;
+    while ring < 3 {
+        let mut side = 0;
+        while side < 4 {
+            // The corners are located on the odd indices, a side spans from one corner to the next one
+            let corner = 2 * side + 1;
+            table[ring * 4 + side] = (1 << (ring * 8 + corner))
+                | (1 << (ring * 8 + (corner + 1) % 8))
+                | (1 << (ring * 8 + (corner + 2) % 8));
+            side += 1;
+        }
+        ring += 1;
+    }
+
+    let mut middle = 0;
+    while middle < 4 {
+        let index = 2 * middle;
+        table[12 + middle] = (1 << index) | (1 << (8 + index)) | (1 << (16 + index));
+        middle += 1;
+    }
+    table
+}
+
+/// The fields of the [PlayField] notation, indexed by the coded index
+#[rustfmt::skip]
+pub const CODED_FIELDS: [Field; FIELD_COUNT] = [
+    ('D',7), ('G',7), ('G',4), ('G',1), ('D',1), ('A',1), ('A',4), ('A',7),
+    ('D',6), ('F',6), ('F',4), ('F',2), ('D',2), ('B',2), ('B',4), ('B',6),
+    ('D',5), ('E',5), ('E',4), ('E',3), ('D',3), ('C',3), ('C',4), ('C',5),
+];
+
+/// Returns the coded index of a field in the [PlayField] notation, if it's a valid field
+pub fn coded_index_of(field: Field) -> Option<usize> {
+    CODED_FIELDS.iter().position(|&coded_field| coded_field == field)
+}
+
+/// A move addressed by coded indices. Placements of the set phase have no start field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EfficientMove {
+    pub start: Option<u8>,
+    pub target: u8,
+    /// The opponents stone taken after closing a mill
+    pub take: Option<u8>,
+}
+
+impl Display for EfficientMove {
+    /// Written in the notation of [crate::game::notation], e.g. `a7-d7xd6`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Move::from(*self).fmt(f)
+    }
+}
+
+/// Returns all moves of the player owning `movers_stones`, placements if `set_phase` is true.
+/// A move closing a mill is returned once for every stone of the opponent which can be taken.
+pub fn generate_moves(movers_stones: u32, opponents_stones: u32, set_phase: bool) -> Vec<EfficientMove> {
+    let mut moves = Vec::new();
+    let free = !(movers_stones | opponents_stones) & FULL_MASK;
+
+    let mut push_with_takes = |start: Option<u8>, target: u8, stones_after: u32| {
+        if closes_mill(stones_after, target as usize) {
+            let mut takeable = takeable_stones(opponents_stones);
+            while takeable != 0 {
+                let take = takeable.trailing_zeros() as u8;
+                takeable &= takeable - 1;
+                moves.push(EfficientMove {
+                    start,
+                    target,
+                    take: Some(take),
+                });
+            }
+        } else {
+            moves.push(EfficientMove {
+                start,
+                target,
+                take: None,
+            });
+        }
+    };
+
+    if set_phase {
+        let mut targets = free;
+        while targets != 0 {
+            let target = targets.trailing_zeros() as u8;
+            targets &= targets - 1;
+            push_with_takes(None, target, movers_stones | (1 << target));
+        }
+        return moves;
+    }
+
+    let mut starts = movers_stones;
+    while starts != 0 {
+        let start = starts.trailing_zeros() as u8;
+        starts &= starts - 1;
+
+        let mut targets = if can_jump(movers_stones) {
+            free
+        } else {
+            ADJACENT[start as usize] & free
+        };
+        while targets != 0 {
+            let target = targets.trailing_zeros() as u8;
+            targets &= targets - 1;
+            push_with_takes(Some(start), target, movers_stones ^ (1 << start) ^ (1 << target));
+        }
+    }
+    moves
+}
+
+/// Returns true if the player owning `movers_stones` may jump in the move phase, which is the case with exactly 3 stones
+/// left. With less the player has lost already.
+#[inline]
+pub fn can_jump(movers_stones: u32) -> bool {
+    movers_stones.count_ones() == 3
+}
+
+/// Returns true if the stones of `stones` form a mill containing the field with the coded index `field`
+#[inline]
+pub fn closes_mill(stones: u32, field: usize) -> bool {
+    MILLS
+        .iter()
+        .any(|&mill| mill & (1 << field) != 0 && mill & stones == mill)
+}
+
+/// Returns the mask of the stones in `stones` which might be taken by the opponent.
+/// Stones being part of a mill are protected, unless all stones are part of a mill.
+#[inline]
+pub fn takeable_stones(stones: u32) -> u32 {
+    let in_mills = MILLS
+        .iter()
+        .filter(|&&mill| mill & stones == mill)
+        .fold(0u32, |acc, &mill| acc | mill);
+
+    if stones & !in_mills == 0 {
+        stones
+    } else {
+        stones & !in_mills
+    }
+}
+
+/// Calls `on_successor` with the masks `(movers_stones, opponents_stones)` of every position reachable by one move of the
+/// player owning `movers_stones` in the move phase, jumps included if the player has only 3 stones left.
+/// If a move closes a mill, one successor per takeable stone of the opponent is generated.
+///
+/// Returns the amount of moves (not successors) which were possible
+pub fn for_each_move_successor(
+    movers_stones: u32,
+    opponents_stones: u32,
+    mut on_successor: impl FnMut(u32, u32),
+) -> u32 {
+    let free = !(movers_stones | opponents_stones) & FULL_MASK;
+    let mut moves_possible = 0;
+
+    let mut starts = movers_stones;
+    while starts != 0 {
+        let start = starts.trailing_zeros() as usize;
+        starts &= starts - 1;
+
+        let mut targets = if can_jump(movers_stones) {
+            free
+        } else {
+            ADJACENT[start] & free
+        };
+        while targets != 0 {
+            let target = targets.trailing_zeros() as usize;
+            targets &= targets - 1;
+            moves_possible += 1;
+
+            let moved = movers_stones ^ (1 << start) ^ (1 << target);
+            emit_with_takes(moved, opponents_stones, target, &mut on_successor);
+        }
+    }
+    moves_possible
+}
+
+/// Like [for_each_move_successor], but for placing a new stone in the set phase
+pub fn for_each_set_successor(
+    movers_stones: u32,
+    opponents_stones: u32,
+    mut on_successor: impl FnMut(u32, u32),
+) -> u32 {
+    let mut targets = !(movers_stones | opponents_stones) & FULL_MASK;
+    let moves_possible = targets.count_ones();
+
+    while targets != 0 {
+        let target = targets.trailing_zeros() as usize;
+        targets &= targets - 1;
+
+        emit_with_takes(
+            movers_stones | (1 << target),
+            opponents_stones,
+            target,
+            &mut on_successor,
+        );
+    }
+    moves_possible
+}
+
+#[inline]
+fn emit_with_takes(movers_stones: u32, opponents_stones: u32, target: usize, on_successor: &mut impl FnMut(u32, u32)) {
+    if closes_mill(movers_stones, target) {
+        let mut takeable = takeable_stones(opponents_stones);
+        while takeable != 0 {
+            let take = takeable.trailing_zeros();
+            takeable &= takeable - 1;
+
+            on_successor(movers_stones, opponents_stones ^ (1 << take));
+        }
+    } else {
+        on_successor(movers_stones, opponents_stones);
+    }
+}
+
+impl EfficientPlayField {
+    /// Constructs a play field from the masks of the white and black stones, indexed by the coded index
+    pub fn from_masks(white_stones: u32, black_stones: u32) -> EfficientPlayField {
+        assert!(white_stones & black_stones == 0, "Fields can't be both white and black");
+
+        let mut play_field_state = 0u64;
+        for field in 0..FIELD_COUNT {
+            if white_stones & (1 << field) != 0 {
+                play_field_state |= 1u64 << (field * 2);
+            } else if black_stones & (1 << field) != 0 {
+                play_field_state |= 2u64 << (field * 2);
+            }
+        }
+
+        // Same layout as in [from_coded]: the coding starts with the outest ring
+        EfficientPlayField {
+            state: [
+                (play_field_state >> 32) as u16,
+                (play_field_state >> 16) as u16,
+                play_field_state as u16,
+            ],
+        }
+    }
+
+    /// Returns the masks of the `(white, black)` stones, indexed by the coded index
+    pub fn get_masks(&self) -> (u32, u32) {
+        let play_field_state = (self.state[2] as u64) | ((self.state[1] as u64) << 16) | ((self.state[0] as u64) << 32);
+
+        let mut masks = (0u32, 0u32);
+        for field in 0..FIELD_COUNT {
+            match (play_field_state >> (field * 2)) & 0x03 {
+                1 => masks.0 |= 1 << field,
+                2 => masks.1 |= 1 << field,
+                _ => {}
+            }
+        }
+        masks
+    }
+
+    /// Returns the masks of the stones as `(player_color's stones, opponent's stones)`
+    pub fn get_masks_of(&self, player_color: PlayerColor) -> (u32, u32) {
+        let (white_stones, black_stones) = self.get_masks();
+        match player_color {
+            PlayerColor::White => (white_stones, black_stones),
+            PlayerColor::Black => (black_stones, white_stones),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjacency_is_symmetric() {
+        for (field, &neighbors) in ADJACENT.iter().enumerate() {
+            let mut neighbors = neighbors;
+            assert!(matches!(neighbors.count_ones(), 2..=4));
+
+            while neighbors != 0 {
+                let neighbor = neighbors.trailing_zeros() as usize;
+                neighbors &= neighbors - 1;
+                assert!(ADJACENT[neighbor] & (1 << field) != 0);
+            }
+        }
+    }
+
+    #[test]
+    fn every_field_is_in_two_mills() {
+        for field in 0..FIELD_COUNT {
+            assert_eq!(MILLS.iter().filter(|&&mill| mill & (1 << field) != 0).count(), 2);
+        }
+    }
+
+    #[test]
+    fn masks_round_trip() {
+        let epf = EfficientPlayField::from_coded("BBEEEEEBEEEEWEWWBWWEEEBE");
+        let (white_stones, black_stones) = epf.get_masks();
+
+        assert_eq!(white_stones.count_ones(), 5);
+        assert_eq!(black_stones.count_ones(), 5);
+        assert!(EfficientPlayField::from_masks(white_stones, black_stones) == epf);
+    }
+
+    #[test]
+    fn takes_only_from_mills_if_nothing_else_is_left() {
+        let mill = MILLS[0];
+        assert_eq!(takeable_stones(mill), mill);
+        assert_eq!(takeable_stones(mill | 1 << 20), 1 << 20);
+    }
+
+    #[test]
+    fn coded_fields_match_the_coding() {
+        // Every field of the PlayField notation occurs exactly once
+        for field in crate::game::state::representation::constants::FIELD_LUT {
+            assert!(coded_index_of(field).is_some());
+        }
+
+        let epf = EfficientPlayField::from_coded("EEEEEWEEEEEEEEEEEEEEEEEB");
+        assert_eq!(CODED_FIELDS[epf.get_masks().0.trailing_zeros() as usize], ('A', 1));
+        assert_eq!(CODED_FIELDS[epf.get_masks().1.trailing_zeros() as usize], ('C', 5));
+    }
+
+    #[test]
+    fn generated_moves_match_successors() {
+        let (white_stones, black_stones) = EfficientPlayField::from_coded("WWEEEWWEBBEEEBEBEEEEEEEE").get_masks();
+
+        let mut successors = 0;
+        for_each_move_successor(white_stones, black_stones, |_, _| successors += 1);
+        assert_eq!(generate_moves(white_stones, black_stones, false).len(), successors);
+
+        // With less than 3 stones the solver's successors don't jump, neither do the generated moves
+        let two_stones = (1 << 7) | (1 << 12);
+        let mut successors = 0;
+        for_each_move_successor(two_stones, black_stones, |_, _| successors += 1);
+        assert_eq!(generate_moves(two_stones, black_stones, false).len(), successors);
+        assert!(!can_jump(two_stones));
+
+        let placements = generate_moves(white_stones, black_stones, true);
+        assert!(placements.iter().all(|placement| placement.start.is_none()));
+        assert_eq!(
+            placements.iter().filter(|placement| placement.take.is_none()).count(),
+            16 - 1
+        );
+    }
+
+    #[test]
+    fn move_into_mill_yields_takes() {
+        // White: outer ring top left corner & top right corner, middle field of the middle ring; black: 3 stones
+        let white_stones = (1 << 7) | (1 << 1) | (1 << 8);
+        let black_stones = (1 << 20) | (1 << 21) | (1 << 22);
+
+        let mut successors = Vec::new();
+        let moves = for_each_move_successor(white_stones, black_stones, |w, b| successors.push((w, b)));
+
+        // With 3 stones white jumps: 3 stones * 18 free fields
+        assert_eq!(moves, 3 * 18);
+        assert!(successors
+            .iter()
+            .any(|&(w, b)| w & MILLS[3] == MILLS[3] && b.count_ones() == 2));
+    }
+}
@@ -0,0 +1,202 @@
+//! Orders of the fields in E/W/B board codes. [EfficientPlayField::from_coded] reads the rings from the outer one
+//! inwards, each clockwise from its top middle field. [PlayField] stores the fields row by row from A1 like
+//! [FIELD_LUT]. Other tools use other orders, which can be given as custom permutation of all 24 fields.
+
+use std::{fmt::Display, str::FromStr};
+
+use super::{
+    moves::{coded_index_of, CODED_FIELDS},
+    CodedError, EfficientPlayField,
+};
+use crate::game::{notation, state::representation::constants::FIELD_LUT, Field};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// The order of [EfficientPlayField::from_coded]: D7 G7 G4 G1 D1 A1 A4 A7 D6 ... C5
+    RingClockwise,
+    /// The order of [FIELD_LUT]: A1 D1 G1 B2 D2 F2 ... G7
+    RowMajor,
+    /// The field of each character of the code
+    Custom([Field; 24]),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldOrderError {
+    UnknownName(String),
+    /// A custom order must have 24 fields
+    WrongLength(usize),
+    InvalidField {
+        index: usize,
+        message: String,
+    },
+    DuplicateField(Field),
+}
+
+impl Display for FieldOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldOrderError::UnknownName(name) => write!(
+                f,
+                "Unknown order '{name}', expected ring, row or 24 comma separated fields"
+            ),
+            FieldOrderError::WrongLength(length) => write!(f, "Expected 24 fields, found {length}"),
+            FieldOrderError::InvalidField { index, message } => write!(f, "Field {}: {message}", index + 1),
+            FieldOrderError::DuplicateField((column, row)) => {
+                write!(f, "{}{row} occurs twice", column.to_ascii_lowercase())
+            }
+        }
+    }
+}
+
+impl FieldOrder {
+    /// Checks that every field occurs exactly once
+    pub fn custom(fields: [Field; 24]) -> Result<Self, FieldOrderError> {
+        for (index, field) in fields.iter().enumerate() {
+            if coded_index_of(*field).is_none() {
+                return Err(FieldOrderError::InvalidField {
+                    index,
+                    message: format!("{}{} is no field of the play field", field.0, field.1),
+                });
+            }
+            if fields[..index].contains(field) {
+                return Err(FieldOrderError::DuplicateField(*field));
+            }
+        }
+        Ok(FieldOrder::Custom(fields))
+    }
+
+    /// The field of each character of a code
+    pub fn fields(&self) -> &[Field; 24] {
+        match self {
+            FieldOrder::RingClockwise => &CODED_FIELDS,
+            FieldOrder::RowMajor => &FIELD_LUT,
+            FieldOrder::Custom(fields) => fields,
+        }
+    }
+
+    /// Reads a code in this order, the error positions refer to the given code
+    pub fn import(&self, code: &str) -> Result<EfficientPlayField, CodedError> {
+        // Checks length & characters
+        EfficientPlayField::try_from_coded(code)?;
+
+        let mut ring_clockwise = ['E'; 24];
+        for (field, state) in self.fields().iter().zip(code.chars()) {
+            ring_clockwise[coded_index_of(*field).unwrap()] = state;
+        }
+        Ok(EfficientPlayField::from_coded(&String::from_iter(ring_clockwise)))
+    }
+
+    pub fn export(&self, board: &EfficientPlayField) -> String {
+        let ring_clockwise: Vec<char> = board.to_string_representation().chars().collect();
+        self.fields()
+            .iter()
+            .map(|field| ring_clockwise[coded_index_of(*field).unwrap()])
+            .collect()
+    }
+}
+
+impl FromStr for FieldOrder {
+    type Err = FieldOrderError;
+
+    /// `ring`, `row` or the fields of a custom order separated by commas or whitespace, like `a1,d1,g1,...`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "ring" | "ring-clockwise" => return Ok(FieldOrder::RingClockwise),
+            "row" | "row-major" => return Ok(FieldOrder::RowMajor),
+            _ => {}
+        }
+
+        let names: Vec<&str> = text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if names.len() == 1 {
+            return Err(FieldOrderError::UnknownName(text.trim().to_string()));
+        } else if names.len() != 24 {
+            return Err(FieldOrderError::WrongLength(names.len()));
+        }
+
+        let mut fields = [('A', 1); 24];
+        for (index, name) in names.iter().enumerate() {
+            fields[index] = notation::parse_field(name, 0).map_err(|error| FieldOrderError::InvalidField {
+                index,
+                message: error.to_string(),
+            })?;
+        }
+        FieldOrder::custom(fields)
+    }
+}
+
+impl Display for FieldOrder {
+    /// Parseable again by [FieldOrder::from_str]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldOrder::RingClockwise => f.write_str("ring"),
+            FieldOrder::RowMajor => f.write_str("row"),
+            FieldOrder::Custom(fields) => {
+                let names: Vec<String> = fields
+                    .iter()
+                    .map(|(column, row)| format!("{}{row}", column.to_ascii_lowercase()))
+                    .collect();
+                f.write_str(&names.join(","))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "WWEEEEEEBBEEEEEEEEEEEEEW";
+
+    #[test]
+    fn ring_and_row_orders() {
+        let board = EfficientPlayField::from_coded(CODE);
+        assert_eq!(FieldOrder::RingClockwise.import(CODE).unwrap(), board);
+        assert_eq!(FieldOrder::RingClockwise.export(&board), CODE);
+
+        // D7 & G7 white, D6 & F6 black, C5 white. In rows from A1: C5 is the 16th, D6 the 20th field
+        let row_major = FieldOrder::RowMajor.export(&board);
+        assert_eq!(row_major, "EEEEEEEEEEEEEEEWEEEBBEWW");
+        assert_eq!(FieldOrder::RowMajor.import(&row_major).unwrap(), board);
+    }
+
+    #[test]
+    fn custom_orders() {
+        // Rows from the top left, like a book is read
+        let text = "a7,d7,g7,b6,d6,f6,c5,d5,e5,a4,b4,c4,e4,f4,g4,c3,d3,e3,b2,d2,f2,a1,d1,g1";
+        let order: FieldOrder = text.parse().unwrap();
+        assert_eq!(order.fields()[3], ('B', 6));
+        assert_eq!(order.to_string(), text);
+        assert_eq!(text.replace(',', " ").parse::<FieldOrder>().unwrap(), order);
+
+        let board = EfficientPlayField::from_coded(CODE);
+        assert_eq!(order.export(&board), "EWWEBBWEEEEEEEEEEEEEEEEE");
+        assert_eq!(order.import(&order.export(&board)).unwrap(), board);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            "diagonal".parse::<FieldOrder>(),
+            Err(FieldOrderError::UnknownName("diagonal".to_string()))
+        );
+        assert_eq!("a1,d1".parse::<FieldOrder>(), Err(FieldOrderError::WrongLength(2)));
+
+        let mut fields = FIELD_LUT;
+        fields[1] = ('A', 1);
+        assert_eq!(
+            FieldOrder::custom(fields),
+            Err(FieldOrderError::DuplicateField(('A', 1)))
+        );
+        fields[1] = ('B', 1);
+        assert!(matches!(
+            FieldOrder::custom(fields),
+            Err(FieldOrderError::InvalidField { index: 1, .. })
+        ));
+
+        let error = FieldOrder::RowMajor.import("EEX").unwrap_err();
+        assert_eq!(error, CodedError::InvalidChar { index: 2, found: 'X' });
+    }
+}
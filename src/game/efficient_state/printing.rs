@@ -95,7 +95,7 @@ impl Display for EfficientPlayField {
 impl EfficientPlayField {
     /// Converts the state of the specified index to a char (00 to '·', 01 to '●' & 10 to '○')
     fn get_field_state_as_char(&self, ring_index: usize, index: u32) -> char {
-        match ((self.state[ring_index] & (3u16 << (index * 2))) >> (index * 2)) as u16 {
+        match (self.state[ring_index] & (3u16 << (index * 2))) >> (index * 2) {
             0u16 => '·',
             1u16 => '●',
             2u16 => '○',
@@ -0,0 +1,132 @@
+//! The 16 symmetries of the play field used by [EfficientPlayField::get_canonical_form], made explicit so moves can be
+//! mapped between a play field and its canonical form.
+
+use std::fmt::Display;
+
+use super::{moves::FIELD_COUNT, EfficientPlayField};
+
+/// A symmetry is applied by first swapping the inner & outer ring, then rotating clockwise by 90° `rotations` times and
+/// last mirroring on the vertical axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Symmetry {
+    pub swap_rings: bool,
+    pub rotations: u8,
+    pub mirror: bool,
+}
+
+impl Symmetry {
+    /// All symmetries, the first one is the identity
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..16u8).map(|i| Symmetry {
+            swap_rings: i & 8 != 0,
+            rotations: (i >> 1) & 3,
+            mirror: i & 1 != 0,
+        })
+    }
+
+    pub fn apply(&self, play_field: &EfficientPlayField) -> EfficientPlayField {
+        let mut transformed = *play_field;
+
+        if self.swap_rings {
+            transformed.swap_rings();
+        }
+        transformed.rotate_self_right(self.rotations as u32);
+        if self.mirror {
+            transformed.mirror_on_y();
+        }
+        transformed
+    }
+
+    /// Returns the coded index the field with the coded index `field` is moved to by this symmetry
+    pub fn map_field(&self, field: usize) -> usize {
+        let single_stone = EfficientPlayField::from_masks(1 << field, 0);
+        self.apply(&single_stone).get_masks().0.trailing_zeros() as usize
+    }
+
+    /// Inverse of [map_field]
+    pub fn unmap_field(&self, field: usize) -> usize {
+        (0..FIELD_COUNT)
+            .find(|&original| self.map_field(original) == field)
+            .unwrap()
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut steps = Vec::new();
+        if self.swap_rings {
+            steps.push("swap inner & outer ring".to_string());
+        }
+        if self.rotations != 0 {
+            steps.push(format!("rotate {}° clockwise", self.rotations as u32 * 90));
+        }
+        if self.mirror {
+            steps.push("mirror on the vertical axis".to_string());
+        }
+
+        if steps.is_empty() {
+            f.write_str("identity")
+        } else {
+            f.write_str(&steps.join(", then "))
+        }
+    }
+}
+
+impl EfficientPlayField {
+    /// Like [get_canonical_form], but also returns the symmetry mapping this play field onto its canonical form
+    pub fn get_canonical_form_with_symmetry(&self) -> (EfficientPlayField, Symmetry) {
+        Symmetry::all().map(|symmetry| (symmetry.apply(self), symmetry)).fold(
+            (EfficientPlayField::default(), Symmetry::default()),
+            |max, candidate| {
+                if max.0 < candidate.0 {
+                    candidate
+                } else {
+                    max
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_canonical_form() {
+        for coded in [
+            "BBEEEEEBEEEEWEWWBWWEEEBE",
+            "BWEWEEWEBEBBEBWEWEEBEWWB",
+            "EEBEBWWEWEWWEEEEEEEBBBEE",
+        ] {
+            let mut epf = EfficientPlayField::from_coded(coded);
+            let (canonical_form, symmetry) = epf.get_canonical_form_with_symmetry();
+
+            assert!(canonical_form == epf.get_canonical_form());
+            assert!(symmetry.apply(&epf) == canonical_form);
+        }
+    }
+
+    #[test]
+    fn symmetries_permute_fields() {
+        for symmetry in Symmetry::all() {
+            let mut images = 0u32;
+            for field in 0..FIELD_COUNT {
+                images |= 1 << symmetry.map_field(field);
+                assert_eq!(symmetry.unmap_field(symmetry.map_field(field)), field);
+            }
+            assert_eq!(images, (1 << FIELD_COUNT) - 1);
+        }
+    }
+
+    #[test]
+    fn rotation_is_clockwise() {
+        let rotation = Symmetry {
+            rotations: 1,
+            ..Default::default()
+        };
+        // Outer ring's top middle to its right middle
+        assert_eq!(rotation.map_field(0), 2);
+        assert_eq!(rotation.to_string(), "rotate 90° clockwise");
+    }
+}
@@ -0,0 +1,155 @@
+//! Events of a game as it happens, for everything reacting to a game without being part of its loop: logs, statistics,
+//! sounds or spectators. Observers are registered at the coordinator of the game.
+//!
+//! Each event displays as one line of a log:
+//!
+//! ```text
+//! Alice (white) against Bob (black) from EEEEEEEEEEEEEEEEEEEEEEEE w 9 9 - 0 1
+//! White placed on d7
+//! Black moved d6-d5
+//! White closed a mill on a7, d7, g7
+//! White took d5
+//! Black is in the jump phase
+//! Game over: 1-0 by too few stones
+//! ```
+
+use std::{fmt::Display, io::Write};
+
+use smallvec::SmallVec;
+
+use super::{
+    position::{ExtendedPosition, Phase},
+    record::{GameResult, Termination},
+    Field, PlayerColor,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    /// A new game started or a saved one continues from the position
+    Started {
+        white: String,
+        black: String,
+        position: ExtendedPosition,
+    },
+    StonePlaced {
+        color: PlayerColor,
+        field: Field,
+    },
+    /// Moves & jumps alike
+    StoneMoved {
+        color: PlayerColor,
+        from: Field,
+        to: Field,
+    },
+    /// The fields of the closed mills, like [crate::game::state::PlayField::get_mill_crossing] returns them. Two mills
+    /// closed at once hold six fields.
+    MillClosed {
+        color: PlayerColor,
+        fields: SmallVec<[Field; 3]>,
+    },
+    /// The stone of the opponent taken by `color`
+    StoneTaken {
+        color: PlayerColor,
+        field: Field,
+    },
+    /// The set phase ended or a player is down to 3 stones
+    PhaseChanged {
+        color: PlayerColor,
+        phase: Phase,
+    },
+    /// Games stopped before their end finish with [GameResult::Ongoing] & [Termination::Unterminated]
+    Ended {
+        result: GameResult,
+        termination: Termination,
+    },
+}
+
+impl Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let field = |(column, row): Field| format!("{}{row}", column.to_ascii_lowercase());
+
+        match self {
+            GameEvent::Started { white, black, position } => {
+                write!(f, "{white} (white) against {black} (black) from {position}")
+            }
+            GameEvent::StonePlaced { color, field: placed } => write!(f, "{color:?} placed on {}", field(*placed)),
+            GameEvent::StoneMoved { color, from, to } => {
+                write!(f, "{color:?} moved {}-{}", field(*from), field(*to))
+            }
+            GameEvent::MillClosed { color, fields } => {
+                let fields: Vec<String> = fields.iter().map(|closed| field(*closed)).collect();
+                write!(f, "{color:?} closed a mill on {}", fields.join(", "))
+            }
+            GameEvent::StoneTaken { color, field: taken } => write!(f, "{color:?} took {}", field(*taken)),
+            GameEvent::PhaseChanged { color, phase } => {
+                write!(f, "{color:?} is in the {} phase", format!("{phase:?}").to_lowercase())
+            }
+            GameEvent::Ended { result, termination } => write!(f, "Game over: {result} by {termination}"),
+        }
+    }
+}
+
+/// Receives the events of a game in their order. Closures taking a `&GameEvent` are observers too.
+pub trait GameObserver {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+impl<F: FnMut(&GameEvent)> GameObserver for F {
+    fn notify(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+/// Writes each event as a line, flushed right away so the log can be followed while the game runs. Failed writes are
+/// ignored, a broken log shouldn't stop the game.
+pub struct EventLog<W: Write> {
+    output: W,
+}
+
+impl<W: Write> EventLog<W> {
+    pub fn new(output: W) -> Self {
+        EventLog { output }
+    }
+}
+
+impl<W: Write> GameObserver for EventLog<W> {
+    fn notify(&mut self, event: &GameEvent) {
+        let _ = writeln!(self.output, "{event}").and_then(|_| self.output.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+
+    #[test]
+    fn log_lines() {
+        let mut output = Vec::new();
+        let mut log = EventLog::new(&mut output);
+        log.notify(&GameEvent::StoneMoved {
+            color: PlayerColor::Black,
+            from: ('D', 6),
+            to: ('D', 5),
+        });
+        log.notify(&GameEvent::MillClosed {
+            color: PlayerColor::White,
+            fields: smallvec![('A', 7), ('D', 7), ('G', 7)],
+        });
+        log.notify(&GameEvent::PhaseChanged {
+            color: PlayerColor::Black,
+            phase: Phase::Jump,
+        });
+        log.notify(&GameEvent::Ended {
+            result: GameResult::WhiteWins,
+            termination: Termination::TooFewStones,
+        });
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Black moved d6-d5\nWhite closed a mill on a7, d7, g7\nBlack is in the jump phase\n\
+             Game over: 1-0 by too few stones\n"
+        );
+    }
+}
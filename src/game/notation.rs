@@ -0,0 +1,310 @@
+//! The move notation used in the mill literature: a placement is written as its target field like `a1`, a slide or jump
+//! as `a1-d1` and a taken stone is appended with `x` like in `a1-d1xg7`. Columns are accepted in lower & upper case,
+//! the formatting uses lower case.
+
+use std::{fmt::Display, str::FromStr};
+
+use super::{
+    efficient_state::moves::{coded_index_of, EfficientMove, ADJACENT, CODED_FIELDS},
+    state::representation::constants::FIELD_LUT,
+    Field,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    /// None for placements of the set phase
+    pub start: Option<Field>,
+    pub target: Field,
+    pub take: Option<Field>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveKind {
+    Placement,
+    Slide,
+    /// A move to a field which isn't adjacent, only allowed with 3 stones left
+    Jump,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationErrorKind {
+    Empty,
+    UnexpectedEnd,
+    InvalidColumn(char),
+    InvalidRow(char),
+    /// Column & row are valid on their own, but there is no field on their crossing like on `b1`
+    NoSuchField(Field),
+    UnexpectedChar(char),
+    SameStartAndTarget,
+}
+
+/// A malformed move, `position` is the index of the offending character in the parsed string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationError {
+    pub position: usize,
+    pub kind: NotationErrorKind,
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            NotationErrorKind::Empty => return f.write_str("The move is empty"),
+            NotationErrorKind::UnexpectedEnd => f.write_str("The move ends unexpectedly")?,
+            NotationErrorKind::InvalidColumn(c) => write!(f, "'{c}' is no column, expected a - g")?,
+            NotationErrorKind::InvalidRow(c) => write!(f, "'{c}' is no row, expected 1 - 7")?,
+            NotationErrorKind::NoSuchField((column, row)) => {
+                write!(f, "{}{row} is no field of the play field", column.to_ascii_lowercase())?
+            }
+            NotationErrorKind::UnexpectedChar(c) => write!(f, "Unexpected character '{c}'")?,
+            NotationErrorKind::SameStartAndTarget => f.write_str("Start & target of the move are the same field")?,
+        }
+        write!(f, " at position {}", self.position + 1)
+    }
+}
+
+/// Parses a single field like `a1` or `A1`, `offset` is added to the positions of errors
+pub fn parse_field(text: &str, offset: usize) -> Result<Field, NotationError> {
+    let error = |position: usize, kind| NotationError {
+        position: offset + position,
+        kind,
+    };
+
+    let mut chars = text.chars();
+    let column = chars.next().ok_or(error(0, NotationErrorKind::UnexpectedEnd))?;
+    if !('a'..='g').contains(&column.to_ascii_lowercase()) {
+        return Err(error(0, NotationErrorKind::InvalidColumn(column)));
+    }
+    let row = chars.next().ok_or(error(1, NotationErrorKind::UnexpectedEnd))?;
+    if !('1'..='7').contains(&row) {
+        return Err(error(1, NotationErrorKind::InvalidRow(row)));
+    }
+    if let Some(unexpected) = chars.next() {
+        return Err(error(2, NotationErrorKind::UnexpectedChar(unexpected)));
+    }
+
+    let field = (column.to_ascii_uppercase(), row as u8 - b'0');
+    if FIELD_LUT.contains(&field) {
+        Ok(field)
+    } else {
+        Err(error(0, NotationErrorKind::NoSuchField(field)))
+    }
+}
+
+impl FromStr for Move {
+    type Err = NotationError;
+
+    /// Surrounding whitespace is ignored, the error positions refer to the string as passed
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let offset = text.len() - text.trim_start().len();
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Err(NotationError {
+                position: offset,
+                kind: NotationErrorKind::Empty,
+            });
+        }
+        if let Some(position) = trimmed.find(|c: char| !c.is_ascii()) {
+            return Err(NotationError {
+                position: offset + trimmed[..position].chars().count(),
+                kind: NotationErrorKind::UnexpectedChar(trimmed[position..].chars().next().unwrap()),
+            });
+        }
+
+        // Every field has 2 characters, so the separators can only be at the positions 2 & 5
+        let field_at = |position: usize| -> Result<Field, NotationError> {
+            let end = (position + 2).min(trimmed.len());
+            parse_field(&trimmed[position..end], offset + position)
+        };
+        let separator_at = |position: usize| trimmed[position..].chars().next();
+
+        let mut position = 0;
+        let mut start = None;
+        let mut target = field_at(0)?;
+        position += 2;
+
+        if separator_at(position) == Some('-') {
+            start = Some(target);
+            target = field_at(position + 1)?;
+            if start == Some(target) {
+                return Err(NotationError {
+                    position: offset + position + 1,
+                    kind: NotationErrorKind::SameStartAndTarget,
+                });
+            }
+            position += 3;
+        }
+
+        let mut take = None;
+        if matches!(separator_at(position), Some('x' | 'X')) {
+            take = Some(field_at(position + 1)?);
+            position += 3;
+        }
+
+        match separator_at(position) {
+            None => Ok(Move { start, target, take }),
+            Some(unexpected) => Err(NotationError {
+                position: offset + position,
+                kind: NotationErrorKind::UnexpectedChar(unexpected),
+            }),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let write_field =
+            |f: &mut std::fmt::Formatter<'_>, (column, row): Field| write!(f, "{}{row}", column.to_ascii_lowercase());
+
+        if let Some(start) = self.start {
+            write_field(f, start)?;
+            f.write_str("-")?;
+        }
+        write_field(f, self.target)?;
+        if let Some(take) = self.take {
+            f.write_str("x")?;
+            write_field(f, take)?;
+        }
+        Ok(())
+    }
+}
+
+impl Move {
+    pub fn placement(target: Field) -> Self {
+        Move {
+            start: None,
+            target,
+            take: None,
+        }
+    }
+
+    pub fn kind(&self) -> MoveKind {
+        match self.start {
+            None => MoveKind::Placement,
+            Some(start) => {
+                let (start, target) = (coded_index_of(start).unwrap(), coded_index_of(self.target).unwrap());
+                if ADJACENT[start] & (1 << target) != 0 {
+                    MoveKind::Slide
+                } else {
+                    MoveKind::Jump
+                }
+            }
+        }
+    }
+
+    /// Returns the move addressed by coded indices, None if a field isn't on the play field
+    pub fn to_efficient(&self) -> Option<EfficientMove> {
+        let coded = |field: Field| coded_index_of(field).map(|index| index as u8);
+
+        Some(EfficientMove {
+            start: match self.start {
+                Some(start) => Some(coded(start)?),
+                None => None,
+            },
+            target: coded(self.target)?,
+            take: match self.take {
+                Some(take) => Some(coded(take)?),
+                None => None,
+            },
+        })
+    }
+}
+
+impl From<EfficientMove> for Move {
+    fn from(efficient_move: EfficientMove) -> Self {
+        Move {
+            start: efficient_move.start.map(|start| CODED_FIELDS[start as usize]),
+            target: CODED_FIELDS[efficient_move.target as usize],
+            take: efficient_move.take.map(|take| CODED_FIELDS[take as usize]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_of(text: &str) -> NotationError {
+        text.parse::<Move>().unwrap_err()
+    }
+
+    #[test]
+    fn parse_and_format() {
+        for (text, expected) in [
+            ("a1", Move::placement(('A', 1))),
+            (
+                "A1-D1",
+                Move {
+                    start: Some(('A', 1)),
+                    target: ('D', 1),
+                    take: None,
+                },
+            ),
+            (
+                " a1-d1Xg7 ",
+                Move {
+                    start: Some(('A', 1)),
+                    target: ('D', 1),
+                    take: Some(('G', 7)),
+                },
+            ),
+            (
+                "d6xf4",
+                Move {
+                    start: None,
+                    target: ('D', 6),
+                    take: Some(('F', 4)),
+                },
+            ),
+        ] {
+            let parsed: Move = text.parse().unwrap();
+            assert_eq!(parsed, expected);
+            assert_eq!(parsed.to_string(), text.trim().to_lowercase());
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!("a1".parse::<Move>().unwrap().kind(), MoveKind::Placement);
+        assert_eq!("a1-d1".parse::<Move>().unwrap().kind(), MoveKind::Slide);
+        assert_eq!("a1-g7".parse::<Move>().unwrap().kind(), MoveKind::Jump);
+        // Same row, but not adjacent
+        assert_eq!("a4-c4".parse::<Move>().unwrap().kind(), MoveKind::Jump);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_character() {
+        assert_eq!(error_of("").kind, NotationErrorKind::Empty);
+        assert_eq!(error_of("h1").position, 0);
+        assert_eq!(error_of("a8").kind, NotationErrorKind::InvalidRow('8'));
+        assert_eq!(error_of("a8").position, 1);
+        assert_eq!(error_of("a1-b1").kind, NotationErrorKind::NoSuchField(('B', 1)));
+        assert_eq!(error_of("a1-b1").position, 3);
+        assert_eq!(error_of("a1-d1xg").kind, NotationErrorKind::UnexpectedEnd);
+        assert_eq!(error_of("a1-d1xg").position, 7);
+        assert_eq!(error_of("a1+d1").kind, NotationErrorKind::UnexpectedChar('+'));
+        assert_eq!(error_of("  a1-a1").position, 5);
+        assert_eq!(error_of("a1xd1xg7").position, 5);
+        assert_eq!(
+            error_of("a1-b1").to_string(),
+            "b1 is no field of the play field at position 4"
+        );
+    }
+
+    #[test]
+    fn efficient_round_trip() {
+        let parsed: Move = "d7-a7xd6".parse().unwrap();
+        let efficient = parsed.to_efficient().unwrap();
+
+        assert_eq!(
+            efficient,
+            EfficientMove {
+                start: Some(0),
+                target: 7,
+                take: Some(8)
+            }
+        );
+        assert_eq!(Move::from(efficient), parsed);
+        assert_eq!(Move::placement(('B', 1)).to_efficient(), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! A complete state of the game on top of [EfficientPlayField]: besides the stones on the play field it knows whose
+//! turn it is & how many stones each player still has to place.
+
+mod de_encode;
+
+pub use de_encode::{ExtendedPosition, PackedError, PositionStringError};
+
+use super::{
+    efficient_state::{
+        moves::{generate_moves, EfficientMove},
+        EfficientPlayField,
+    },
+    PlayerColor,
+};
+
+/// Amount of stones each player places in the set phase
+pub const STONES_PER_PLAYER: u8 = 9;
+
+/// The phase of the player on turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    Set,
+    Move,
+    /// Only 3 stones are left, which may move to any free field
+    Jump,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub board: EfficientPlayField,
+    pub side_to_move: PlayerColor,
+    /// Stones still to be placed as (white, black)
+    pub stones_in_hand: (u8, u8),
+}
+
+impl Default for Position {
+    /// The empty play field, white begins
+    fn default() -> Self {
+        Self {
+            board: EfficientPlayField::default(),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (STONES_PER_PLAYER, STONES_PER_PLAYER),
+        }
+    }
+}
+
+impl Position {
+    pub fn stones_in_hand_of(&self, color: PlayerColor) -> u8 {
+        match color {
+            PlayerColor::White => self.stones_in_hand.0,
+            PlayerColor::Black => self.stones_in_hand.1,
+        }
+    }
+
+    pub fn stones_on_board_of(&self, color: PlayerColor) -> u8 {
+        self.board.get_masks_of(color).0.count_ones() as u8
+    }
+
+    /// True as long as the player on turn has stones left to place
+    pub fn is_set_phase(&self) -> bool {
+        self.stones_in_hand_of(self.side_to_move) != 0
+    }
+
+    pub fn phase(&self) -> Phase {
+        if self.is_set_phase() {
+            Phase::Set
+        } else if self.stones_on_board_of(self.side_to_move) == 3 {
+            Phase::Jump
+        } else {
+            Phase::Move
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<EfficientMove> {
+        let (movers_stones, opponents_stones) = self.board.get_masks_of(self.side_to_move);
+        generate_moves(movers_stones, opponents_stones, self.is_set_phase())
+    }
+
+    /// Checks that the stones could have come about in a game: no player has more than 9 stones on the board & in hand
+    /// together, and the hands are in step with the side to move. White places first, so black has as many stones in
+    /// hand as white, or one more if black is on turn.
+    pub fn check_stone_counts(&self) -> Result<(), String> {
+        for color in [PlayerColor::White, PlayerColor::Black] {
+            let (on_board, in_hand) = (self.stones_on_board_of(color), self.stones_in_hand_of(color));
+            if STONES_PER_PLAYER < on_board + in_hand {
+                return Err(format!(
+                    "{color:?} has {on_board} stones on the board & {in_hand} in hand, more than {STONES_PER_PLAYER}"
+                ));
+            }
+        }
+
+        let (white_hand, black_hand) = self.stones_in_hand;
+        let hands_in_step = match self.side_to_move {
+            _ if self.stones_in_hand == (0, 0) => true,
+            PlayerColor::White => white_hand == black_hand,
+            PlayerColor::Black => white_hand + 1 == black_hand,
+        };
+        if !hands_in_step {
+            return Err(format!(
+                "Black must have as many stones in hand as white, or one more if black is on turn, not {white_hand} & \
+                 {black_hand}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the winner, if the player on turn has lost: either by having less than 3 stones or by being unable to move
+    pub fn winner(&self) -> Option<PlayerColor> {
+        let color = self.side_to_move;
+        if self.stones_on_board_of(color) + self.stones_in_hand_of(color) < 3 || self.legal_moves().is_empty() {
+            Some(!color)
+        } else {
+            None
+        }
+    }
+
+    /// Plays the move for the player on turn, which must be one of [legal_moves]
+    pub fn play(&mut self, played_move: EfficientMove) {
+        debug_assert!(self.legal_moves().contains(&played_move));
+
+        let (mut movers_stones, mut opponents_stones) = self.board.get_masks_of(self.side_to_move);
+        match played_move.start {
+            Some(start) => movers_stones ^= (1 << start) | (1 << played_move.target),
+            None => {
+                movers_stones |= 1 << played_move.target;
+                match self.side_to_move {
+                    PlayerColor::White => self.stones_in_hand.0 -= 1,
+                    PlayerColor::Black => self.stones_in_hand.1 -= 1,
+                }
+            }
+        }
+        if let Some(take) = played_move.take {
+            opponents_stones &= !(1 << take);
+        }
+
+        self.board = match self.side_to_move {
+            PlayerColor::White => EfficientPlayField::from_masks(movers_stones, opponents_stones),
+            PlayerColor::Black => EfficientPlayField::from_masks(opponents_stones, movers_stones),
+        };
+        self.side_to_move = !self.side_to_move;
+    }
+
+    /// Returns the position after playing the move
+    pub fn after(&self, played_move: EfficientMove) -> Position {
+        let mut position = *self;
+        position.play(played_move);
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_phase_ends_after_placing_all_stones() {
+        let mut position = Position::default();
+
+        for _ in 0..2 * STONES_PER_PLAYER {
+            assert!(position.is_set_phase());
+            let placement = *position
+                .legal_moves()
+                .iter()
+                .find(|placement| placement.take.is_none())
+                .unwrap();
+            position.play(placement);
+        }
+
+        assert!(!position.is_set_phase());
+        assert_eq!(position.side_to_move, PlayerColor::White);
+        assert!(position
+            .legal_moves()
+            .iter()
+            .all(|legal_move| legal_move.start.is_some()));
+    }
+
+    #[test]
+    fn player_with_two_stones_lost() {
+        let position = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBBEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (0, 0),
+        };
+        assert_eq!(position.winner(), Some(PlayerColor::Black));
+    }
+
+    #[test]
+    fn impossible_stone_counts() {
+        assert_eq!(Position::default().check_stone_counts(), Ok(()));
+
+        let twelve_white = Position {
+            board: EfficientPlayField::from_coded("WWWWWWWWWWWWEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (0, 0),
+        };
+        assert!(twelve_white.check_stone_counts().unwrap_err().contains("more than 9"));
+
+        let out_of_step = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBEEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (7, 5),
+        };
+        assert!(out_of_step.check_stone_counts().is_err());
+        let black_to_place = Position {
+            side_to_move: PlayerColor::Black,
+            stones_in_hand: (6, 7),
+            ..out_of_step
+        };
+        assert_eq!(black_to_place.check_stone_counts(), Ok(()));
+    }
+}
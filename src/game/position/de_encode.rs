@@ -0,0 +1,562 @@
+//! Encodings of a [Position].
+//!
+//! The extended position notation adds everything to the 24 character code of [EfficientPlayField::from_coded]
+//! needed to continue a game from it:
+//!
+//! ```text
+//! <board> <side to move> <white in hand> <black in hand> [<capture>] [<plies since take> <move number>]
+//! WWEEEEEEBBEEEEEEEEEEEEEE w 7 7
+//! WWEEEEEWBBEEEEEEEEEEEEEE w 6 7 x 0 3
+//! ```
+//!
+//! The side to move is `w` or `b`. The capture flag is `x` if the side to move closed a mill & still has to take a
+//! stone, `-` otherwise. The stones in hand are counted after the stone closing the mill was placed, so white taking
+//! with its third stone has `6 7` in hand like black on turn after it. The counters count the plies since the last stone
+//! was taken & the moves, starting at 1 and increased after each move of black. Missing optional fields default to
+//! `-`, `0` and `1`.
+//!
+//! For databases & the network a position is packed into an u64 instead:
+//!
+//! ```text
+//! bits  0 - 47: the board, two bits per field like EfficientPlayField::to_bits
+//! bit       48: the side to move, 0 for white
+//! bits 49 - 52: the stones in hand of white
+//! bits 53 - 56: the stones in hand of black
+//! bits 57 - 58: the phase of the side to move: 00 set, 01 move, 10 jump
+//! bits 59 - 63: always 0
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use super::{Phase, Position, STONES_PER_PLAYER};
+use crate::game::{
+    efficient_state::{moves::EfficientMove, CodedError, EfficientPlayField},
+    PlayerColor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtendedPosition {
+    pub position: Position,
+    /// The side to move closed a mill & has to take a stone before the opponent moves
+    pub pending_capture: bool,
+    pub plies_since_take: u32,
+    pub move_number: u32,
+}
+
+impl Default for ExtendedPosition {
+    fn default() -> Self {
+        ExtendedPosition {
+            position: Position::default(),
+            pending_capture: false,
+            plies_since_take: 0,
+            move_number: 1,
+        }
+    }
+}
+
+/// A malformed position string. `field` is the index of the whitespace separated field (counting from 1) and `position`
+/// the index of the offending character in the whole string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionStringError {
+    pub field: usize,
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for PositionStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (field {}, position {})",
+            self.message,
+            self.field,
+            self.position + 1
+        )
+    }
+}
+
+impl ExtendedPosition {
+    /// [Position::check_stone_counts] with the hands of a pending capture, which are in step like those of the opponent
+    /// on turn after the take
+    pub fn check_stone_counts(&self) -> Result<(), String> {
+        match self.pending_capture {
+            false => self.position.check_stone_counts(),
+            true => Position {
+                side_to_move: !self.position.side_to_move,
+                ..self.position
+            }
+            .check_stone_counts(),
+        }
+    }
+
+    /// Plays the move with its counters, the move must be legal & hold its take. Pending captures are resolved with
+    /// [ExtendedPosition::after_take] instead.
+    pub fn after(&self, played_move: EfficientMove) -> ExtendedPosition {
+        debug_assert!(!self.pending_capture);
+
+        ExtendedPosition {
+            position: self.position.after(played_move),
+            pending_capture: false,
+            plies_since_take: match played_move.take {
+                Some(_) => 0,
+                None => self.plies_since_take + 1,
+            },
+            move_number: self.move_number + (self.position.side_to_move == PlayerColor::Black) as u32,
+        }
+    }
+
+    /// Resolves the pending capture by taking the opponents stone on the coded index, the opponent moves next
+    pub fn after_take(&self, take: u8) -> ExtendedPosition {
+        debug_assert!(self.pending_capture);
+
+        let position = self.position;
+        let (movers_stones, opponents_stones) = position.board.get_masks_of(position.side_to_move);
+        debug_assert!(opponents_stones & (1 << take) != 0);
+        let opponents_stones = opponents_stones & !(1 << take);
+
+        ExtendedPosition {
+            position: Position {
+                board: match position.side_to_move {
+                    PlayerColor::White => EfficientPlayField::from_masks(movers_stones, opponents_stones),
+                    PlayerColor::Black => EfficientPlayField::from_masks(opponents_stones, movers_stones),
+                },
+                side_to_move: !position.side_to_move,
+                ..position
+            },
+            pending_capture: false,
+            plies_since_take: 0,
+            move_number: self.move_number + (position.side_to_move == PlayerColor::Black) as u32,
+        }
+    }
+}
+
+/// Why an u64 isn't a packed [Position]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackedError {
+    /// A field coded as `11`, given by the ring & bit index of [EfficientPlayField]
+    ForbiddenFieldCode {
+        ring_index: usize,
+        bit_index: u8,
+    },
+    /// More than 9 stones in hand, or more than 9 stones on the board & in hand together
+    TooManyStones(PlayerColor),
+    InvalidPhase,
+    /// The phase doesn't match the stones of the side to move
+    WrongPhase {
+        packed: Phase,
+        actual: Phase,
+    },
+    UnusedBitsSet,
+}
+
+impl Display for PackedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackedError::ForbiddenFieldCode { ring_index, bit_index } => {
+                write!(f, "The field at bit {bit_index} of ring {ring_index} is coded as 11")
+            }
+            PackedError::TooManyStones(color) => write!(f, "{color:?} has more than 9 stones"),
+            PackedError::InvalidPhase => f.write_str("The phase is coded as 11"),
+            PackedError::WrongPhase { packed, actual } => {
+                write!(f, "The phase is {packed:?}, but the stones are those of {actual:?}")
+            }
+            PackedError::UnusedBitsSet => f.write_str("The unused bits 59 - 63 aren't 0"),
+        }
+    }
+}
+
+const SIDE_TO_MOVE_SHIFT: u32 = 48;
+const WHITE_HAND_SHIFT: u32 = 49;
+const BLACK_HAND_SHIFT: u32 = 53;
+const PHASE_SHIFT: u32 = 57;
+const USED_BITS: u32 = 59;
+
+impl Position {
+    pub fn to_u64(&self) -> u64 {
+        let phase = match self.phase() {
+            Phase::Set => 0u64,
+            Phase::Move => 1,
+            Phase::Jump => 2,
+        };
+
+        self.board.to_bits()
+            | ((self.side_to_move == PlayerColor::Black) as u64) << SIDE_TO_MOVE_SHIFT
+            | (self.stones_in_hand.0 as u64) << WHITE_HAND_SHIFT
+            | (self.stones_in_hand.1 as u64) << BLACK_HAND_SHIFT
+            | phase << PHASE_SHIFT
+    }
+
+    /// The inverse of [to_u64], which rejects every u64 not produced by it
+    pub fn from_u64(packed: u64) -> Result<Position, PackedError> {
+        if packed >> USED_BITS != 0 {
+            return Err(PackedError::UnusedBitsSet);
+        }
+
+        let board = EfficientPlayField::try_from_bits(packed & ((1 << SIDE_TO_MOVE_SHIFT) - 1))
+            .map_err(|(ring_index, bit_index)| PackedError::ForbiddenFieldCode { ring_index, bit_index })?;
+        let position = Position {
+            board,
+            side_to_move: match (packed >> SIDE_TO_MOVE_SHIFT) & 1 {
+                0 => PlayerColor::White,
+                _ => PlayerColor::Black,
+            },
+            stones_in_hand: (
+                ((packed >> WHITE_HAND_SHIFT) & 0x0f) as u8,
+                ((packed >> BLACK_HAND_SHIFT) & 0x0f) as u8,
+            ),
+        };
+
+        for color in [PlayerColor::White, PlayerColor::Black] {
+            let in_hand = position.stones_in_hand_of(color);
+            if STONES_PER_PLAYER < in_hand || STONES_PER_PLAYER < in_hand + position.stones_on_board_of(color) {
+                return Err(PackedError::TooManyStones(color));
+            }
+        }
+
+        let packed_phase = match (packed >> PHASE_SHIFT) & 0x03 {
+            0 => Phase::Set,
+            1 => Phase::Move,
+            2 => Phase::Jump,
+            _ => return Err(PackedError::InvalidPhase),
+        };
+        if packed_phase != position.phase() {
+            return Err(PackedError::WrongPhase {
+                packed: packed_phase,
+                actual: position.phase(),
+            });
+        }
+
+        Ok(position)
+    }
+}
+
+impl FromStr for ExtendedPosition {
+    type Err = PositionStringError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        // The fields with their character offsets, so errors can point into the string
+        let mut fields = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            let length = rest[start..].find(char::is_whitespace).unwrap_or(rest.len() - start);
+            let offset = text.len() - rest.len() + start;
+            fields.push((offset, &rest[start..start + length]));
+            rest = &rest[start + length..];
+        }
+
+        let error = |field: usize, position: usize, message: String| PositionStringError {
+            field: field + 1,
+            position,
+            message,
+        };
+
+        if !matches!(fields.len(), 4 | 5 | 7) {
+            let (offset, _) = fields.get(4).copied().unwrap_or((text.len(), ""));
+            return Err(error(
+                fields.len().min(4),
+                offset,
+                format!("Expected 4, 5 or 7 fields, found {}", fields.len()),
+            ));
+        }
+
+        let (offset, board) = fields[0];
+        let board = EfficientPlayField::try_from_coded(board).map_err(|coded_error| {
+            let position = match coded_error {
+                CodedError::InvalidChar { index, .. } => offset + index,
+                CodedError::WrongLength(_) => offset,
+            };
+            error(0, position, coded_error.to_string())
+        })?;
+
+        let (offset, side) = fields[1];
+        let side_to_move = match side {
+            "w" | "W" => PlayerColor::White,
+            "b" | "B" => PlayerColor::Black,
+            _ => {
+                return Err(error(
+                    1,
+                    offset,
+                    format!("'{side}' is no side to move, expected w or b"),
+                ))
+            }
+        };
+
+        let mut stones_in_hand = [0u8; 2];
+        for (index, color) in [(2, PlayerColor::White), (3, PlayerColor::Black)] {
+            let (offset, hand) = fields[index];
+            let in_hand = hand
+                .parse::<u8>()
+                .ok()
+                .filter(|in_hand| *in_hand <= STONES_PER_PLAYER)
+                .ok_or_else(|| {
+                    error(
+                        index,
+                        offset,
+                        format!("'{hand}' is no amount of stones between 0 and 9"),
+                    )
+                })?;
+
+            let on_board = board.get_masks_of(color).0.count_ones() as u8;
+            if STONES_PER_PLAYER < on_board + in_hand {
+                return Err(error(
+                    index,
+                    offset,
+                    format!("{color:?} has {on_board} stones on the board & {in_hand} in hand, more than 9"),
+                ));
+            }
+            stones_in_hand[index - 2] = in_hand;
+        }
+
+        let pending_capture = match fields.get(4) {
+            None | Some((_, "-")) => false,
+            Some((_, "x" | "X")) => true,
+            Some((offset, flag)) => {
+                return Err(error(
+                    4,
+                    *offset,
+                    format!("'{flag}' is no capture flag, expected x or -"),
+                ))
+            }
+        };
+
+        let mut counters = [0u32, 1u32];
+        for index in [5, 6] {
+            if let Some((offset, counter)) = fields.get(index) {
+                counters[index - 5] = counter
+                    .parse()
+                    .map_err(|_| error(index, *offset, format!("'{counter}' is no number")))?;
+            }
+        }
+        if counters[1] == 0 {
+            return Err(error(6, fields[6].0, "The move number starts at 1".to_string()));
+        }
+
+        Ok(ExtendedPosition {
+            position: Position {
+                board,
+                side_to_move,
+                stones_in_hand: (stones_in_hand[0], stones_in_hand[1]),
+            },
+            pending_capture,
+            plies_since_take: counters[0],
+            move_number: counters[1],
+        })
+    }
+}
+
+impl Display for ExtendedPosition {
+    /// Always writes all fields
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            self.position.board.to_string_representation(),
+            match self.position.side_to_move {
+                PlayerColor::White => 'w',
+                PlayerColor::Black => 'b',
+            },
+            self.position.stones_in_hand.0,
+            self.position.stones_in_hand.1,
+            if self.pending_capture { 'x' } else { '-' },
+            self.plies_since_take,
+            self.move_number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nanorand::{Rng, WyRand};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for text in [
+            "EEEEEEEEEEEEEEEEEEEEEEEE w 9 9 - 0 1",
+            "WWEEEEEWBBEEEEEEEEEEEEEE w 6 7 x 0 3",
+            "WEEEEEEWEEEEEEEWBBBEEEEE b 0 0 - 12 30",
+        ] {
+            let parsed: ExtendedPosition = text.parse().unwrap();
+            assert_eq!(parsed.to_string(), text);
+        }
+
+        assert_eq!(
+            ExtendedPosition::default().to_string(),
+            "EEEEEEEEEEEEEEEEEEEEEEEE w 9 9 - 0 1"
+        );
+    }
+
+    #[test]
+    fn optional_fields_default() {
+        let parsed: ExtendedPosition = "  WWEEEEEEBBEEEEEEEEEEEEEE B 6 7 ".parse().unwrap();
+        assert_eq!(parsed.position.side_to_move, PlayerColor::Black);
+        assert_eq!(parsed.position.stones_in_hand, (6, 7));
+        assert!(!parsed.pending_capture);
+        assert_eq!((parsed.plies_since_take, parsed.move_number), (0, 1));
+
+        let parsed: ExtendedPosition = "WWEEEEEEBBEEEEEEEEEEEEEE b 6 6 x".parse().unwrap();
+        assert!(parsed.pending_capture);
+    }
+
+    #[test]
+    fn pending_capture_is_resolved() {
+        // The coded index 8 is D6
+        let parsed: ExtendedPosition = "WWWEEEEEBBEEEEEEEEEEEEEE w 6 7 x 3 4".parse().unwrap();
+        assert_eq!(parsed.after_take(8).to_string(), "WWWEEEEEEBEEEEEEEEEEEEEE b 6 7 - 0 4");
+
+        let parsed: ExtendedPosition = "WWEEEEEEBBBEEEEEEEEEEEEE b 6 6 x 3 4".parse().unwrap();
+        assert_eq!(parsed.after_take(0).to_string(), "EWEEEEEEBBBEEEEEEEEEEEEE w 6 6 - 0 5");
+    }
+
+    #[test]
+    fn hands_of_pending_captures() {
+        for (text, in_step) in [
+            ("WWWEEEEEBBEEEEEEEEEEEEEE w 6 7 x", true),
+            ("WWWEEEEEBBEEEEEEEEEEEEEE w 6 6 x", false),
+            ("WWEEEEEEBBBEEEEEEEEEEEEE b 6 6 x", true),
+            ("WWEEEEEEBBBEEEEEEEEEEEEE b 7 6 x", false),
+            ("WWWWEEEEBBBEEEEEEEEEEEEE w 0 0 x", true),
+        ] {
+            let parsed: ExtendedPosition = text.parse().unwrap();
+            assert_eq!(parsed.check_stone_counts().is_ok(), in_step, "{text}");
+        }
+    }
+
+    #[test]
+    fn counters_after_moves() {
+        let played = |position: ExtendedPosition, text: &str| {
+            position.after(
+                text.parse::<crate::game::notation::Move>()
+                    .unwrap()
+                    .to_efficient()
+                    .unwrap(),
+            )
+        };
+        let start: ExtendedPosition = "WWEEEEEEBBEEEEEEEEEEEEEE b 7 7 - 3 4".parse().unwrap();
+        let after = played(start, "d5");
+        assert_eq!(after.to_string(), "WWEEEEEEBBEEEEEEBEEEEEEE w 7 6 - 4 5");
+        assert_eq!(
+            played(after, "a7xd6").to_string(),
+            "WWEEEEEWEBEEEEEEBEEEEEEE b 6 6 - 0 5"
+        );
+    }
+
+    fn random_position(rng: &mut WyRand) -> Position {
+        loop {
+            let code: String = (0..24)
+                .map(|_| ['E', 'W', 'B'][rng.generate_range(0..3usize)])
+                .collect();
+            let board = EfficientPlayField::from_coded(&code);
+            let (white_stones, black_stones) = board.get_masks();
+            let on_board = (white_stones.count_ones() as u8, black_stones.count_ones() as u8);
+            if STONES_PER_PLAYER < on_board.0 || STONES_PER_PLAYER < on_board.1 {
+                continue;
+            }
+
+            break Position {
+                board,
+                side_to_move: if rng.generate::<bool>() {
+                    PlayerColor::White
+                } else {
+                    PlayerColor::Black
+                },
+                stones_in_hand: (
+                    rng.generate_range(0..=STONES_PER_PLAYER - on_board.0),
+                    rng.generate_range(0..=STONES_PER_PLAYER - on_board.1),
+                ),
+            };
+        }
+    }
+
+    #[test]
+    fn packed_round_trip() {
+        let mut rng = WyRand::new_seed(38);
+        for _ in 0..10_000 {
+            let position = random_position(&mut rng);
+            let packed = position.to_u64();
+            assert_eq!(packed >> USED_BITS, 0);
+            assert_eq!(Position::from_u64(packed), Ok(position));
+        }
+
+        assert_eq!(
+            Position::default().to_u64(),
+            9 << WHITE_HAND_SHIFT | 9 << BLACK_HAND_SHIFT
+        );
+    }
+
+    #[test]
+    fn packed_values_are_validated() {
+        // Every accepted u64 must be the encoding of its position, whatever bits are changed
+        let mut rng = WyRand::new_seed(64);
+        let mut rejected = 0;
+        for _ in 0..10_000 {
+            let packed = random_position(&mut rng).to_u64() ^ (1 << rng.generate_range(0..64u64));
+            match Position::from_u64(packed) {
+                Ok(position) => assert_eq!(position.to_u64(), packed),
+                Err(_) => rejected += 1,
+            }
+        }
+        assert!(0 < rejected);
+
+        let white_on_d7 = Position::default().to_u64() | 1;
+        assert_eq!(
+            Position::from_u64(white_on_d7 | 2),
+            Err(PackedError::ForbiddenFieldCode {
+                ring_index: 2,
+                bit_index: 0
+            })
+        );
+        assert_eq!(Position::from_u64(1 << 63), Err(PackedError::UnusedBitsSet));
+        assert_eq!(
+            Position::from_u64(white_on_d7),
+            Err(PackedError::TooManyStones(PlayerColor::White))
+        );
+        assert_eq!(
+            Position::from_u64(Position::default().to_u64() | 1 << PHASE_SHIFT),
+            Err(PackedError::WrongPhase {
+                packed: Phase::Move,
+                actual: Phase::Set
+            })
+        );
+        assert_eq!(
+            Position::from_u64(Position::default().to_u64() | 3 << PHASE_SHIFT),
+            Err(PackedError::InvalidPhase)
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_field() {
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEQ w 7 7"
+            .parse::<ExtendedPosition>()
+            .unwrap_err();
+        assert_eq!((error.field, error.position), (1, 23));
+
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEE white 7 7"
+            .parse::<ExtendedPosition>()
+            .unwrap_err();
+        assert_eq!((error.field, error.position), (2, 25));
+
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEE w 7 8"
+            .parse::<ExtendedPosition>()
+            .unwrap_err();
+        assert_eq!((error.field, error.position), (4, 29));
+        assert!(error.message.contains("more than 9"));
+
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEE w 7 7 y"
+            .parse::<ExtendedPosition>()
+            .unwrap_err();
+        assert_eq!(error.field, 5);
+
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEE w 7 7 - 0"
+            .parse::<ExtendedPosition>()
+            .unwrap_err();
+        assert!(error.message.contains("found 6"));
+
+        let error = "WWEEEEEEBBEEEEEEEEEEEEEE w".parse::<ExtendedPosition>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected 4, 5 or 7 fields, found 2 (field 3, position 27)"
+        );
+    }
+}
@@ -0,0 +1,651 @@
+//! A plain text format for keeping finished & interrupted games. A header of tag pairs is followed by the numbered moves
+//! in the notation of [crate::game::notation]:
+//!
+//! ```text
+//! [White "Alice"]
+//! [Black "Bob"]
+//! [Date "2026.10.19"]
+//! [Variant "Nine Men's Morris"]
+//! [Result "1-0"]
+//! [Termination "too few stones"]
+//!
+//! 1. d7 d6 2. g7 f6 3. a7xd6 ...
+//! ```
+//!
+//! Games which don't start on the empty play field keep their start in a `[Position "..."]` tag, written in the
+//! extended position notation of [ExtendedPosition].
+//!
+//! A move may be followed by the evaluation of the engine after it, like `d7 {eval -35}`. Evaluations are in the units
+//! of [crate::engine::evaluate], but from the view of white. Games with clocks keep their time control in a
+//! `[TimeControl "300+5"]` tag & the remaining time of the player after each move in seconds, like `d7 {clock 295.2}`.
+//! Other comments in braces are skipped.
+//!
+//! Loading a record replays its moves on a [Position], so illegal moves & results which don't match the moves are
+//! detected.
+
+pub mod csv;
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    clock::{self, Clock, TimeControl},
+    notation::Move,
+    position::{ExtendedPosition, Position},
+    PlayerColor,
+};
+
+pub const VARIANT: &str = "Nine Men's Morris";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game isn't finished yet
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn won_by(color: PlayerColor) -> Self {
+        match color {
+            PlayerColor::White => GameResult::WhiteWins,
+            PlayerColor::Black => GameResult::BlackWins,
+        }
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        })
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "1-0" => Ok(GameResult::WhiteWins),
+            "0-1" => Ok(GameResult::BlackWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Ongoing),
+            _ => Err(format!("Unknown result '{text}', expected 1-0, 0-1, 1/2-1/2 or *")),
+        }
+    }
+}
+
+/// Why the game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The loser has less than 3 stones left
+    TooFewStones,
+    /// The loser can't move anymore
+    NoMovesLeft,
+    Resignation,
+    Agreement,
+    /// The loser ran out of time
+    TimeForfeit,
+    /// Drawn after too many plies without a take
+    MoveLimit,
+    /// The game was interrupted & may be resumed
+    Unterminated,
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Termination::TooFewStones => "too few stones",
+            Termination::NoMovesLeft => "no moves left",
+            Termination::Resignation => "resignation",
+            Termination::Agreement => "agreement",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::MoveLimit => "move limit",
+            Termination::Unterminated => "unterminated",
+        })
+    }
+}
+
+impl FromStr for Termination {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        [
+            Termination::TooFewStones,
+            Termination::NoMovesLeft,
+            Termination::Resignation,
+            Termination::Agreement,
+            Termination::TimeForfeit,
+            Termination::MoveLimit,
+            Termination::Unterminated,
+        ]
+        .into_iter()
+        .find(|termination| termination.to_string() == text)
+        .ok_or_else(|| format!("Unknown termination '{text}'"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    Header {
+        line: usize,
+        message: String,
+    },
+    /// `ply` counts from 1
+    Notation {
+        ply: usize,
+        message: String,
+    },
+    IllegalMove {
+        ply: usize,
+        played: Move,
+    },
+    MoveAfterEnd {
+        ply: usize,
+    },
+    ResultMismatch {
+        stated: GameResult,
+        replayed: GameResult,
+    },
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordError::Header { line, message } => write!(f, "Line {line}: {message}"),
+            RecordError::Notation { ply, message } => write!(f, "Move {ply}: {message}"),
+            RecordError::IllegalMove { ply, played } => write!(f, "Move {ply}: {played} isn't a legal move"),
+            RecordError::MoveAfterEnd { ply } => write!(f, "Move {ply}: the game was already over"),
+            RecordError::ResultMismatch { stated, replayed } => {
+                write!(
+                    f,
+                    "The record states the result {stated}, but the moves lead to {replayed}"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRecord {
+    pub white: String,
+    pub black: String,
+    /// Formatted as `YYYY.MM.DD`
+    pub date: String,
+    pub variant: String,
+    pub result: GameResult,
+    pub termination: Termination,
+    /// None for games starting on the empty play field. The start can't have a pending capture.
+    pub start: Option<ExtendedPosition>,
+    pub moves: Vec<Move>,
+    /// The evaluations after some of the moves, by their ply counting from 1
+    pub evaluations: BTreeMap<usize, i32>,
+    /// None for games without clocks
+    pub time_control: Option<TimeControl>,
+    /// The remaining time of the player after their moves, by the ply counting from 1
+    pub clocks: BTreeMap<usize, Duration>,
+}
+
+impl GameRecord {
+    /// A record of a game starting today
+    pub fn new(white: &str, black: &str) -> Self {
+        GameRecord {
+            white: white.to_string(),
+            black: black.to_string(),
+            date: today(),
+            variant: VARIANT.to_string(),
+            result: GameResult::Ongoing,
+            termination: Termination::Unterminated,
+            start: None,
+            moves: Vec::new(),
+            evaluations: BTreeMap::new(),
+            time_control: None,
+            clocks: BTreeMap::new(),
+        }
+    }
+
+    pub fn name_of(&self, color: PlayerColor) -> &str {
+        match color {
+            PlayerColor::White => &self.white,
+            PlayerColor::Black => &self.black,
+        }
+    }
+
+    /// The color playing the move of the ply counting from 1
+    pub fn color_of_ply(&self, ply: usize) -> PlayerColor {
+        let first = self
+            .start
+            .map_or(PlayerColor::White, |start| start.position.side_to_move);
+        if ply % 2 == 1 {
+            first
+        } else {
+            !first
+        }
+    }
+
+    /// The clocks after the moves so far, with the time of the last clock comment of each player. None for games
+    /// without time control.
+    pub fn clock(&self) -> Option<Clock> {
+        let mut clock = Clock::new(self.time_control?);
+        for (ply, remaining) in &self.clocks {
+            match self.color_of_ply(*ply) {
+                PlayerColor::White => clock.remaining.0 = *remaining,
+                PlayerColor::Black => clock.remaining.1 = *remaining,
+            }
+        }
+        Some(clock)
+    }
+
+    /// Plays all moves from the start & returns the positions after each of them, starting with the start itself.
+    /// Fails on the first illegal move.
+    pub fn replay(&self) -> Result<Vec<Position>, RecordError> {
+        let mut positions = vec![self.start.map_or(Position::default(), |start| start.position)];
+
+        for (ply, played) in self.moves.iter().enumerate() {
+            let position = *positions.last().unwrap();
+            if position.winner().is_some() {
+                return Err(RecordError::MoveAfterEnd { ply: ply + 1 });
+            }
+
+            let legal_move = played
+                .to_efficient()
+                .filter(|efficient_move| position.legal_moves().contains(efficient_move))
+                .ok_or(RecordError::IllegalMove {
+                    ply: ply + 1,
+                    played: *played,
+                })?;
+            positions.push(position.after(legal_move));
+        }
+        Ok(positions)
+    }
+
+    /// Replays the moves & checks that the stated result matches the end of the game. Resignations, agreements & move
+    /// limits can't be checked, as long as the moves didn't end the game already.
+    pub fn validate(&self) -> Result<Position, RecordError> {
+        let position = *self.replay()?.last().unwrap();
+
+        let replayed = position.winner().map_or(GameResult::Ongoing, GameResult::won_by);
+        let ended_by_rules = matches!(self.termination, Termination::TooFewStones | Termination::NoMovesLeft);
+        if (replayed != GameResult::Ongoing || ended_by_rules) && replayed != self.result {
+            return Err(RecordError::ResultMismatch {
+                stated: self.result,
+                replayed,
+            });
+        }
+        Ok(position)
+    }
+
+    /// Sets result & termination, if the moves ended the game by the rules
+    pub fn update_result(&mut self) -> Result<(), RecordError> {
+        let position = *self.replay()?.last().unwrap();
+
+        if let Some(winner) = position.winner() {
+            let losers_stones = position.stones_on_board_of(!winner) + position.stones_in_hand_of(!winner);
+            self.result = GameResult::won_by(winner);
+            self.termination = if losers_stones < 3 {
+                Termination::TooFewStones
+            } else {
+                Termination::NoMovesLeft
+            };
+        }
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, RecordError> {
+        let mut record = GameRecord {
+            white: String::new(),
+            black: String::new(),
+            date: String::from("????.??.??"),
+            variant: VARIANT.to_string(),
+            result: GameResult::Ongoing,
+            termination: Termination::Unterminated,
+            start: None,
+            moves: Vec::new(),
+            evaluations: BTreeMap::new(),
+            time_control: None,
+            clocks: BTreeMap::new(),
+        };
+
+        let mut lines = text.lines().enumerate().peekable();
+        while let Some((index, line)) = lines.next_if(|(_, line)| line.trim().is_empty() || line.starts_with('[')) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| RecordError::Header {
+                line: index + 1,
+                message,
+            };
+
+            let (tag, value) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+                .and_then(|line| line.split_once(' '))
+                .and_then(|(tag, value)| Some((tag, value.strip_prefix('"')?.strip_suffix('"')?)))
+                .ok_or_else(|| error("Expected a tag like [White \"Name\"]".to_string()))?;
+
+            match tag {
+                "White" => record.white = value.to_string(),
+                "Black" => record.black = value.to_string(),
+                "Date" => record.date = value.to_string(),
+                "Variant" if value == VARIANT => record.variant = value.to_string(),
+                "Variant" => return Err(error(format!("The variant '{value}' isn't supported"))),
+                "Result" => record.result = value.parse().map_err(error)?,
+                "Termination" => record.termination = value.parse().map_err(error)?,
+                "TimeControl" => record.time_control = Some(value.parse().map_err(error)?),
+                "Position" => {
+                    let start: ExtendedPosition = value.parse().map_err(|message| error(format!("{message}")))?;
+                    if start.pending_capture {
+                        return Err(error("The start position can't have a pending capture".to_string()));
+                    }
+                    record.start = Some(start);
+                }
+                // Unknown tags are kept by other programs, so they are skipped here
+                _ => {}
+            }
+        }
+
+        let move_text: Vec<&str> = lines.map(|(_, line)| line).collect();
+        let mut move_text = move_text.join("\n");
+        while let Some(open) = move_text.find('{') {
+            let close = move_text[open..]
+                .find('}')
+                .map(|close| open + close)
+                .ok_or(RecordError::Notation {
+                    ply: record.moves.len() + 1,
+                    message: "Unclosed comment, expected '}'".to_string(),
+                })?;
+            // Comments belong to the move before them
+            record.parse_moves(&move_text[..open])?;
+            let comment = move_text[open + 1..close].trim();
+            if let Some(evaluation) = comment.strip_prefix("eval ") {
+                let evaluation = evaluation.trim().parse().map_err(|_| RecordError::Notation {
+                    ply: record.moves.len(),
+                    message: format!("Malformed evaluation '{}'", evaluation.trim()),
+                })?;
+                record.evaluations.insert(record.moves.len(), evaluation);
+            } else if let Some(remaining) = comment.strip_prefix("clock ") {
+                let remaining = clock::parse_seconds(remaining).ok_or_else(|| RecordError::Notation {
+                    ply: record.moves.len(),
+                    message: format!("Malformed clock '{}'", remaining.trim()),
+                })?;
+                record.clocks.insert(record.moves.len(), remaining);
+            }
+            move_text.replace_range(..=close, "");
+        }
+        record.parse_moves(&move_text)?;
+
+        Ok(record)
+    }
+
+    /// Appends the moves of text without comments, skipping move numbers & results
+    fn parse_moves(&mut self, text: &str) -> Result<(), RecordError> {
+        for token in text.split_whitespace() {
+            // `3.` before a move of white, `3...` if the game starts with a move of black
+            let number = token.trim_end_matches('.');
+            let is_move_number =
+                number.len() < token.len() && !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+            if is_move_number || token.parse::<GameResult>().is_ok() {
+                continue;
+            }
+
+            let played = token
+                .parse()
+                .map_err(|error: super::notation::NotationError| RecordError::Notation {
+                    ply: self.moves.len() + 1,
+                    message: format!("'{token}': {error}"),
+                })?;
+            self.moves.push(played);
+        }
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (tag, value) in [
+            ("White", self.white.clone()),
+            ("Black", self.black.clone()),
+            ("Date", self.date.clone()),
+            ("Variant", self.variant.clone()),
+            ("Result", self.result.to_string()),
+            ("Termination", self.termination.to_string()),
+        ]
+        .into_iter()
+        .chain(self.start.map(|start| ("Position", start.to_string())))
+        .chain(self.time_control.map(|control| ("TimeControl", control.to_string())))
+        {
+            text.push_str(&format!("[{tag} \"{value}\"]\n"));
+        }
+        text.push('\n');
+
+        // Ten moves of both players per line
+        let (mut number, mut color) = self.start.map_or((1, PlayerColor::White), |start| {
+            (start.move_number, start.position.side_to_move)
+        });
+        let mut numbers_written = 0;
+        for (ply, played) in self.moves.iter().enumerate() {
+            if ply == 0 || color == PlayerColor::White {
+                if ply != 0 {
+                    text.push(if numbers_written % 10 == 0 { '\n' } else { ' ' });
+                }
+                let dots = if color == PlayerColor::White { "." } else { "..." };
+                text.push_str(&format!("{number}{dots}"));
+                numbers_written += 1;
+            }
+            text.push_str(&format!(" {played}"));
+            if let Some(evaluation) = self.evaluations.get(&(ply + 1)) {
+                text.push_str(&format!(" {{eval {evaluation}}}"));
+            }
+            if let Some(remaining) = self.clocks.get(&(ply + 1)) {
+                text.push_str(&format!(" {{clock {}}}", clock::format_seconds(*remaining)));
+            }
+
+            if color == PlayerColor::Black {
+                number += 1;
+            }
+            color = !color;
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Loads & validates a record
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let record = GameRecord::parse(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        record
+            .validate()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        Ok(record)
+    }
+
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+}
+
+/// The current date (UTC) as `YYYY.MM.DD`
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86_400) as i64;
+
+    // Converts days since 1970-01-01 into the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT_GAME: &str = "\
+[White \"Alice\"]
+[Black \"Bob\"]
+[Date \"2026.10.19\"]
+[Variant \"Nine Men's Morris\"]
+[Result \"*\"]
+[Termination \"unterminated\"]
+
+1. d7 d6 2. g7 f6 3. a7xd6 D6
+";
+
+    #[test]
+    fn parse_replay_and_write() {
+        let record = GameRecord::parse(SHORT_GAME).unwrap();
+        assert_eq!(record.white, "Alice");
+        assert_eq!(record.name_of(PlayerColor::Black), "Bob");
+        assert_eq!(record.moves.len(), 6);
+
+        let position = record.validate().unwrap();
+        assert_eq!(position.stones_in_hand, (6, 6));
+        assert_eq!(position.stones_on_board_of(PlayerColor::Black), 2);
+
+        assert_eq!(record.to_text(), SHORT_GAME.replace("D6", "d6"));
+        assert_eq!(GameRecord::parse(&record.to_text()).unwrap(), record);
+    }
+
+    #[test]
+    fn games_from_a_position() {
+        let text = SHORT_GAME.replace(
+            "[Termination \"unterminated\"]\n\n1. d7 d6 2. g7 f6 3. a7xd6 D6",
+            "[Termination \"unterminated\"]\n[Position \"WWEEEEEEBBEEEEEEEEEEEEEE b 7 7 - 0 2\"]\n\n2... d5 3. g4 e5",
+        );
+        let record = GameRecord::parse(&text).unwrap();
+        assert_eq!(record.start.unwrap().move_number, 2);
+
+        let position = record.validate().unwrap();
+        assert_eq!(position.stones_in_hand, (6, 5));
+        assert_eq!(position.side_to_move, PlayerColor::White);
+        assert_eq!(record.to_text(), text);
+
+        let error = GameRecord::parse(&text.replace("b 7 7 -", "b 7 7 x")).unwrap_err();
+        assert!(matches!(error, RecordError::Header { line: 7, .. }));
+    }
+
+    #[test]
+    fn evaluations_are_kept() {
+        let text = SHORT_GAME
+            .replace("g7 f6", "g7 {eval 40} f6 {a comment}")
+            .replace("D6", "d6 {eval 1030}");
+        let record = GameRecord::parse(&text).unwrap();
+        assert_eq!(record.moves.len(), 6);
+        assert_eq!(record.evaluations, BTreeMap::from([(3, 40), (6, 1030)]));
+        assert_eq!(GameRecord::parse(&record.to_text()).unwrap(), record);
+
+        let error = GameRecord::parse(&SHORT_GAME.replace("g7", "g7 {eval high}")).unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::Notation {
+                ply: 3,
+                message: "Malformed evaluation 'high'".to_string()
+            }
+        );
+        assert!(GameRecord::parse(&SHORT_GAME.replace("g7", "g7 {eval 3")).is_err());
+    }
+
+    #[test]
+    fn clocks_are_kept() {
+        let text = SHORT_GAME
+            .replace(
+                "[Termination \"unterminated\"]",
+                "[Termination \"unterminated\"]\n[TimeControl \"60+1\"]",
+            )
+            .replace("d7 d6", "d7 {clock 58.75} d6 {eval 5} {clock 60}")
+            .replace("g7 f6", "g7 {clock 55.5} f6");
+        let record = GameRecord::parse(&text).unwrap();
+        assert_eq!(record.time_control, Some("60+1".parse().unwrap()));
+        assert_eq!(record.clocks.get(&2), Some(&Duration::from_secs(60)));
+        assert_eq!(record.to_text(), text.replace("D6", "d6"));
+
+        let clock = record.clock().unwrap();
+        assert_eq!(
+            clock.remaining,
+            (Duration::from_millis(55_500), Duration::from_secs(60))
+        );
+        assert_eq!(record.color_of_ply(4), PlayerColor::Black);
+        assert_eq!(GameRecord::parse(SHORT_GAME).unwrap().clock(), None);
+
+        let error = GameRecord::parse(&SHORT_GAME.replace("g7", "g7 {clock 1:30}")).unwrap_err();
+        assert!(matches!(error, RecordError::Notation { ply: 3, .. }));
+    }
+
+    #[test]
+    fn corrupt_records_are_detected() {
+        // d6 is taken by black already
+        let error = GameRecord::parse(&SHORT_GAME.replace("2. g7", "2. d6"))
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::IllegalMove {
+                ply: 3,
+                played: "d6".parse().unwrap()
+            }
+        );
+
+        let error = GameRecord::parse(&SHORT_GAME.replace("a7xd6", "a7xd9")).unwrap_err();
+        assert!(matches!(error, RecordError::Notation { ply: 5, .. }));
+
+        let error = GameRecord::parse(&SHORT_GAME.replace("Result \"*\"", "Result \"2-0\"")).unwrap_err();
+        assert!(matches!(error, RecordError::Header { line: 5, .. }));
+
+        // More than 9 white stones can't be placed on a play field
+        let error = GameRecord::parse(&SHORT_GAME.replace(
+            "[Result",
+            "[Position \"WWWWWWWWWWEEEEEEBBEEEEEE w 0 0 - 0 20\"]\n[Result",
+        ))
+        .unwrap_err();
+        assert!(matches!(error, RecordError::Header { line: 5, .. }));
+
+        let error = GameRecord::parse(
+            &SHORT_GAME
+                .replace("Result \"*\"", "Result \"1-0\"")
+                .replace("unterminated", "too few stones"),
+        )
+        .unwrap()
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::ResultMismatch {
+                stated: GameResult::WhiteWins,
+                replayed: GameResult::Ongoing
+            }
+        );
+    }
+
+    #[test]
+    fn date_is_formatted() {
+        let date = today();
+        assert_eq!(date.len(), 10);
+        assert!(date.starts_with("20"));
+        assert_eq!(&date[4..5], ".");
+    }
+}
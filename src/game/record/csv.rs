@@ -0,0 +1,123 @@
+//! Exports games as CSV for spreadsheets, one row per ply. The columns are:
+//!
+//! `game,ply,side_to_move,move,board,white_stones,black_stones,white_captures,black_captures,white_moves,
+//! white_mill_moves,white_takeable,black_moves,black_mill_moves,black_takeable,evaluation`
+//!
+//! `side_to_move` is the color playing the move of the row, `w` or `b`. Everything else describes the position after
+//! the move: the board code, the stones on the board, the stones taken by each color since the start, the move triples
+//! of [crate::game::efficient_state::EfficientPlayField::get_move_triple] for both colors & the recorded evaluation,
+//! which is empty if there is none.
+
+use std::io::{self, Write};
+
+use super::GameRecord;
+use crate::game::PlayerColor;
+
+pub const HEADER: &str = "game,ply,side_to_move,move,board,white_stones,black_stones,white_captures,black_captures,\
+white_moves,white_mill_moves,white_takeable,black_moves,black_mill_moves,black_takeable,evaluation";
+
+/// Writes the header & the rows of all games, which are identified by the given ids. The games are replayed, so an
+/// illegal move fails with [io::ErrorKind::InvalidData].
+pub fn write_games<'a>(
+    games: impl IntoIterator<Item = (&'a str, &'a GameRecord)>,
+    mut output: impl Write,
+) -> io::Result<()> {
+    writeln!(output, "{HEADER}")?;
+    for (id, record) in games {
+        write_rows(id, record, &mut output)?;
+    }
+    output.flush()
+}
+
+/// Writes the rows of a single game without header
+pub fn write_rows(id: &str, record: &GameRecord, mut output: impl Write) -> io::Result<()> {
+    let positions = record
+        .replay()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("Game {id}: {error}")))?;
+    let mut captures = (0, 0);
+
+    for (index, (played, position)) in record.moves.iter().zip(&positions[1..]).enumerate() {
+        let color = positions[index].side_to_move;
+        if played.take.is_some() {
+            match color {
+                PlayerColor::White => captures.0 += 1,
+                PlayerColor::Black => captures.1 += 1,
+            }
+        }
+
+        let mut board = position.board;
+        let (white_moves, white_mill_moves, white_takeable) = board.get_move_triple(PlayerColor::White);
+        let (black_moves, black_mill_moves, black_takeable) = board.get_move_triple(PlayerColor::Black);
+
+        writeln!(
+            output,
+            "{},{},{},{played},{},{},{},{},{},{white_moves},{white_mill_moves},{white_takeable},{black_moves},\
+             {black_mill_moves},{black_takeable},{}",
+            escape(id),
+            index + 1,
+            match color {
+                PlayerColor::White => 'w',
+                PlayerColor::Black => 'b',
+            },
+            position.board.to_string_representation(),
+            position.stones_on_board_of(PlayerColor::White),
+            position.stones_on_board_of(PlayerColor::Black),
+            captures.0,
+            captures.1,
+            record
+                .evaluations
+                .get(&(index + 1))
+                .map_or(String::new(), |evaluation| evaluation.to_string())
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes values containing commas, quotes or line breaks
+fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_row_per_ply() {
+        let mut record = GameRecord::new("Alice", "Bob");
+        record.moves = ["d7", "d6", "g7", "f6", "a7xd6"]
+            .iter()
+            .map(|played| played.parse().unwrap())
+            .collect();
+        record.evaluations.insert(5, 1040);
+
+        let mut output = Vec::new();
+        write_games([("game, 1", &record)], &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = output.lines().collect();
+
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[0], HEADER);
+        assert_eq!(rows[0].split(',').count(), 16);
+        assert!(rows[1].starts_with("\"game, 1\",1,w,d7,WEEEEEEEEEEEEEEEEEEEEEEE,1,0,0,0,"));
+        assert!(rows[1].ends_with(','));
+        assert!(rows[2].starts_with("\"game, 1\",2,b,d6,"));
+        // White closed the mill A7 D7 G7 & took the stone on D6
+        assert!(rows[5].starts_with("\"game, 1\",5,w,a7xd6,WWEEEEEWEBEEEEEEEEEEEEEE,3,1,1,0,"));
+        assert!(rows[5].ends_with(",1040"));
+    }
+
+    #[test]
+    fn illegal_moves_fail() {
+        let mut record = GameRecord::new("Alice", "Bob");
+        record.moves = vec!["d7".parse().unwrap(), "d7".parse().unwrap()];
+
+        let error = write_rows("broken", &record, io::sink()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("Game broken: Move 2"));
+    }
+}
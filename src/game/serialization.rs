@@ -0,0 +1,129 @@
+//! The `serde` support of the types, which have a text notation of their own. They are (de-)serialized as strings in
+//! it, so JSON stays readable: the 24 character code for [EfficientPlayField], `a1-d1xg7` for [Move], `1-0` for
+//! [GameResult] etc. All other types derive their implementations.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    clock::TimeControl,
+    efficient_state::EfficientPlayField,
+    notation::Move,
+    position::ExtendedPosition,
+    record::{GameResult, Termination},
+};
+
+/// Implements both traits by the conversions from & to strings
+macro_rules! impl_as_string {
+    ($type:ty, $to_string:expr, $from_str:expr) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&$to_string(self))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                $from_str(text.as_str()).map_err(D::Error::custom)
+            }
+        }
+    };
+}
+
+impl_as_string!(
+    EfficientPlayField,
+    EfficientPlayField::to_string_representation,
+    EfficientPlayField::try_from_coded
+);
+impl_as_string!(Move, Move::to_string, str::parse::<Move>);
+impl_as_string!(GameResult, GameResult::to_string, str::parse::<GameResult>);
+impl_as_string!(Termination, Termination::to_string, str::parse::<Termination>);
+impl_as_string!(TimeControl, TimeControl::to_string, str::parse::<TimeControl>);
+impl_as_string!(
+    ExtendedPosition,
+    ExtendedPosition::to_string,
+    str::parse::<ExtendedPosition>
+);
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::game::{
+        efficient_state::moves::EfficientMove, notation::MoveKind, position::Position, record::GameRecord,
+        state::representation::types::FieldState, state::PlayField, PlayerColor,
+    };
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: T, json: &str) {
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+    }
+
+    #[test]
+    fn plain_types() {
+        round_trip(PlayerColor::Black, "\"Black\"");
+        round_trip(FieldState::Free, "\"Free\"");
+        round_trip(MoveKind::Jump, "\"Jump\"");
+        round_trip(
+            EfficientMove {
+                start: None,
+                target: 3,
+                take: Some(8),
+            },
+            r#"{"start":null,"target":3,"take":8}"#,
+        );
+    }
+
+    #[test]
+    fn types_with_notation() {
+        let board = EfficientPlayField::from_coded("WWEEEEEEBBEEEEEEEEEEEEEE");
+        round_trip(board, "\"WWEEEEEEBBEEEEEEEEEEEEEE\"");
+        round_trip("a1-d1xg7".parse::<Move>().unwrap(), "\"a1-d1xg7\"");
+        round_trip(GameResult::Draw, "\"1/2-1/2\"");
+        round_trip(Termination::NoMovesLeft, "\"no moves left\"");
+        round_trip("300+5/20".parse::<TimeControl>().unwrap(), "\"300+5/20\"");
+        round_trip(
+            "WWEEEEEEBBEEEEEEEEEEEEEE b 7 6".parse::<ExtendedPosition>().unwrap(),
+            "\"WWEEEEEEBBEEEEEEEEEEEEEE b 7 6 - 0 1\"",
+        );
+        round_trip(
+            Position {
+                board,
+                side_to_move: PlayerColor::White,
+                stones_in_hand: (7, 7),
+            },
+            r#"{"board":"WWEEEEEEBBEEEEEEEEEEEEEE","side_to_move":"White","stones_in_hand":[7,7]}"#,
+        );
+
+        let error = serde_json::from_str::<EfficientPlayField>("\"WWEEEEEEBBEEEEEEEEEEEEEX\"").unwrap_err();
+        assert!(error.to_string().contains('X'));
+        assert!(serde_json::from_str::<Move>("\"a1-b1\"").is_err());
+    }
+
+    #[test]
+    fn play_field() {
+        let mut play_field = PlayField::default();
+        play_field.try_set(('A', 1), PlayerColor::White).unwrap();
+        play_field.try_set(('G', 7), PlayerColor::Black).unwrap();
+
+        let json = serde_json::to_string(&play_field).unwrap();
+        assert!(json.ends_with(r#""amount_of_stones":[1,1]}"#));
+
+        let parsed: PlayField = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        assert_eq!(parsed.amount_of_stones, (1, 1));
+    }
+
+    #[test]
+    fn game_record() {
+        let mut record = GameRecord::new("Alice", "Bob");
+        record.moves = ["d7", "d6", "g7"].iter().map(|text| text.parse().unwrap()).collect();
+        record.start = Some(ExtendedPosition::default());
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""result":"*","termination":"unterminated""#));
+        assert!(json.contains(r#""moves":["d7","d6","g7"]"#));
+        assert_eq!(serde_json::from_str::<GameRecord>(&json).unwrap(), record);
+    }
+}
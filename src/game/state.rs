@@ -33,8 +33,13 @@ pub enum PlayFieldError {
 
 use self::representation::constants::*;
 
-use super::PlayerColor;
+use super::{
+    efficient_state::{moves::CODED_FIELDS, EfficientPlayField},
+    position::STONES_PER_PLAYER,
+    PlayerColor,
+};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayField {
     state: [FieldState; FIELD_COUNT],
     // first one: white, second one: black
@@ -50,6 +55,33 @@ impl Default for PlayField {
     }
 }
 
+impl TryFrom<EfficientPlayField> for PlayField {
+    type Error = PlayFieldError;
+
+    /// Places the stones of the coded representation, which can't hold more than 9 stones of a color
+    fn try_from(board: EfficientPlayField) -> Result<Self, Self::Error> {
+        let (white_stones, black_stones) = board.get_masks();
+        if white_stones.count_ones().max(black_stones.count_ones()) > STONES_PER_PLAYER as u32 {
+            return Err(PlayFieldError::InvalidProgramStateError {
+                message: "A color can't have more than 9 stones on the play field.",
+            });
+        }
+
+        let mut play_field = PlayField::default();
+        for (coded_index, field) in CODED_FIELDS.iter().enumerate() {
+            let color = if white_stones & (1 << coded_index) != 0 {
+                PlayerColor::White
+            } else if black_stones & (1 << coded_index) != 0 {
+                PlayerColor::Black
+            } else {
+                continue;
+            };
+            play_field.try_set(*field, color)?;
+        }
+        Ok(play_field)
+    }
+}
+
 impl PlayField {
     /// Sets a sone to the specified position by calling the [get_status_of] method.
     /// Then modifies the interior [state] array
@@ -95,11 +127,8 @@ impl PlayField {
             PlayerColor::Black => self.amount_of_stones.1,
         };
 
-        if let Err(err) = self.map_to_state_index(start_pos) {
-            return Err(err);
-        } else if let Err(err) = self.map_to_state_index(target_pos) {
-            return Err(err);
-        }
+        self.map_to_state_index(start_pos)?;
+        self.map_to_state_index(target_pos)?;
 
         // Jumps, with more than 3 stones
         if 4 <= players_stone_count && start_pos.0 != target_pos.0 && start_pos.1 != target_pos.1 {
@@ -188,8 +217,8 @@ impl PlayField {
         };
 
         if field_state != player_color.into() && field_state != FieldState::Free {
-            // If the field to take is in a mill
-            if !self.get_mill_crossing(field_to_take).is_empty() {
+            // If the field to take is in a mill, unless all stones of the opponent are
+            if !self.get_mill_crossing(field_to_take).is_empty() && self.has_stones_outside_of_mills(field_state) {
                 return Err(PlayFieldError::FailedToTake {
                     field: field_to_take,
                     message: "The specified stone to take is in at lease one mill.",
@@ -204,6 +233,13 @@ impl PlayField {
             })
         }
     }
+
+    /// True if at least one stone of the color isn't part of a mill
+    fn has_stones_outside_of_mills(&self, color: FieldState) -> bool {
+        CODED_FIELDS.iter().any(|field| {
+            self.get_status_of(*field).is_ok_and(|state| state == color) && self.get_mill_crossing(*field).is_empty()
+        })
+    }
 }
 
 impl PlayField {
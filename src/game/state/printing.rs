@@ -1,6 +1,7 @@
 //! Contains everything related to the "low abstraction" of the [PlayField] printing/ painting.
 use core::fmt;
 use std::fmt::Display;
+use std::io::{self, Write};
 use std::iter::{Enumerate, Rev};
 use std::slice::Iter;
 
@@ -62,6 +63,11 @@ type EitherIter<'a> = Either<Rev<Iter<'a, FieldState>>, Enumerate<Rev<Iter<'a, F
 impl PlayField {
     /// Printing from top left to bottom right, representation in memory is left bottom to to right
     pub fn print_highlighted(&self, fields_to_highlight: Option<&[Field]>) {
+        self.write_highlighted(&mut io::stdout(), fields_to_highlight).unwrap();
+    }
+
+    /// Like [PlayField::print_highlighted], but into any output
+    pub fn write_highlighted(&self, output: &mut impl Write, fields_to_highlight: Option<&[Field]>) -> io::Result<()> {
         let mut iter = if fields_to_highlight.is_none() {
             EitherIter::Left(self.state.iter().rev())
         } else {
@@ -83,23 +89,23 @@ impl PlayField {
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  {}------------{}------------{}", row_counter, c, b, a);
+        writeln!(output, "\t{}|  {}------------{}------------{}", row_counter, c, b, a)?;
         row_counter -= 1;
-        println!("\t |  |            |            |");
+        writeln!(output, "\t |  |            |            |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  |   {}--------{}--------{}   |", row_counter, c, b, a);
+        writeln!(output, "\t{}|  |   {}--------{}--------{}   |", row_counter, c, b, a)?;
         row_counter -= 1;
-        println!("\t |  |   |        |        |   |");
+        writeln!(output, "\t |  |   |        |        |   |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  |   |   {}----{}----{}   |   |", row_counter, c, b, a);
+        writeln!(output, "\t{}|  |   |   {}----{}----{}   |   |", row_counter, c, b, a)?;
         row_counter -= 1;
-        println!("\t |  |   |   |         |   |   |");
+        writeln!(output, "\t |  |   |   |         |   |   |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
@@ -107,33 +113,34 @@ impl PlayField {
         let d = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let e = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let f = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!(
+        writeln!(
+            output,
             "\t{}|  {}---{}---{}         {}---{}---{}",
             row_counter, f, e, d, c, b, a
-        );
+        )?;
         row_counter -= 1;
-        println!("\t |  |   |   |         |   |   |");
+        writeln!(output, "\t |  |   |   |         |   |   |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  |   |   {}----{}----{}   |   |", row_counter, c, b, a);
+        writeln!(output, "\t{}|  |   |   {}----{}----{}   |   |", row_counter, c, b, a)?;
         row_counter -= 1;
-        println!("\t |  |   |        |        |   |");
+        writeln!(output, "\t |  |   |        |        |   |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  |   {}--------{}--------{}   |", row_counter, c, b, a);
+        writeln!(output, "\t{}|  |   {}--------{}--------{}   |", row_counter, c, b, a)?;
         row_counter -= 1;
-        println!("\t |  |            |            |");
+        writeln!(output, "\t |  |            |            |")?;
 
         let a = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let b = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
         let c = self.unwrap_and_highligth(&mut iter, &indices_to_highlight);
-        println!("\t{}|  {}------------{}------------{}", row_counter, c, b, a);
-        println!("\t   ____________________________");
-        println!("\t    A   B   C    D    E   F   G");
+        writeln!(output, "\t{}|  {}------------{}------------{}", row_counter, c, b, a)?;
+        writeln!(output, "\t   ____________________________")?;
+        writeln!(output, "\t    A   B   C    D    E   F   G")
     }
 
     // TODO This method is probably really inefficient...
@@ -26,6 +26,7 @@ pub mod constants {
 pub mod types {
 
     #[derive(Copy, Clone, PartialEq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum FieldState {
         Free = 0b11,
         White = 0b10,
@@ -117,7 +118,7 @@ impl PlayField {
                 1 | 5 => ('B', 'D', 'F'),
                 2 | 4 => ('C', 'D', 'E'),
                 3 if column < 4 => ('A', 'B', 'C'),
-                3 if 4 <= row => ('E', 'F', 'G'),
+                3 if 4 <= column => ('E', 'F', 'G'),
                 _ => panic!(),
             };
             let field_1 = (hor_coords_for_column.0, last_updated_field.1);
@@ -0,0 +1,251 @@
+//! Renders the play field as SVG image for documentation & bug reports. The stones are drawn on the usual 7 x 7 grid
+//! with the columns A - G from left to right & the rows 1 - 7 from bottom to top, like the terminal output of
+//! [PlayField::print_highlighted].
+
+use std::fmt::Write;
+
+use super::{
+    efficient_state::{
+        moves::{CODED_FIELDS, MILLS},
+        EfficientPlayField,
+    },
+    notation::Move,
+    state::{representation::types::FieldState, PlayField},
+    Field, PlayerColor,
+};
+
+/// Distance of neighboring grid points in pixels
+const CELL: u32 = 60;
+/// Space around the outer ring for the labels
+const MARGIN: u32 = 60;
+const SIZE: u32 = 2 * MARGIN + 6 * CELL;
+const STONE_RADIUS: u32 = 20;
+
+/// The emphasis color of the terminal, see [crate::game::painting::EMP]
+const LAST_MOVE_COLOR: &str = "#c13100";
+const MILL_COLOR: &str = "#e0a000";
+const SUGGESTION_COLOR: &str = "#1f5fbf";
+
+/// Optional markings drawn on top of the play field
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Highlights {
+    /// Start & target are outlined, a taken stone is crossed out
+    pub last_move: Option<Move>,
+    /// Drawn as line from the first to the last field
+    pub mills: Vec<[Field; 3]>,
+    /// Drawn as dashed arrows, placements as dashed circle on their target
+    pub suggested_moves: Vec<Move>,
+}
+
+impl Highlights {
+    /// The mills closed on the board by either color
+    pub fn closed_mills(board: &EfficientPlayField) -> Vec<[Field; 3]> {
+        let (white_stones, black_stones) = board.get_masks();
+
+        MILLS
+            .iter()
+            .filter(|&&mill| white_stones & mill == mill || black_stones & mill == mill)
+            .map(|&mill| {
+                let mut fields = (0..CODED_FIELDS.len()).filter(|index| mill & (1 << index) != 0);
+                let mut mill = [(); 3].map(|_| CODED_FIELDS[fields.next().unwrap()]);
+                // Along the line, so the ends are first & last
+                mill.sort();
+                mill
+            })
+            .collect()
+    }
+}
+
+impl EfficientPlayField {
+    pub fn to_svg(&self, highlights: &Highlights) -> String {
+        let (white_stones, black_stones) = self.get_masks();
+        let stones = CODED_FIELDS.iter().enumerate().filter_map(|(index, field)| {
+            if white_stones & (1 << index) != 0 {
+                Some((*field, PlayerColor::White))
+            } else if black_stones & (1 << index) != 0 {
+                Some((*field, PlayerColor::Black))
+            } else {
+                None
+            }
+        });
+        render(stones, highlights)
+    }
+}
+
+impl PlayField {
+    pub fn to_svg(&self, highlights: &Highlights) -> String {
+        let stones = CODED_FIELDS
+            .iter()
+            .filter_map(|field| match self.get_status_of(*field).unwrap() {
+                FieldState::Free => None,
+                FieldState::White => Some((*field, PlayerColor::White)),
+                FieldState::Black => Some((*field, PlayerColor::Black)),
+            });
+        render(stones, highlights)
+    }
+}
+
+/// The pixel coordinates of the center of a field
+fn point_of((column, row): Field) -> (u32, u32) {
+    let x = MARGIN + (column as u32 - 'A' as u32) * CELL;
+    let y = MARGIN + (7 - row as u32) * CELL;
+    (x, y)
+}
+
+fn render(stones: impl Iterator<Item = (Field, PlayerColor)>, highlights: &Highlights) -> String {
+    let mut svg = String::new();
+    // Writing into a String can't fail
+    let mut line = |text: String| writeln!(svg, "  {text}").unwrap();
+
+    line(format!(r##"<rect width="{SIZE}" height="{SIZE}" fill="#f5deb3"/>"##));
+
+    // The three rings & the four lines connecting them
+    line(r##"<g stroke="#000" stroke-width="3" fill="none">"##.to_string());
+    for ring in 0..3 {
+        let (x, y) = (MARGIN + ring * CELL, MARGIN + ring * CELL);
+        let side = (6 - 2 * ring) * CELL;
+        line(format!(r#"  <rect x="{x}" y="{y}" width="{side}" height="{side}"/>"#));
+    }
+    for (start, end) in [
+        (('D', 7), ('D', 5)),
+        (('D', 3), ('D', 1)),
+        (('A', 4), ('C', 4)),
+        (('E', 4), ('G', 4)),
+    ] {
+        let ((x1, y1), (x2, y2)) = (point_of(start), point_of(end));
+        line(format!(r#"  <line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}"/>"#));
+    }
+    line("</g>".to_string());
+
+    // Columns below & rows left of the outer ring
+    line(r##"<g font-family="sans-serif" font-size="20" text-anchor="middle" fill="#000">"##.to_string());
+    for (index, column) in ('A'..='G').enumerate() {
+        let x = MARGIN + index as u32 * CELL;
+        let y = SIZE - MARGIN / 3;
+        line(format!(r#"  <text x="{x}" y="{y}">{column}</text>"#));
+    }
+    for row in 1..=7u8 {
+        let (_, y) = point_of(('A', row));
+        let x = MARGIN / 3;
+        line(format!(r#"  <text x="{x}" y="{}">{row}</text>"#, y + 7));
+    }
+    line("</g>".to_string());
+
+    for mill in &highlights.mills {
+        let ((x1, y1), (x2, y2)) = (point_of(mill[0]), point_of(mill[2]));
+        line(format!(
+            r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{MILL_COLOR}" stroke-width="14" stroke-linecap="round" opacity="0.6"/>"#
+        ));
+    }
+
+    // Every field gets a small dot, so free fields stay visible
+    for field in CODED_FIELDS {
+        let (x, y) = point_of(field);
+        line(format!(r##"<circle cx="{x}" cy="{y}" r="5" fill="#000"/>"##));
+    }
+    for (field, color) in stones {
+        let (x, y) = point_of(field);
+        let fill = match color {
+            PlayerColor::White => "#fff",
+            PlayerColor::Black => "#222",
+        };
+        line(format!(
+            r##"<circle cx="{x}" cy="{y}" r="{STONE_RADIUS}" fill="{fill}" stroke="#000" stroke-width="2"/>"##
+        ));
+    }
+
+    if let Some(last_move) = highlights.last_move {
+        for field in last_move.start.into_iter().chain([last_move.target]) {
+            let (x, y) = point_of(field);
+            line(format!(
+                r#"<circle cx="{x}" cy="{y}" r="{}" fill="none" stroke="{LAST_MOVE_COLOR}" stroke-width="4"/>"#,
+                STONE_RADIUS + 5
+            ));
+        }
+        if let Some(take) = last_move.take {
+            let (x, y) = point_of(take);
+            let d = STONE_RADIUS / 2 + 4;
+            line(format!(
+                r#"<path d="M{} {} L{} {} M{} {} L{} {}" stroke="{LAST_MOVE_COLOR}" stroke-width="5"/>"#,
+                x - d,
+                y - d,
+                x + d,
+                y + d,
+                x - d,
+                y + d,
+                x + d,
+                y - d
+            ));
+        }
+    }
+
+    if !highlights.suggested_moves.is_empty() {
+        line(format!(
+            r#"<defs><marker id="arrow" markerWidth="8" markerHeight="8" refX="6" refY="4" orient="auto"><path d="M0 0 L8 4 L0 8 z" fill="{SUGGESTION_COLOR}"/></marker></defs>"#
+        ));
+    }
+    for suggestion in &highlights.suggested_moves {
+        let (x2, y2) = point_of(suggestion.target);
+        match suggestion.start {
+            Some(start) => {
+                let (x1, y1) = point_of(start);
+                line(format!(
+                    r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{SUGGESTION_COLOR}" stroke-width="4" stroke-dasharray="10 6" marker-end="url(#arrow)"/>"#
+                ));
+            }
+            None => line(format!(
+                r#"<circle cx="{x2}" cy="{y2}" r="{STONE_RADIUS}" fill="none" stroke="{SUGGESTION_COLOR}" stroke-width="4" stroke-dasharray="8 5"/>"#
+            )),
+        }
+    }
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SIZE}\" height=\"{SIZE}\" viewBox=\"0 0 {SIZE} {SIZE}\">\n{svg}</svg>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_board_and_stones() {
+        let board = EfficientPlayField::from_coded("WWEEEEEWBEEEEEEEEEEEEEEE");
+        let svg = board.to_svg(&Highlights::default());
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect x=").count(), 3);
+        assert_eq!(svg.matches(r##"fill="#fff" stroke"##).count(), 3);
+        assert_eq!(svg.matches(r##"fill="#222" stroke"##).count(), 1);
+        // D7 is the first coded field, top middle
+        assert!(svg.contains(r##"<circle cx="240" cy="60" r="20" fill="#fff""##));
+
+        let mut play_field = PlayField::default();
+        for field in [('D', 7), ('G', 7), ('A', 7)] {
+            play_field.try_set(field, PlayerColor::White).unwrap();
+        }
+        play_field.try_set(('D', 6), PlayerColor::Black).unwrap();
+        assert_eq!(play_field.to_svg(&Highlights::default()), svg);
+    }
+
+    #[test]
+    fn draws_highlights() {
+        let board = EfficientPlayField::from_coded("WWEEEEEWBEEEEEEEEEEEEEEE");
+        let mills = Highlights::closed_mills(&board);
+        assert_eq!(mills, vec![[('A', 7), ('D', 7), ('G', 7)]]);
+
+        let highlights = Highlights {
+            last_move: Some("g1-g4xd5".parse().unwrap()),
+            mills,
+            suggested_moves: vec!["d6-f6".parse().unwrap(), "a1".parse().unwrap()],
+        };
+        let svg = board.to_svg(&highlights);
+
+        assert_eq!(svg.matches(LAST_MOVE_COLOR).count(), 3);
+        assert_eq!(svg.matches(MILL_COLOR).count(), 1);
+        // The arrow head & both suggestions
+        assert_eq!(svg.matches(SUGGESTION_COLOR).count(), 3);
+        assert!(svg.contains("marker-end"));
+    }
+}
@@ -0,0 +1,625 @@
+//! A small HTTP server with a JSON API for web front-ends, built on the standard library only. It handles one request
+//! at a time & is meant to run on localhost:
+//!
+//! - `POST /games` with an optional body `{"white": "Alice", "black": "Bob", "position": "<position string>"}` creates
+//!   a game & returns its state
+//! - `GET /games/<id>` returns the state: board code, side to move, phase, stones in hand, moves, legal moves & winner
+//! - `POST /games/<id>/moves` with `{"move": "a7xd6"}` plays a move. Illegal moves are answered with status 422 &
+//!   the reason, which is the text of the [crate::game::state::PlayFieldError] where the play field rejects them.
+//! - `GET /games/<id>/hint?depth=4` asks the engine for a move, which plays from its opening book if it has one
+//! - `GET /analyse?position=<code or position string>&depth=4` reports like `muehle analyse`
+//!
+//! Errors are returned as `{"error": "<message>"}`. Bodies larger than [MAX_BODY_LENGTH] are answered with status 413,
+//! request lines & header lines longer than [MAX_LINE_LENGTH] with 414 & 431, like more than [MAX_HEADERS] headers.
+//! Clients which don't finish their request within [READ_TIMEOUT] are disconnected.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    analysis,
+    engine::EnginePlayer,
+    game::{
+        efficient_state::moves::EfficientMove,
+        notation::Move,
+        position::{ExtendedPosition, Phase, Position},
+        record::GameRecord,
+        state::PlayField,
+        PlayerColor,
+    },
+};
+
+pub const DEFAULT_PORT: u16 = 8080;
+/// The search depth of hints & analyses without `depth` parameter
+pub const DEFAULT_DEPTH: u8 = 4;
+/// Deeper searches would block the server for too long
+pub const MAX_DEPTH: u8 = 8;
+/// The largest request body read, the moves & games of the API are far smaller
+pub const MAX_BODY_LENGTH: usize = 64 * 1024;
+/// The longest request line & header line read, with the line break
+pub const MAX_LINE_LENGTH: usize = 8 * 1024;
+/// The most header lines read
+pub const MAX_HEADERS: usize = 100;
+/// Requests are handled one at a time, so a client sending nothing or trickling its request mustn't block the others for
+/// long. The whole request has to arrive within this time.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Game {
+    record: GameRecord,
+    position: Position,
+}
+
+/// The games of the server, by their id
+#[derive(Default)]
+pub struct Server {
+    games: BTreeMap<u32, Game>,
+    next_id: u32,
+    /// Answers the hints
+    engine: EnginePlayer,
+}
+
+/// A response before it is written: the status code & the JSON body
+pub type Response = (u16, Value);
+
+impl Server {
+    pub fn new(engine: EnginePlayer) -> Self {
+        Server {
+            engine,
+            ..Server::default()
+        }
+    }
+
+    /// Answers requests until accepting a connection fails. Failed connections are skipped.
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            // A client closing the connection early shouldn't stop the server
+            _ = self.handle_connection(stream?);
+        }
+        Ok(())
+    }
+
+    fn handle_connection(&mut self, mut stream: TcpStream) -> io::Result<()> {
+        let deadline = DeadlineReader {
+            stream: &stream,
+            deadline: Instant::now() + READ_TIMEOUT,
+        };
+        let (status, body) = match read_request(deadline)? {
+            Ok((method, target, body)) => self.handle(&method, &target, &body),
+            Err(response) => response,
+        };
+        write_response(&mut stream, status, &body)
+    }
+
+    /// Answers a single request, `target` is the path with the query string
+    pub fn handle(&mut self, method: &str, target: &str, body: &str) -> Response {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = parse_query(query);
+        let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+        let result = match (method, segments.as_slice()) {
+            ("OPTIONS", _) => return (204, Value::Null),
+            ("POST", ["games"]) => self.create_game(body),
+            ("GET", ["games", id]) => self.game(id).map(|(id, game)| (200, state_of(id, game))),
+            ("POST", ["games", id, "moves"]) => self.play(id, body),
+            ("GET", ["games", id, "hint"]) => self.hint(id, &query),
+            ("GET", ["analyse"]) => analyse(&query),
+            _ => Err((404, format!("No endpoint for {method} {path}"))),
+        };
+        result.unwrap_or_else(|(status, message)| (status, json!({ "error": message })))
+    }
+
+    fn create_game(&mut self, body: &str) -> Result<Response, (u16, String)> {
+        let request = parse_body(body)?;
+        let name = |color: &str| request[color].as_str().unwrap_or_default();
+        let mut record = GameRecord::new(name("white"), name("black"));
+
+        let position = match request["position"].as_str() {
+            Some(text) => {
+                let start: ExtendedPosition = text
+                    .parse()
+                    .map_err(|error| (400, format!("Malformed position '{text}': {error}")))?;
+                if start.pending_capture {
+                    return Err((400, "The start position can't have a pending capture".to_string()));
+                }
+                start
+                    .check_stone_counts()
+                    .map_err(|message| (400, format!("Impossible position '{text}': {message}")))?;
+                record.start = Some(start);
+                start.position
+            }
+            None => Position::default(),
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let game = Game { record, position };
+        let state = state_of(id, &game);
+        self.games.insert(id, game);
+        Ok((201, state))
+    }
+
+    fn game(&mut self, id: &str) -> Result<(u32, &mut Game), (u16, String)> {
+        id.parse()
+            .ok()
+            .and_then(|id| Some((id, self.games.get_mut(&id)?)))
+            .ok_or((404, format!("There is no game {id}")))
+    }
+
+    fn play(&mut self, id: &str, body: &str) -> Result<Response, (u16, String)> {
+        let request = parse_body(body)?;
+        let (id, game) = self.game(id)?;
+
+        let text = request["move"]
+            .as_str()
+            .ok_or((400, "The move is missing".to_string()))?;
+        let played: Move = text
+            .parse()
+            .map_err(|error| (400, format!("Malformed move '{text}': {error}")))?;
+        if game.position.winner().is_some() {
+            return Err((409, "The game is over already".to_string()));
+        }
+
+        let legal_move = check_move(&game.position, played).map_err(|reason| (422, reason))?;
+        game.position.play(legal_move);
+        game.record.moves.push(played);
+        // Can't fail, the move is legal
+        _ = game.record.update_result();
+        Ok((200, state_of(id, game)))
+    }
+
+    fn hint(&mut self, id: &str, query: &BTreeMap<String, String>) -> Result<Response, (u16, String)> {
+        let depth = depth_of(query)?;
+        let position = self.game(id)?.1.position;
+
+        let result = self.engine.choose_move_at_depth(&position, depth);
+        Ok((
+            200,
+            json!({
+                "move": result.best_move.map(|best| Move::from(best).to_string()),
+                "score": result.score,
+                "depth": depth,
+            }),
+        ))
+    }
+}
+
+/// The move as efficient move, if it's legal. Otherwise the reason why not, which is the [PlayFieldError] if the play
+/// field rejects it.
+///
+/// [PlayFieldError]: crate::game::state::PlayFieldError
+fn check_move(position: &Position, played: Move) -> Result<EfficientMove, String> {
+    let legal_move = played
+        .to_efficient()
+        .filter(|efficient_move| position.legal_moves().contains(efficient_move));
+    if let Some(legal_move) = legal_move {
+        return Ok(legal_move);
+    }
+
+    match (played.start, position.is_set_phase()) {
+        (Some(_), true) => return Err("Stones can't be moved before all stones are placed.".to_string()),
+        (None, false) => return Err("All stones are placed, a stone has to be moved.".to_string()),
+        _ => {}
+    }
+
+    let color = position.side_to_move;
+    let mut play_field = PlayField::try_from(position.board).map_err(|error| error.to_string())?;
+    match played.start {
+        Some(start) => play_field.try_move(start, played.target, color),
+        None => play_field.try_set(played.target, color),
+    }
+    .map_err(|error| error.to_string())?;
+
+    let closes_mill = !play_field.get_mill_crossing(played.target).is_empty();
+    match played.take {
+        Some(take) if closes_mill => play_field.try_take(take, color).map_err(|error| error.to_string())?,
+        Some(_) => return Err("The move doesn't close a mill, so no stone can be taken.".to_string()),
+        None if closes_mill => return Err("The move closes a mill, the stone to take is missing.".to_string()),
+        None => {}
+    }
+    Err(format!("{played} isn't a legal move."))
+}
+
+fn state_of(id: u32, game: &Game) -> Value {
+    let position = &game.position;
+    let phase = match position.phase() {
+        Phase::Set => "set",
+        Phase::Move => "move",
+        Phase::Jump => "jump",
+    };
+    let legal_moves: Vec<String> = match position.winner() {
+        Some(_) => Vec::new(),
+        None => position
+            .legal_moves()
+            .into_iter()
+            .map(|legal_move| Move::from(legal_move).to_string())
+            .collect(),
+    };
+
+    json!({
+        "id": id,
+        "white": game.record.white,
+        "black": game.record.black,
+        "board": position.board.to_string_representation(),
+        "side_to_move": color_code(position.side_to_move),
+        "phase": phase,
+        "stones_in_hand": [position.stones_in_hand.0, position.stones_in_hand.1],
+        "moves": game.record.moves.iter().map(Move::to_string).collect::<Vec<_>>(),
+        "legal_moves": legal_moves,
+        "winner": position.winner().map(color_code),
+        "result": game.record.result.to_string(),
+    })
+}
+
+fn analyse(query: &BTreeMap<String, String>) -> Result<Response, (u16, String)> {
+    let depth = depth_of(query)?;
+    let text = query.get("position").ok_or((
+        400,
+        "The position is missing, like ?position=WWEEEEEEBBEEEEEEEEEEEEEE".to_string(),
+    ))?;
+
+    // A board code alone is analysed in the move phase with white to move, like `muehle analyse`
+    let text = if text.contains(' ') {
+        text.clone()
+    } else {
+        format!("{text} w 0 0")
+    };
+    let position = text
+        .parse::<ExtendedPosition>()
+        .map_err(|error| (400, format!("Malformed position '{text}': {error}")))?
+        .position;
+    position
+        .check_stone_counts()
+        .map_err(|message| (400, format!("Impossible position '{text}': {message}")))?;
+
+    // Without database the analysis can't fail
+    let analysis = analysis::analyse(&position, Some(depth), None).unwrap();
+    let legal_moves: Vec<Value> = analysis
+        .legal_moves
+        .iter()
+        .map(|analysed| {
+            json!({
+                "move": Move::from(analysed.legal_move).to_string(),
+                "closes_mill": analysed.closes_mill,
+            })
+        })
+        .collect();
+    let triple = |(moves, moves_into_mill, stones_to_take): (u32, u32, u32)| json!({ "moves": moves, "moves_into_mill": moves_into_mill, "stones_to_take": stones_to_take });
+
+    Ok((
+        200,
+        json!({
+            "board": position.board.to_string_representation(),
+            "side_to_move": color_code(position.side_to_move),
+            "stones_in_hand": [position.stones_in_hand.0, position.stones_in_hand.1],
+            "canonical_form": analysis.canonical_form.to_string_representation(),
+            "symmetry": analysis.symmetry.to_string(),
+            "move_triples": { "white": triple(analysis.move_triples[0]), "black": triple(analysis.move_triples[1]) },
+            "legal_moves": legal_moves,
+            "winner": analysis.winner.map(color_code),
+            "engine": analysis.engine.map(|result| json!({
+                "move": result.best_move.map(|best| Move::from(best).to_string()),
+                "score": result.score,
+                "depth": depth,
+            })),
+        }),
+    ))
+}
+
+fn depth_of(query: &BTreeMap<String, String>) -> Result<u8, (u16, String)> {
+    match query.get("depth") {
+        None => Ok(DEFAULT_DEPTH),
+        Some(depth) => depth
+            .parse()
+            .ok()
+            .filter(|depth| (1..=MAX_DEPTH).contains(depth))
+            .ok_or((400, format!("The depth must be between 1 and {MAX_DEPTH}"))),
+    }
+}
+
+fn color_code(color: PlayerColor) -> &'static str {
+    match color {
+        PlayerColor::White => "w",
+        PlayerColor::Black => "b",
+    }
+}
+
+/// An empty body counts as empty object
+fn parse_body(body: &str) -> Result<Value, (u16, String)> {
+    if body.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(body).map_err(|error| (400, format!("Malformed JSON: {error}")))
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+/// Decodes `%XX` escapes & `+` as space, malformed escapes are kept
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (byte, escaped) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+                continue;
+            }
+            (b'+', _) => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Reads from the stream until the deadline, later reads fail as timed out
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "The request took too long"));
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        (&mut &*self.stream).read(buf)
+    }
+}
+
+/// The method, the target & the body of a request. Requests which are malformed or too large are answered without
+/// reading the rest of them.
+fn read_request(stream: impl Read) -> io::Result<Result<(String, String, String), Response>> {
+    let error = |status: u16, message: &str| Ok(Err((status, json!({ "error": message }))));
+    let mut reader = BufReader::new(stream);
+
+    let Some(request_line) = read_line(&mut reader)? else {
+        return error(414, &format!("The request line is longer than {MAX_LINE_LENGTH} bytes"));
+    };
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return error(400, "Malformed request line");
+    };
+
+    let mut content_length = 0;
+    let mut headers = 0;
+    loop {
+        let Some(header) = read_line(&mut reader)? else {
+            return error(431, &format!("A header line is longer than {MAX_LINE_LENGTH} bytes"));
+        };
+        if header.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if MAX_HEADERS < headers {
+            return error(431, &format!("The request has more than {MAX_HEADERS} headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let Ok(length) = value.trim().parse() else {
+                    return error(400, &format!("Malformed Content-Length '{}'", value.trim()));
+                };
+                content_length = length;
+            }
+        }
+    }
+
+    if MAX_BODY_LENGTH < content_length {
+        return error(413, &format!("The body is larger than {MAX_BODY_LENGTH} bytes"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok((
+        method.to_string(),
+        target.to_string(),
+        String::from_utf8_lossy(&body).into_owned(),
+    )))
+}
+
+/// The next line of at most [MAX_LINE_LENGTH] bytes, None for longer lines. Empty at the end of the stream.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_LENGTH as u64).read_line(&mut line)?;
+    Ok((line.len() < MAX_LINE_LENGTH || line.ends_with('\n')).then_some(line))
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        _ => "",
+    };
+    let body = if body.is_null() {
+        String::new()
+    } else {
+        body.to_string()
+    };
+
+    // Allows web front-ends served from another port
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn play_a_game() {
+        let mut server = Server::default();
+        let (status, state) = server.handle("POST", "/games", r#"{"white": "Alice", "black": "Bob"}"#);
+        assert_eq!(status, 201);
+        assert_eq!(state["id"], 0);
+        assert_eq!(state["phase"], "set");
+        assert_eq!(state["legal_moves"].as_array().unwrap().len(), 24);
+
+        for played in ["d7", "d6", "g7", "f6"] {
+            let (status, _) = server.handle("POST", "/games/0/moves", &format!(r#"{{"move": "{played}"}}"#));
+            assert_eq!(status, 200);
+        }
+        let (status, hint) = server.handle("GET", "/games/0/hint?depth=2", "");
+        assert_eq!(status, 200);
+        assert_eq!(hint["move"], "a7xd6");
+
+        let (status, state) = server.handle("POST", "/games/0/moves", r#"{"move": "a7xd6"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(state["board"], "WWEEEEEWEBEEEEEEEEEEEEEE");
+        assert_eq!(state["side_to_move"], "b");
+        assert_eq!(state["stones_in_hand"], json!([6, 7]));
+        assert_eq!(server.handle("GET", "/games/0", "").1, state);
+    }
+
+    #[test]
+    fn illegal_moves_are_explained() {
+        let mut server = Server::default();
+        server.handle("POST", "/games", r#"{"position": "WWEEEEEEBBEEEEEEEEEEEEEE w 7 7"}"#);
+
+        let (status, error) = server.handle("POST", "/games/0/moves", r#"{"move": "d6"}"#);
+        assert_eq!(status, 422);
+        assert_eq!(
+            error["error"],
+            "Error caused by setting ● to field D6 which is ○ - Stone must be placed upon free field."
+        );
+        let (status, error) = server.handle("POST", "/games/0/moves", r#"{"move": "a7"}"#);
+        assert_eq!(status, 422);
+        assert_eq!(error["error"], "The move closes a mill, the stone to take is missing.");
+        let (status, _) = server.handle("POST", "/games/0/moves", r#"{"move": "d7-d5"}"#);
+        assert_eq!(status, 422);
+
+        assert_eq!(server.handle("POST", "/games/0/moves", r#"{"move": "z9"}"#).0, 400);
+        assert_eq!(server.handle("POST", "/games/0/moves", "{").0, 400);
+        let (status, error) = server.handle("POST", "/games", r#"{"position": "WWEEEEEEBBEEEEEEEEEEEEEE w 7 5"}"#);
+        assert_eq!(status, 400);
+        assert!(error["error"].as_str().unwrap().starts_with("Impossible position"));
+        assert_eq!(server.handle("GET", "/games/7", "").0, 404);
+        assert_eq!(server.handle("DELETE", "/games/0", "").0, 404);
+    }
+
+    #[test]
+    fn analyse_over_http() {
+        let mut server = Server::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(
+                stream,
+                "GET /analyse?position=WWEEEEEEBBEEEEEEEEEEEEEE+w+7%207&depth=2 HTTP/1.1\r\nHost: localhost\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        server.handle_connection(stream).unwrap();
+
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let analysis: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(analysis["stones_in_hand"], json!([7, 7]));
+        assert_eq!(analysis["engine"]["move"], "a7xd6");
+        // 19 placements & a7 taking either black stone
+        assert_eq!(analysis["legal_moves"].as_array().unwrap().len(), 21);
+
+        // Hands out of step with the side to move
+        let (status, _) = server.handle("GET", "/analyse?position=WWEEEEEEBBEEEEEEEEEEEEEE+w+7+5", "");
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn large_bodies_are_rejected() {
+        let mut server = Server::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(
+                stream,
+                "POST /games HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n{{}}"
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let (stream, _) = listener.accept().unwrap();
+        server.handle_connection(stream).unwrap();
+
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+        assert!(server.games.is_empty());
+    }
+
+    #[test]
+    fn malformed_requests_are_answered() {
+        let long_line = "a".repeat(MAX_LINE_LENGTH);
+        for (request, status) in [
+            (format!("GET /{long_line} HTTP/1.1\r\n\r\n"), 414),
+            (format!("GET / HTTP/1.1\r\nX-Long: {long_line}\r\n\r\n"), 431),
+            (
+                format!("GET / HTTP/1.1\r\n{}\r\n", "X-Header: 1\r\n".repeat(MAX_HEADERS + 1)),
+                431,
+            ),
+            ("POST /games HTTP/1.1\r\nContent-Length: two\r\n\r\n{}".to_string(), 400),
+            ("GET\r\n\r\n".to_string(), 400),
+        ] {
+            let Ok(Err((answered, _))) = read_request(request.as_bytes()) else {
+                panic!("The request wasn't answered: {}", &request[..40.min(request.len())]);
+            };
+            assert_eq!(answered, status);
+        }
+
+        let request = "POST /games HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}";
+        let (method, target, body) = read_request(request.as_bytes()).unwrap().unwrap();
+        assert_eq!(
+            (method.as_str(), target.as_str(), body.as_str()),
+            ("POST", "/games", "{}")
+        );
+    }
+
+    #[test]
+    fn query_strings() {
+        let query = parse_query("position=WWE+w%207&depth=3&broken");
+        assert_eq!(query["position"], "WWE w 7");
+        assert_eq!(query["depth"], "3");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
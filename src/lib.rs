@@ -1,5 +1,4 @@
 /// The purpose of this module is to share contents which are important for the games coordination (player handling, game phase enforcement) and the play field storing the state of the game and abstractions around it.
-
 pub mod game {
 
     use std::{fmt::Display, ops::Not};
@@ -18,12 +17,21 @@ pub mod game {
         pub static ERROR: Lazy<Style> = Lazy::new(|| Style::new(yansi::Color::Red).bold());
     }
 
+    pub mod clock;
     pub mod efficient_state;
+    pub mod events;
+    pub mod notation;
+    pub mod position;
+    pub mod record;
+    #[cfg(feature = "serde")]
+    mod serialization;
     pub mod state;
+    pub mod svg;
 
     pub type Field = (char, u8);
 
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum PlayerColor {
         White,
         Black,
@@ -38,19 +46,19 @@ pub mod game {
         }
     }
 
-    impl Into<FieldState> for PlayerColor {
-        fn into(self) -> FieldState {
-            match self {
+    impl From<PlayerColor> for FieldState {
+        fn from(color: PlayerColor) -> Self {
+            match color {
                 PlayerColor::White => FieldState::White,
                 PlayerColor::Black => FieldState::Black,
             }
         }
     }
 
-    impl Into<u16> for PlayerColor {
+    impl From<PlayerColor> for u16 {
         /// Needed for the [EfficientPlayField] representation of the enum
-        fn into(self) -> u16 {
-            match self {
+        fn from(color: PlayerColor) -> Self {
+            match color {
                 PlayerColor::White => 1u16,
                 PlayerColor::Black => 2u16,
             }
@@ -69,3 +77,11 @@ pub mod game {
         }
     }
 }
+
+pub mod analysis;
+pub mod batch;
+pub mod engine;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod network;
+pub mod solver;
@@ -1,8 +1,31 @@
-use coordination::GameCoordinator;
-
+mod cli;
+mod config;
 mod coordination;
+mod remote;
+mod replay;
 
 fn main() {
-    let mut coordinator = GameCoordinator::setup();
-    coordinator.start_game();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("resume") => cli::resume(&args[1..]),
+        Some("replay") => cli::replay(&args[1..]),
+        Some("host") => cli::host(&args[1..]),
+        Some("join") => cli::join(&args[1..]),
+        Some("serve") => cli::serve(&args[1..]),
+        Some("start") => cli::start(&args[1..]),
+        Some("solve") => cli::solve(&args[1..]),
+        Some("verify") => cli::verify(&args[1..]),
+        Some("solve-set") => cli::solve_set(&args[1..]),
+        Some("csv") => cli::export_csv(&args[1..]),
+        Some("svg") => cli::svg(&args[1..]),
+        Some("book") => cli::book(&args[1..]),
+        Some("canon") => cli::canon(&args[1..]),
+        Some("triple") => cli::triple(&args[1..]),
+        Some("engine") => cli::engine(&args[1..]),
+        Some("analyse") => cli::analyse(&args[1..]),
+        Some(flag) if flag.starts_with("--") => cli::play(&args),
+        Some(unknown) => cli::print_usage(unknown),
+        None => cli::play(&args),
+    }
 }
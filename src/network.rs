@@ -0,0 +1,487 @@
+//! Games between two computers over TCP. The host keeps the authoritative game: it checks every move of the guest
+//! with a [PlayField] before it counts & tells the guest which moves were played. Both sides mirror the [Position], so
+//! either can see when the game is over by the rules.
+//!
+//! The protocol is line based, moves are in the notation of [crate::game::notation]:
+//!
+//! ```text
+//! guest: hello <name> [w|b]   optionally the color the guest would like to play
+//! host:  welcome <name> <color of the guest, w or b>
+//! guest: move <move>          host: moved <move> or illegal <reason>
+//! host:  moved <move>         after a move of the host
+//! both:  resign
+//! both:  chat <text>          at any time, also while the other side is on turn
+//! ```
+//!
+//! The color of the host decides, the guest gets the requested color only if the host didn't choose one.
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crate::game::{
+    notation::Move,
+    position::Position,
+    record::{GameRecord, GameResult, Termination},
+    state::PlayField,
+    PlayerColor,
+};
+
+pub mod spectators;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    /// The other side closed the connection
+    Disconnected,
+    /// The other side sent something unexpected
+    Protocol(String),
+    /// A move which can't be played, either checked locally or rejected by the host
+    IllegalMove(String),
+    NotYourTurn,
+    GameOver,
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Io(error) => write!(f, "Connection error: {error}"),
+            NetworkError::Disconnected => f.write_str("The opponent left the game"),
+            NetworkError::Protocol(message) => write!(f, "Unexpected message: {message}"),
+            NetworkError::IllegalMove(reason) => f.write_str(reason),
+            NetworkError::NotYourTurn => f.write_str("It's not your turn"),
+            NetworkError::GameOver => f.write_str("The game is over already"),
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(error: io::Error) -> Self {
+        NetworkError::Io(error)
+    }
+}
+
+/// What the opponent did while waiting for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAction {
+    Moved(Move),
+    Resigned,
+    /// A chat message, the opponent is still on turn afterwards if it was before
+    Chatted(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Host,
+    Guest,
+}
+
+pub struct NetworkGame {
+    role: Role,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// The color of this side
+    color: PlayerColor,
+    position: Position,
+    record: GameRecord,
+    /// Chat messages of the opponent, which arrived while waiting for something else
+    chats: VecDeque<String>,
+}
+
+impl NetworkGame {
+    /// Waits for a guest on the listener. The host plays `color`, otherwise the guest may choose & the host plays white
+    /// if it doesn't.
+    pub fn host(listener: &TcpListener, name: &str, color: Option<PlayerColor>) -> Result<Self, NetworkError> {
+        let (stream, _) = listener.accept()?;
+        let mut game = NetworkGame::new(Role::Host, stream, PlayerColor::White, name, "")?;
+
+        let line = game.receive()?;
+        let hello = line
+            .strip_prefix("hello ")
+            .ok_or_else(|| NetworkError::Protocol(line.clone()))?
+            .trim();
+        let (guest_name, requested) = match hello.rsplit_once(' ') {
+            Some((guest_name, code)) if parse_color(code).is_some() => (guest_name.trim(), parse_color(code)),
+            _ => (hello, None),
+        };
+        game.color = color
+            .or(requested.map(|requested| !requested))
+            .unwrap_or(PlayerColor::White);
+        game.set_names(name, guest_name);
+        game.send(&format!("welcome {name} {}", color_code(!game.color)))?;
+        Ok(game)
+    }
+
+    /// Joins the game of a host, asking to play `color` if given
+    pub fn join(address: impl ToSocketAddrs, name: &str, color: Option<PlayerColor>) -> Result<Self, NetworkError> {
+        let stream = TcpStream::connect(address)?;
+        let mut game = NetworkGame::new(Role::Guest, stream, PlayerColor::White, name, "")?;
+        match color {
+            Some(color) => game.send(&format!("hello {name} {}", color_code(color)))?,
+            None => game.send(&format!("hello {name}"))?,
+        }
+
+        let line = game.receive()?;
+        let welcome = line
+            .strip_prefix("welcome ")
+            .and_then(|welcome| welcome.rsplit_once(' '))
+            .and_then(|(host_name, color)| Some((host_name, parse_color(color)?)));
+        let Some((host_name, color)) = welcome else {
+            return Err(NetworkError::Protocol(line));
+        };
+        game.color = color;
+        game.set_names(name, host_name);
+        Ok(game)
+    }
+
+    fn new(
+        role: Role,
+        stream: TcpStream,
+        color: PlayerColor,
+        name: &str,
+        opponent: &str,
+    ) -> Result<Self, NetworkError> {
+        let (white, black) = match color {
+            PlayerColor::White => (name, opponent),
+            PlayerColor::Black => (opponent, name),
+        };
+        Ok(NetworkGame {
+            role,
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            color,
+            position: Position::default(),
+            record: GameRecord::new(white, black),
+            chats: VecDeque::new(),
+        })
+    }
+
+    /// Names the players once the colors are known
+    fn set_names(&mut self, name: &str, opponent: &str) {
+        let (white, black) = match self.color {
+            PlayerColor::White => (name, opponent),
+            PlayerColor::Black => (opponent, name),
+        };
+        self.record.white = white.to_string();
+        self.record.black = black.to_string();
+    }
+
+    pub fn color(&self) -> PlayerColor {
+        self.color
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// All moves so far & the result once the game is over
+    pub fn record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.record.result != GameResult::Ongoing
+    }
+
+    /// Plays a move of this side. The host checks it itself, the guest waits for the answer of the host.
+    pub fn play(&mut self, played: Move) -> Result<(), NetworkError> {
+        if self.is_over() {
+            return Err(NetworkError::GameOver);
+        } else if self.position.side_to_move != self.color {
+            return Err(NetworkError::NotYourTurn);
+        }
+
+        match self.role {
+            Role::Host => {
+                self.check(played).map_err(NetworkError::IllegalMove)?;
+                self.apply(played);
+                self.send(&format!("moved {played}"))
+            }
+            Role::Guest => {
+                self.send(&format!("move {played}"))?;
+                let line = self.receive()?;
+                if let Some(reason) = line.strip_prefix("illegal ") {
+                    return Err(NetworkError::IllegalMove(reason.to_string()));
+                }
+                let confirmed = self.parse_move(&line, "moved ")?;
+                if confirmed != played {
+                    return Err(NetworkError::Protocol(line));
+                }
+                self.apply(confirmed);
+                Ok(())
+            }
+        }
+    }
+
+    /// Blocks until the opponent moved, resigned or sent a chat message. The host answers illegal moves of the guest &
+    /// keeps waiting. Chat messages which arrived earlier are returned first.
+    pub fn wait_for_opponent(&mut self) -> Result<RemoteAction, NetworkError> {
+        if let Some(text) = self.chats.pop_front() {
+            return Ok(RemoteAction::Chatted(text));
+        } else if self.is_over() {
+            return Err(NetworkError::GameOver);
+        }
+
+        loop {
+            let line = self.receive_line()?;
+            if let Some(text) = line.strip_prefix("chat ") {
+                return Ok(RemoteAction::Chatted(text.to_string()));
+            } else if line == "resign" {
+                self.end(GameResult::won_by(self.color), Termination::Resignation);
+                return Ok(RemoteAction::Resigned);
+            } else if self.position.side_to_move == self.color {
+                return Err(NetworkError::Protocol(line));
+            }
+
+            let played = match self.role {
+                Role::Host => {
+                    let played = match line.strip_prefix("move ").map(|played| played.trim().parse::<Move>()) {
+                        Some(Ok(played)) => played,
+                        Some(Err(error)) => {
+                            self.send(&format!("illegal {error}"))?;
+                            continue;
+                        }
+                        None => return Err(NetworkError::Protocol(line)),
+                    };
+                    if let Err(reason) = self.check(played) {
+                        self.send(&format!("illegal {reason}"))?;
+                        continue;
+                    }
+                    self.send(&format!("moved {played}"))?;
+                    played
+                }
+                Role::Guest => self.parse_move(&line, "moved ")?,
+            };
+            self.apply(played);
+            return Ok(RemoteAction::Moved(played));
+        }
+    }
+
+    /// Sends a chat message to the opponent, line breaks are sent as spaces
+    pub fn chat(&mut self, text: &str) -> Result<(), NetworkError> {
+        self.send(&format!("chat {}", text.replace(['\r', '\n'], " ")))
+    }
+
+    /// The chat messages of the opponent which arrived while playing a move, in the order they were sent
+    pub fn take_chats(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.chats.drain(..)
+    }
+
+    pub fn resign(&mut self) -> Result<(), NetworkError> {
+        if self.is_over() {
+            return Err(NetworkError::GameOver);
+        }
+        self.end(GameResult::won_by(!self.color), Termination::Resignation);
+        self.send("resign")
+    }
+
+    /// Checks a move by playing it on a [PlayField] of the mirrored position
+    fn check(&self, played: Move) -> Result<(), String> {
+        match (played.start, self.position.is_set_phase()) {
+            (Some(_), true) => return Err("Stones can't be moved before all stones are placed.".to_string()),
+            (None, false) => return Err("All stones are placed, a stone has to be moved.".to_string()),
+            _ => {}
+        }
+
+        let color = self.position.side_to_move;
+        let mut play_field = PlayField::try_from(self.position.board).map_err(|error| error.to_string())?;
+        match played.start {
+            Some(start) => play_field.try_move(start, played.target, color),
+            None => play_field.try_set(played.target, color),
+        }
+        .map_err(|error| error.to_string())?;
+
+        let closes_mill = !play_field.get_mill_crossing(played.target).is_empty();
+        match played.take {
+            Some(take) if closes_mill => play_field.try_take(take, color).map_err(|error| error.to_string()),
+            Some(_) => Err("The move doesn't close a mill, so no stone can be taken.".to_string()),
+            None if closes_mill => Err("The move closes a mill, the stone to take is missing.".to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Plays a checked move & ends the game if it's over by the rules
+    fn apply(&mut self, played: Move) {
+        self.position.play(played.to_efficient().unwrap());
+        self.record.moves.push(played);
+        // Can't fail, the moves are checked already
+        _ = self.record.update_result();
+    }
+
+    fn end(&mut self, result: GameResult, termination: Termination) {
+        self.record.result = result;
+        self.record.termination = termination;
+    }
+
+    /// A move sent by the host, which must be legal in the mirrored position
+    fn parse_move(&self, line: &str, prefix: &str) -> Result<Move, NetworkError> {
+        line.strip_prefix(prefix)
+            .and_then(|played| played.trim().parse::<Move>().ok())
+            .filter(|played| self.check(*played).is_ok())
+            .ok_or_else(|| NetworkError::Protocol(line.to_string()))
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), NetworkError> {
+        writeln!(self.writer, "{line}")?;
+        Ok(self.writer.flush()?)
+    }
+
+    /// The next line which isn't a chat message, those are kept for [NetworkGame::take_chats]
+    fn receive(&mut self) -> Result<String, NetworkError> {
+        loop {
+            let line = self.receive_line()?;
+            match line.strip_prefix("chat ") {
+                Some(text) => self.chats.push_back(text.to_string()),
+                None => return Ok(line),
+            }
+        }
+    }
+
+    fn receive_line(&mut self) -> Result<String, NetworkError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NetworkError::Disconnected);
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+fn color_code(color: PlayerColor) -> char {
+    match color {
+        PlayerColor::White => 'w',
+        PlayerColor::Black => 'b',
+    }
+}
+
+fn parse_color(code: &str) -> Option<PlayerColor> {
+    match code {
+        "w" => Some(PlayerColor::White),
+        "b" => Some(PlayerColor::Black),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn moves(text: &str) -> Vec<Move> {
+        text.split_whitespace().map(|played| played.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn host_and_guest_on_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let host = thread::spawn(move || {
+            let mut game = NetworkGame::host(&listener, "Alice", Some(PlayerColor::White)).unwrap();
+            assert_eq!(game.record().black, "Bob");
+
+            for played in moves("d7 g7") {
+                game.play(played).unwrap();
+                assert!(matches!(game.wait_for_opponent().unwrap(), RemoteAction::Moved(_)));
+            }
+            game.play("a7xd6".parse().unwrap()).unwrap();
+            assert_eq!(game.wait_for_opponent().unwrap(), RemoteAction::Resigned);
+            game.record().clone()
+        });
+
+        // The host chose its color, so the request of the guest isn't granted
+        let mut game = NetworkGame::join(address, "Bob", Some(PlayerColor::White)).unwrap();
+        assert_eq!(game.color(), PlayerColor::Black);
+        assert_eq!(game.record().white, "Alice");
+
+        assert!(matches!(
+            game.play("d6".parse().unwrap()),
+            Err(NetworkError::NotYourTurn)
+        ));
+        assert_eq!(
+            game.wait_for_opponent().unwrap(),
+            RemoteAction::Moved("d7".parse().unwrap())
+        );
+        // D7 is taken, the host rejects the move & the guest can try again
+        let error = game.play("d7".parse().unwrap()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Error caused by setting ○ to field D7 which is ● - Stone must be placed upon free field."
+        );
+        game.play("d6".parse().unwrap()).unwrap();
+        game.wait_for_opponent().unwrap();
+        game.play("f6".parse().unwrap()).unwrap();
+        assert_eq!(
+            game.wait_for_opponent().unwrap(),
+            RemoteAction::Moved("a7xd6".parse().unwrap())
+        );
+        assert_eq!(game.position().stones_on_board_of(PlayerColor::Black), 1);
+        game.resign().unwrap();
+
+        let hosts_record = host.join().unwrap();
+        assert_eq!(hosts_record.moves, moves("d7 d6 g7 f6 a7xd6"));
+        assert_eq!(hosts_record.result, GameResult::WhiteWins);
+        assert_eq!(hosts_record.termination, Termination::Resignation);
+        assert_eq!(game.record(), &hosts_record);
+    }
+
+    #[test]
+    fn chat_and_color_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let host = thread::spawn(move || {
+            // Without a color of its own the host plays what the guest left
+            let mut game = NetworkGame::host(&listener, "Alice", None).unwrap();
+            assert_eq!(game.color(), PlayerColor::Black);
+            game.chat("Have fun!").unwrap();
+
+            assert_eq!(
+                game.wait_for_opponent().unwrap(),
+                RemoteAction::Chatted("Thanks, you too".to_string())
+            );
+            assert_eq!(
+                game.wait_for_opponent().unwrap(),
+                RemoteAction::Moved("d7".parse().unwrap())
+            );
+            game.play("d6".parse().unwrap()).unwrap();
+        });
+
+        let mut game = NetworkGame::join(address, "Bob", Some(PlayerColor::White)).unwrap();
+        assert_eq!(game.color(), PlayerColor::White);
+        assert_eq!(game.record().black, "Alice");
+        game.chat("Thanks,\nyou too").unwrap();
+        // The message of the host arrives while the host checks the move
+        game.play("d7".parse().unwrap()).unwrap();
+        assert_eq!(game.take_chats().collect::<Vec<_>>(), ["Have fun!"]);
+        assert_eq!(
+            game.wait_for_opponent().unwrap(),
+            RemoteAction::Moved("d6".parse().unwrap())
+        );
+        host.join().unwrap();
+    }
+
+    #[test]
+    fn guests_see_when_the_host_leaves() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let host = thread::spawn(move || {
+            // The guest plays white & begins
+            let mut game = NetworkGame::host(&listener, "Alice", Some(PlayerColor::Black)).unwrap();
+            game.wait_for_opponent().unwrap();
+        });
+
+        let mut game = NetworkGame::join(address, "Bob", None).unwrap();
+        assert_eq!(game.color(), PlayerColor::White);
+        assert_eq!(game.record().white, "Bob");
+        game.play("d7".parse().unwrap()).unwrap();
+        host.join().unwrap();
+        assert!(matches!(game.wait_for_opponent(), Err(NetworkError::Disconnected)));
+    }
+}
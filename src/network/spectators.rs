@@ -0,0 +1,325 @@
+//! Read-only spectators of a game, connected over TCP. Each spectator gets a snapshot of the game when connecting &
+//! every move with the position after it, as long as they stay connected. Anything sent by spectators is ignored.
+//!
+//! The stream is line based, moves are in the notation of [crate::game::notation] & positions in the extended position
+//! notation, which starts with the board code:
+//!
+//! ```text
+//! white Alice                                   the snapshot, sent again when another game starts
+//! black Bob
+//! start EEEEEEEEEEEEEEEEEEEEEEEE w 9 9 - 0 1
+//! moves d7 d6                                   the moves since the start, none for new games
+//! position WEEEEEEEBEEEEEEEEEEEEEEE w 8 8 - 2 2
+//! move g7 WWEEEEEEBEEEEEEEEEEEEEEE b 7 8 - 3 2   after each move
+//! position ...                                  after a pending capture of the start position was resolved
+//! end 1-0 too few stones                        once the game is over, in the snapshot too
+//! ```
+//!
+//! [Spectators] are fed by the moves of a network game or observe a local game as [GameObserver]. Spectators which
+//! can't keep up for a second are disconnected, so they can't stall the game.
+
+use std::{
+    io::Write,
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::game::{
+    efficient_state::moves::coded_index_of,
+    events::{GameEvent, GameObserver},
+    notation::Move,
+    position::ExtendedPosition,
+    record::{GameResult, Termination},
+};
+
+/// How long writing to a spectator may block
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The game as the spectators see it
+struct Game {
+    white: String,
+    black: String,
+    start: ExtendedPosition,
+    moves: Vec<Move>,
+    position: ExtendedPosition,
+    end: Option<(GameResult, Termination)>,
+}
+
+impl Game {
+    fn snapshot(&self) -> String {
+        let moves: String = self.moves.iter().map(|played| format!(" {played}")).collect();
+        let mut snapshot = format!(
+            "white {}\nblack {}\nstart {}\nmoves{moves}\nposition {}\n",
+            self.white, self.black, self.start, self.position
+        );
+        if let Some(end) = self.end {
+            snapshot += &end_line(end);
+        }
+        snapshot
+    }
+}
+
+fn end_line((result, termination): (GameResult, Termination)) -> String {
+    format!("end {result} {termination}\n")
+}
+
+#[derive(Default)]
+struct Broadcast {
+    clients: Vec<TcpStream>,
+    /// None until the first game starts
+    game: Option<Game>,
+}
+
+impl Broadcast {
+    /// Sends the lines to all spectators & drops the ones which failed
+    fn send(&mut self, lines: &str) {
+        self.clients
+            .retain_mut(|client| client.write_all(lines.as_bytes()).and_then(|_| client.flush()).is_ok());
+    }
+}
+
+pub struct Spectators {
+    broadcast: Arc<Mutex<Broadcast>>,
+    /// The move of a local game waiting for its take, see [GameObserver]
+    pending: Option<Move>,
+}
+
+impl Spectators {
+    /// Accepts spectators on the listener in the background, for as long as the program runs
+    pub fn listen(listener: TcpListener) -> Self {
+        let broadcast = Arc::new(Mutex::new(Broadcast::default()));
+
+        let accepting = Arc::clone(&broadcast);
+        thread::spawn(move || {
+            for client in listener.incoming() {
+                let Ok(mut client) = client else {
+                    continue;
+                };
+                // Read-only, a spectator sending something can't block anything
+                if client.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                    continue;
+                }
+                let mut broadcast = accepting.lock().unwrap();
+                let snapshot = broadcast.game.as_ref().map(Game::snapshot).unwrap_or_default();
+                if client.write_all(snapshot.as_bytes()).is_ok() {
+                    broadcast.clients.push(client);
+                }
+            }
+        });
+
+        Spectators {
+            broadcast,
+            pending: None,
+        }
+    }
+
+    /// The number of connected spectators, the ones which left are only noticed when writing to them
+    pub fn count(&self) -> usize {
+        self.broadcast.lock().unwrap().clients.len()
+    }
+
+    /// A new game starts, all spectators get its snapshot
+    pub fn start(&self, white: &str, black: &str, start: ExtendedPosition) {
+        let game = Game {
+            white: white.to_string(),
+            black: black.to_string(),
+            start,
+            moves: Vec::new(),
+            position: start,
+            end: None,
+        };
+        let mut broadcast = self.broadcast.lock().unwrap();
+        broadcast.send(&game.snapshot());
+        broadcast.game = Some(game);
+    }
+
+    /// Streams a legal move of the current game
+    pub fn play(&self, played: Move) {
+        let mut broadcast = self.broadcast.lock().unwrap();
+        let Some(game) = &mut broadcast.game else {
+            return;
+        };
+        let Some(efficient_move) = played.to_efficient() else {
+            return;
+        };
+        game.position = game.position.after(efficient_move);
+        game.moves.push(played);
+        let line = format!("move {played} {}\n", game.position);
+        broadcast.send(&line);
+    }
+
+    pub fn end(&self, result: GameResult, termination: Termination) {
+        let mut broadcast = self.broadcast.lock().unwrap();
+        let Some(game) = &mut broadcast.game else {
+            return;
+        };
+        game.end = Some((result, termination));
+        broadcast.send(&end_line((result, termination)));
+    }
+
+    /// Resolves the pending capture of the start position
+    fn take_first(&self, taken: usize) {
+        let mut broadcast = self.broadcast.lock().unwrap();
+        let Some(game) = &mut broadcast.game else {
+            return;
+        };
+        game.position = game.position.after_take(taken as u8);
+        let line = format!("position {}\n", game.position);
+        broadcast.send(&line);
+    }
+
+    /// True if the placement or move of the side to move closes a mill, so the take follows
+    fn closes_mill(&self, played: Move) -> bool {
+        let broadcast = self.broadcast.lock().unwrap();
+        let (Some(game), Some(efficient_move)) = (&broadcast.game, played.to_efficient()) else {
+            return false;
+        };
+        game.position.position.legal_moves().iter().any(|legal| {
+            legal.start == efficient_move.start && legal.target == efficient_move.target && legal.take.is_some()
+        })
+    }
+
+    fn pending_capture(&self) -> bool {
+        let broadcast = self.broadcast.lock().unwrap();
+        broadcast
+            .game
+            .as_ref()
+            .is_some_and(|game| game.position.pending_capture)
+    }
+}
+
+/// The events of a local game are put together to moves: a placement or move is streamed right away, unless it closes
+/// a mill & the take follows.
+impl GameObserver for Spectators {
+    fn notify(&mut self, event: &GameEvent) {
+        let played = match *event {
+            GameEvent::Started {
+                ref white,
+                ref black,
+                position,
+            } => {
+                self.pending = None;
+                self.start(white, black, position);
+                return;
+            }
+            GameEvent::StonePlaced { field, .. } => Move::placement(field),
+            GameEvent::StoneMoved { from, to, .. } => Move {
+                start: Some(from),
+                target: to,
+                take: None,
+            },
+            GameEvent::StoneTaken { field, .. } => match self.pending.take() {
+                Some(pending) => Move {
+                    take: Some(field),
+                    ..pending
+                },
+                None => {
+                    if let (true, Some(taken)) = (self.pending_capture(), coded_index_of(field)) {
+                        self.take_first(taken);
+                    }
+                    return;
+                }
+            },
+            GameEvent::Ended { result, termination } => {
+                self.end(result, termination);
+                return;
+            }
+            GameEvent::MillClosed { .. } | GameEvent::PhaseChanged { .. } => return,
+        };
+
+        if played.take.is_none() && self.closes_mill(played) {
+            self.pending = Some(played);
+        } else {
+            self.play(played);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::game::PlayerColor;
+
+    fn read_lines(reader: &mut impl BufRead, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|_| {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_and_moves() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let mut spectators = Spectators::listen(listener);
+
+        // Connected before the game starts
+        let mut early = BufReader::new(TcpStream::connect(address).unwrap());
+        while spectators.count() == 0 {
+            thread::yield_now();
+        }
+        let start = "WWEEEEEEBBEEEEEEEEEEEEEE b 7 7 - 3 4".parse().unwrap();
+        spectators.notify(&GameEvent::Started {
+            white: "Alice".to_string(),
+            black: "Bob".to_string(),
+            position: start,
+        });
+        spectators.notify(&GameEvent::StonePlaced {
+            color: PlayerColor::Black,
+            field: ('D', 5),
+        });
+        // The take is waited for
+        spectators.notify(&GameEvent::StonePlaced {
+            color: PlayerColor::White,
+            field: ('A', 7),
+        });
+        spectators.notify(&GameEvent::MillClosed {
+            color: PlayerColor::White,
+            fields: smallvec![('A', 7), ('D', 7), ('G', 7)],
+        });
+        spectators.notify(&GameEvent::StoneTaken {
+            color: PlayerColor::White,
+            field: ('D', 6),
+        });
+
+        assert_eq!(
+            read_lines(&mut early, 7),
+            [
+                "white Alice",
+                "black Bob",
+                "start WWEEEEEEBBEEEEEEEEEEEEEE b 7 7 - 3 4",
+                "moves",
+                "position WWEEEEEEBBEEEEEEEEEEEEEE b 7 7 - 3 4",
+                "move d5 WWEEEEEEBBEEEEEEBEEEEEEE w 7 6 - 4 5",
+                "move a7xd6 WWEEEEEWEBEEEEEEBEEEEEEE b 6 6 - 0 5",
+            ]
+        );
+
+        // Late spectators get the game so far
+        let mut late = BufReader::new(TcpStream::connect(address).unwrap());
+        assert_eq!(
+            read_lines(&mut late, 5)[3..],
+            ["moves d5 a7xd6", "position WWEEEEEWEBEEEEEEBEEEEEEE b 6 6 - 0 5"]
+        );
+
+        spectators.end(GameResult::WhiteWins, Termination::Resignation);
+        for reader in [&mut early, &mut late] {
+            assert_eq!(read_lines(reader, 1), ["end 1-0 resignation"]);
+        }
+
+        // Spectators which left are dropped with the next line
+        drop(early);
+        spectators.end(GameResult::WhiteWins, Termination::Resignation);
+        spectators.end(GameResult::WhiteWins, Termination::Resignation);
+        assert_eq!(spectators.count(), 1);
+    }
+}
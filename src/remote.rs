@@ -0,0 +1,169 @@
+//! The terminal side of a game over the network: moves are entered in notation like `d7`, `d6-d5` or `a7xd6`, the
+//! moves of the opponent are shown as they arrive. Chat messages are entered at the move prompt with `chat <text>`,
+//! the ones of the opponent are shown as they arrive too. Spectators, if any, see the moves of both sides.
+
+use std::io::{self, Write};
+
+use muehle::{
+    game::{
+        notation::Move,
+        painting::*,
+        position::ExtendedPosition,
+        record::{GameResult, Termination},
+        state::PlayField,
+        Field,
+    },
+    network::{spectators::Spectators, NetworkError, NetworkGame, RemoteAction},
+};
+use smallvec::SmallVec;
+
+pub fn run(mut game: NetworkGame, spectators: Option<Spectators>) {
+    let record = game.record();
+    if let Some(spectators) = &spectators {
+        spectators.start(&record.white, &record.black, ExtendedPosition::default());
+    }
+    println!(
+        "> {} against {}, you play {}.",
+        EMP.paint(&record.white),
+        EMP.paint(&record.black),
+        HIGHLIGHT.paint(game.color())
+    );
+    println!(
+        "> Enter moves like {}, {} or {}, {} sends a message & {} gives up.",
+        HIGHLIGHT.paint("d7"),
+        HIGHLIGHT.paint("d6-d5"),
+        HIGHLIGHT.paint("a7xd6"),
+        HIGHLIGHT.paint("chat <text>"),
+        HIGHLIGHT.paint("resign")
+    );
+    show(&game, None);
+
+    while !game.is_over() {
+        let result = if game.position().side_to_move == game.color() {
+            play_own_move(&mut game)
+        } else {
+            println!("> Waiting for {}...", EMP.paint(game.record().name_of(!game.color())));
+            wait_for_move(&mut game)
+        };
+
+        match result {
+            Ok(Some(played)) => {
+                if let Some(spectators) = &spectators {
+                    spectators.play(played);
+                }
+                show(&game, Some(played));
+            }
+            Ok(None) => {}
+            Err(error) => {
+                print_error(&error.to_string());
+                if let Some(spectators) = &spectators {
+                    spectators.end(GameResult::Ongoing, Termination::Unterminated);
+                }
+                return;
+            }
+        }
+    }
+
+    let record = game.record();
+    if let Some(spectators) = &spectators {
+        spectators.end(record.result, record.termination);
+    }
+    let winner = match record.result {
+        GameResult::WhiteWins => &record.white,
+        GameResult::BlackWins => &record.black,
+        _ => unreachable!("network games end by a win"),
+    };
+    match record.termination {
+        Termination::Resignation => println!("> {} won by resignation.", EMP.paint(winner)),
+        termination => println!("> {} won the game by {termination}.", EMP.paint(winner)),
+    }
+}
+
+/// Shows the chat messages of the opponent until it moved, None if it resigned
+fn wait_for_move(game: &mut NetworkGame) -> Result<Option<Move>, NetworkError> {
+    loop {
+        match game.wait_for_opponent()? {
+            RemoteAction::Moved(played) => return Ok(Some(played)),
+            RemoteAction::Resigned => return Ok(None),
+            RemoteAction::Chatted(text) => print_chat(game, &text),
+        }
+    }
+}
+
+/// Asks until a move is accepted, None if the player resigned
+fn play_own_move(game: &mut NetworkGame) -> Result<Option<Move>, NetworkError> {
+    loop {
+        print_received_chats(game);
+        print!("> Your move: ");
+        io::stdout().flush().unwrap();
+
+        let mut input_buffer = String::new();
+        if io::stdin().read_line(&mut input_buffer)? == 0 {
+            // End of input counts as giving up
+            game.resign()?;
+            return Ok(None);
+        }
+        let input = input_buffer.trim();
+
+        if input.eq_ignore_ascii_case("resign") {
+            game.resign()?;
+            return Ok(None);
+        } else if let Some(text) = input.strip_prefix("chat ") {
+            game.chat(text.trim())?;
+            continue;
+        }
+        let played = match input.parse::<Move>() {
+            Ok(played) => played,
+            Err(error) => {
+                print_error(&format!("'{input}': {error}"));
+                continue;
+            }
+        };
+        let result = game.play(played);
+        // Messages may arrive while the host checks the move
+        print_received_chats(game);
+        match result {
+            Ok(_) => return Ok(Some(played)),
+            Err(NetworkError::IllegalMove(reason)) => print_error(&reason),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn show(game: &NetworkGame, last_move: Option<Move>) {
+    let mut to_highlight = SmallVec::<[Field; 3]>::new();
+    if let Some(played) = last_move {
+        println!(
+            "> {} played {}.",
+            EMP.paint(game.record().name_of(!game.position().side_to_move)),
+            EMP.paint(played)
+        );
+        to_highlight.extend(played.start);
+        to_highlight.push(played.target);
+        to_highlight.extend(played.take);
+    }
+
+    println!("\n");
+    let play_field = PlayField::try_from(game.position().board).expect("Network games start on the empty play field");
+    play_field.print_highlighted(if to_highlight.is_empty() {
+        None
+    } else {
+        Some(&to_highlight)
+    });
+    println!("\n");
+}
+
+fn print_received_chats(game: &mut NetworkGame) {
+    let chats: Vec<String> = game.take_chats().collect();
+    for text in chats {
+        print_chat(game, &text);
+    }
+}
+
+fn print_chat(game: &NetworkGame, text: &str) {
+    println!("> {}: {text}", EMP.paint(game.record().name_of(!game.color())));
+}
+
+fn print_error(message: &str) {
+    println!("> {}", ERROR.paint(message))
+}
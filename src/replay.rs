@@ -0,0 +1,243 @@
+//! Steps through a recorded game move by move, showing the play field after each move with the move highlighted.
+//! Closed mills & taken stones are announced when the move is shown.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use muehle::game::{
+    painting::*,
+    position::Position,
+    record::{GameRecord, GameResult},
+    state::PlayField,
+    Field, PlayerColor,
+};
+use smallvec::SmallVec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Next,
+    Previous,
+    /// To the position after the given amount of moves, 0 is the start
+    Jump(usize),
+    Quit,
+}
+
+impl Command {
+    fn parse(input: &str) -> Result<Command, String> {
+        let input = input.trim().to_lowercase();
+        let number = input
+            .strip_prefix("jump")
+            .or_else(|| input.strip_prefix('j'))
+            .unwrap_or(&input)
+            .trim();
+
+        match input.as_str() {
+            "" | "n" | "next" => Ok(Command::Next),
+            "p" | "prev" | "previous" => Ok(Command::Previous),
+            "q" | "quit" => Ok(Command::Quit),
+            _ => number
+                .parse()
+                .map(Command::Jump)
+                .map_err(|_| format!("Unknown command '{input}', expected n, p, j <move> or q.")),
+        }
+    }
+}
+
+pub struct Replay {
+    record: GameRecord,
+    /// The positions after each move, starting with the start of the game
+    positions: Vec<Position>,
+    /// Amount of moves played in the shown position
+    ply: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let record = GameRecord::load(path).map_err(|error| format!("Loading '{}' failed: {error}", path.display()))?;
+        let positions = record.replay().map_err(|error| error.to_string())?;
+
+        Ok(Replay {
+            record,
+            positions,
+            ply: 0,
+        })
+    }
+
+    pub fn run(&mut self) {
+        println!(
+            "> Replaying {} against {} ({} moves, result {}).",
+            EMP.paint(&self.record.white),
+            EMP.paint(&self.record.black),
+            HIGHLIGHT.paint(self.record.moves.len()),
+            HIGHLIGHT.paint(self.record.result)
+        );
+        println!(
+            "> Enter {} or nothing for the next move, {} for the previous one, {} to jump to a move & {} to quit.",
+            HIGHLIGHT.paint("n"),
+            HIGHLIGHT.paint("p"),
+            HIGHLIGHT.paint("j <move>"),
+            HIGHLIGHT.paint("q")
+        );
+        self.show();
+
+        loop {
+            print!("> Replay command: ");
+            io::stdout().flush().unwrap();
+
+            let mut input_buffer = String::new();
+            match io::stdin().read_line(&mut input_buffer) {
+                // End of input
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(error) => {
+                    print_error(&format!("Error occurred processing input: {error}"));
+                    continue;
+                }
+            }
+
+            let last_ply = self.record.moves.len();
+            let ply = match Command::parse(&input_buffer) {
+                Ok(Command::Quit) => break,
+                Ok(Command::Next) if self.ply == last_ply => {
+                    print_error("This is the end of the game.");
+                    continue;
+                }
+                Ok(Command::Previous) if self.ply == 0 => {
+                    print_error("This is the start of the game.");
+                    continue;
+                }
+                Ok(Command::Next) => self.ply + 1,
+                Ok(Command::Previous) => self.ply - 1,
+                Ok(Command::Jump(ply)) if last_ply < ply => {
+                    print_error(&format!("The game only has {last_ply} moves."));
+                    continue;
+                }
+                Ok(Command::Jump(ply)) => ply,
+                Err(message) => {
+                    print_error(&message);
+                    continue;
+                }
+            };
+            self.ply = ply;
+            self.show();
+        }
+    }
+
+    /// Prints the current position, the move leading to it & its events
+    fn show(&self) {
+        let position = &self.positions[self.ply];
+        let play_field = PlayField::try_from(position.board).expect("Records can't start with more than 9 stones");
+        let mut to_highlight = SmallVec::<[Field; 3]>::new();
+
+        println!();
+        if self.ply == 0 {
+            println!("> Start of the game.");
+        } else {
+            let played = self.record.moves[self.ply - 1];
+            let color = self.positions[self.ply - 1].side_to_move;
+            println!(
+                "> Move {} of {}: {} {} by {} ({}).",
+                HIGHLIGHT.paint(self.ply),
+                HIGHLIGHT.paint(self.record.moves.len()),
+                self.move_number_of(self.ply - 1),
+                EMP.paint(played),
+                EMP.paint(self.record.name_of(color)),
+                HIGHLIGHT.paint(color)
+            );
+
+            to_highlight.extend(played.start);
+            to_highlight.push(played.target);
+            if let Some(take) = played.take {
+                let mills = play_field.get_mill_crossing(played.target);
+                println!(
+                    "> {} closes a mill on {}.",
+                    EMP.paint(self.record.name_of(color)),
+                    EMP.paint(fields_to_string(&mills))
+                );
+                println!(
+                    "> {} takes the stone on {}.",
+                    EMP.paint(self.record.name_of(color)),
+                    EMP.paint(fields_to_string(&[take]))
+                );
+                to_highlight.extend(mills.into_iter().filter(|field| *field != played.target));
+                to_highlight.push(take);
+            }
+        }
+
+        println!("\n");
+        play_field.print_highlighted(if to_highlight.is_empty() {
+            None
+        } else {
+            Some(&to_highlight)
+        });
+        println!("\n");
+
+        if self.ply == self.record.moves.len() {
+            match (self.record.result, position.winner()) {
+                (_, Some(winner)) => println!(
+                    "> {} won the game by {}.",
+                    EMP.paint(self.record.name_of(winner)),
+                    self.record.termination
+                ),
+                (GameResult::Ongoing, None) => println!("> The game is unfinished."),
+                (result, None) => println!(
+                    "> The game ended {} by {}.",
+                    HIGHLIGHT.paint(result),
+                    self.record.termination
+                ),
+            }
+        } else {
+            println!(
+                "> {} is on turn.",
+                EMP.paint(self.record.name_of(position.side_to_move))
+            );
+        }
+    }
+
+    /// The move number like `3.` for moves of white & `3...` for moves of black
+    fn move_number_of(&self, ply: usize) -> String {
+        let (first_number, black_begins) = self.record.start.map_or((1, false), |start| {
+            (
+                start.move_number as usize,
+                start.position.side_to_move == PlayerColor::Black,
+            )
+        });
+        let number = first_number + (ply + black_begins as usize) / 2;
+
+        match self.positions[ply].side_to_move {
+            PlayerColor::White => format!("{number}."),
+            PlayerColor::Black => format!("{number}..."),
+        }
+    }
+}
+
+fn fields_to_string(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|(column, row)| format!("{}{row}", column.to_ascii_lowercase()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_error(message: &str) {
+    println!("> {}", ERROR.paint(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        assert_eq!(Command::parse("\n"), Ok(Command::Next));
+        assert_eq!(Command::parse(" P "), Ok(Command::Previous));
+        assert_eq!(Command::parse("j 12"), Ok(Command::Jump(12)));
+        assert_eq!(Command::parse("jump 0"), Ok(Command::Jump(0)));
+        assert_eq!(Command::parse("7"), Ok(Command::Jump(7)));
+        assert_eq!(Command::parse("quit"), Ok(Command::Quit));
+        assert!(Command::parse("j").is_err());
+        assert!(Command::parse("back").is_err());
+    }
+}
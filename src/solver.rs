@@ -0,0 +1,130 @@
+//! Retrograde analysis of the move (& jump) phase. The positions are split into classes by the amount of stones each
+//! player has left (see [StoneClass]), which are solved one after another: taking a stone always leads into a class
+//! with less stones, so the classes can be solved in the order of their total amount of stones.
+//!
+//! A class is solved by repeatedly passing over all of its positions, deriving the value of each position from the
+//! values of its successors, until a pass changes nothing anymore. Positions which are still undecided then are draws.
+
+pub mod class;
+pub mod database;
+pub mod driver;
+pub mod set_phase;
+pub mod verify;
+
+use crate::game::efficient_state::moves::for_each_move_successor;
+
+use self::class::StoneClass;
+
+/// A player with less stones than this has lost the game
+pub const MIN_STONES: u8 = 3;
+
+/// The game theoretic value of a position from the view of the player to move.
+/// While solving, [PositionValue::Draw] is also used for the positions which aren't decided yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PositionValue {
+    Draw = 0,
+    Win = 1,
+    Loss = 2,
+}
+
+impl From<u8> for PositionValue {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => PositionValue::Draw,
+            1 => PositionValue::Win,
+            2 => PositionValue::Loss,
+            _ => panic!("Invalid position value {value}"),
+        }
+    }
+}
+
+/// Derives the value of the position `(movers_stones, opponents_stones)` of `class` from the values of its successors,
+/// which are requested by calling `lookup` with the successors class & index.
+///
+/// Rules applied:
+/// - A player who can't move anymore has lost
+/// - Closing a mill takes exactly one stone of the opponent, even if two mills were closed at once
+/// - Taking the opponents third last stone wins the game
+pub fn evaluate_position(
+    class: StoneClass,
+    movers_stones: u32,
+    opponents_stones: u32,
+    mut lookup: impl FnMut(StoneClass, u64) -> PositionValue,
+) -> PositionValue {
+    let mut found_win = false;
+    let mut all_successors_won = true;
+
+    for_each_move_successor(
+        movers_stones,
+        opponents_stones,
+        |new_movers_stones, new_opponents_stones| {
+            if found_win {
+                return;
+            }
+
+            let opponents_left = new_opponents_stones.count_ones() as u8;
+            if opponents_left < MIN_STONES {
+                found_win = true;
+                return;
+            }
+
+            // The successor is viewed from the opponent, who's on turn next
+            let successor_class = StoneClass::new(opponents_left, class.movers_stones);
+            match lookup(
+                successor_class,
+                successor_class.rank(new_opponents_stones, new_movers_stones),
+            ) {
+                PositionValue::Loss => found_win = true,
+                PositionValue::Draw => all_successors_won = false,
+                PositionValue::Win => {}
+            }
+        },
+    );
+
+    if found_win {
+        PositionValue::Win
+    } else if all_successors_won {
+        // Also the case if no move was possible at all
+        PositionValue::Loss
+    } else {
+        PositionValue::Draw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::efficient_state::EfficientPlayField;
+
+    fn evaluate_coded(coded: &str, successor_value: PositionValue) -> PositionValue {
+        let (movers_stones, opponents_stones) = EfficientPlayField::from_coded(coded).get_masks();
+        let class = StoneClass::new(movers_stones.count_ones() as u8, opponents_stones.count_ones() as u8);
+
+        evaluate_position(class, movers_stones, opponents_stones, |_, _| successor_value)
+    }
+
+    #[test]
+    fn closing_a_mill_against_three_stones_wins() {
+        // White jumps into the top row of the outer ring
+        assert_eq!(
+            evaluate_coded("WEEEEEEWEEEEEEEWBBBEEEEE", PositionValue::Draw),
+            PositionValue::Win
+        );
+    }
+
+    #[test]
+    fn blocked_player_loses() {
+        // White's 4 stones on the outer ring's top right are enclosed by black
+        let coded = "WWWBEEBWBEBEEEEEEEEEEEEE";
+        assert_eq!(evaluate_coded(coded, PositionValue::Draw), PositionValue::Loss);
+    }
+
+    #[test]
+    fn value_derived_from_successors() {
+        let coded = "WWEEEWWEBBEEEBEBEEEEEEEE";
+        assert_eq!(evaluate_coded(coded, PositionValue::Loss), PositionValue::Win);
+        assert_eq!(evaluate_coded(coded, PositionValue::Win), PositionValue::Loss);
+        assert_eq!(evaluate_coded(coded, PositionValue::Draw), PositionValue::Draw);
+    }
+}
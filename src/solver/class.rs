@@ -0,0 +1,165 @@
+//! Enumeration of the positions of one stone-count class with a perfect index, using the combinatorial number system.
+//! The moving player's stones are ranked among all 24 fields, the opponents stones among the fields left free.
+
+use crate::game::efficient_state::moves::FIELD_COUNT;
+
+/// Positions of the move phase are stored from the view of the player to move: [StoneClass] `(3, 4)` holds all
+/// positions where the player on turn has 3 stones left and the opponent 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StoneClass {
+    pub movers_stones: u8,
+    pub opponents_stones: u8,
+}
+
+const BINOMIAL: [[u64; FIELD_COUNT + 1]; FIELD_COUNT + 1] = binomial_table();
+
+const fn binomial_table() -> [[u64; FIELD_COUNT + 1]; FIELD_COUNT + 1] {
+    let mut table = [[0u64; FIELD_COUNT + 1]; FIELD_COUNT + 1];
+
+    let mut n = 0;
+    while n <= FIELD_COUNT {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+impl StoneClass {
+    pub fn new(movers_stones: u8, opponents_stones: u8) -> Self {
+        assert!(
+            movers_stones as usize + opponents_stones as usize <= FIELD_COUNT,
+            "More stones than fields"
+        );
+        StoneClass {
+            movers_stones,
+            opponents_stones,
+        }
+    }
+
+    /// The class the positions of this class lead to, if no stone is taken: the opponent is on turn
+    pub fn swapped(&self) -> StoneClass {
+        StoneClass::new(self.opponents_stones, self.movers_stones)
+    }
+
+    /// The amount of positions in this class
+    pub fn size(&self) -> u64 {
+        BINOMIAL[FIELD_COUNT][self.movers_stones as usize]
+            * BINOMIAL[FIELD_COUNT - self.movers_stones as usize][self.opponents_stones as usize]
+    }
+
+    /// Maps the stone masks of a position of this class to its index in `0..self.size()`
+    pub fn rank(&self, movers_stones: u32, opponents_stones: u32) -> u64 {
+        debug_assert!(movers_stones.count_ones() == self.movers_stones as u32);
+        debug_assert!(opponents_stones.count_ones() == self.opponents_stones as u32);
+
+        // The opponents stones are numbered by their position among the fields not covered by the mover
+        let mut compressed = 0u32;
+        let mut remaining = opponents_stones;
+        while remaining != 0 {
+            let field = remaining.trailing_zeros();
+            remaining &= remaining - 1;
+
+            let movers_stones_below = (movers_stones & ((1 << field) - 1)).count_ones();
+            compressed |= 1 << (field - movers_stones_below);
+        }
+
+        let opponents_combinations =
+            BINOMIAL[FIELD_COUNT - self.movers_stones as usize][self.opponents_stones as usize];
+        rank_subset(movers_stones) * opponents_combinations + rank_subset(compressed)
+    }
+
+    /// Inverse of [rank], returns the stone masks `(movers_stones, opponents_stones)`
+    pub fn unrank(&self, index: u64) -> (u32, u32) {
+        debug_assert!(index < self.size());
+
+        let opponents_combinations =
+            BINOMIAL[FIELD_COUNT - self.movers_stones as usize][self.opponents_stones as usize];
+        let movers_stones = unrank_subset(index / opponents_combinations, self.movers_stones as usize);
+        let compressed = unrank_subset(index % opponents_combinations, self.opponents_stones as usize);
+
+        // Spread the compressed positions over the fields left free by the mover
+        let mut opponents_stones = 0u32;
+        let mut free_position = 0;
+        for field in 0..FIELD_COUNT {
+            if movers_stones & (1 << field) != 0 {
+                continue;
+            }
+            if compressed & (1 << free_position) != 0 {
+                opponents_stones |= 1 << field;
+            }
+            free_position += 1;
+        }
+
+        (movers_stones, opponents_stones)
+    }
+}
+
+/// Colexicographical rank of the set bits of `subset`
+fn rank_subset(mut subset: u32) -> u64 {
+    let mut rank = 0;
+    let mut k = 1;
+
+    while subset != 0 {
+        let position = subset.trailing_zeros() as usize;
+        subset &= subset - 1;
+
+        rank += BINOMIAL[position][k];
+        k += 1;
+    }
+    rank
+}
+
+fn unrank_subset(mut rank: u64, size: usize) -> u32 {
+    let mut subset = 0u32;
+    let mut position = FIELD_COUNT;
+
+    for k in (1..=size).rev() {
+        // Largest position with binomial(position, k) <= rank
+        position -= 1;
+        while BINOMIAL[position][k] > rank {
+            position -= 1;
+        }
+        rank -= BINOMIAL[position][k];
+        subset |= 1 << position;
+    }
+    subset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StoneClass;
+
+    #[test]
+    fn class_sizes() {
+        assert_eq!(StoneClass::new(3, 3).size(), 2024 * 1330);
+        assert_eq!(StoneClass::new(9, 9).size(), 1_307_504 * 5005);
+    }
+
+    #[test]
+    fn rank_unrank_round_trip() {
+        let class = StoneClass::new(4, 3);
+
+        for index in (0..class.size()).step_by(997) {
+            let (movers_stones, opponents_stones) = class.unrank(index);
+
+            assert_eq!(movers_stones.count_ones(), 4);
+            assert_eq!(opponents_stones.count_ones(), 3);
+            assert_eq!(movers_stones & opponents_stones, 0);
+            assert_eq!(class.rank(movers_stones, opponents_stones), index);
+        }
+    }
+
+    #[test]
+    fn last_index_covers_the_last_fields() {
+        let class = StoneClass::new(3, 3);
+        let (movers_stones, opponents_stones) = class.unrank(class.size() - 1);
+
+        assert_eq!(movers_stones, 0b111 << 21);
+        assert_eq!(opponents_stones, 0b111 << 18);
+    }
+}
@@ -0,0 +1,300 @@
+//! The on-disk format of the solved classes. Each [StoneClass] is stored in its own file inside of the database
+//! directory, holding a small header followed by one byte per position of the class, in the order of [StoneClass::rank].
+//!
+//! Header layout (little endian):
+//! - 8 bytes magic `MUEHLEDB`
+//! - 1 byte format version, 1 byte movers stones, 1 byte opponents stones, 1 byte completion flag
+//! - 4 bytes passes done so far
+//! - 8 bytes amount of positions
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use super::{class::StoneClass, PositionValue};
+use crate::game::efficient_state::moves::FIELD_COUNT;
+
+const MAGIC: &[u8; 8] = b"MUEHLEDB";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 24;
+
+/// The values of all positions of one [StoneClass], either finished or as checkpoint of an interrupted run
+pub struct ClassDatabase {
+    pub class: StoneClass,
+    /// False if the database is only a checkpoint of a class which is still being solved
+    pub complete: bool,
+    pub passes: u32,
+    pub values: Vec<u8>,
+}
+
+impl ClassDatabase {
+    pub fn new(class: StoneClass) -> Self {
+        ClassDatabase {
+            class,
+            complete: false,
+            passes: 0,
+            values: vec![PositionValue::Draw as u8; class.size() as usize],
+        }
+    }
+
+    #[inline]
+    pub fn value(&self, index: u64) -> PositionValue {
+        PositionValue::from(self.values[index as usize])
+    }
+
+    /// Reads a class database file & checks its header for consistency
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let (class, complete, passes, count) = read_header(&mut reader, path)?;
+
+        let mut values = Vec::with_capacity(count as usize);
+        reader.read_to_end(&mut values)?;
+        if values.len() as u64 != count {
+            return Err(invalid_data(path, "file is truncated"));
+        } else if values.iter().any(|value| 2 < *value) {
+            return Err(invalid_data(path, "invalid position value"));
+        }
+
+        Ok(ClassDatabase {
+            class,
+            complete,
+            passes,
+            values,
+        })
+    }
+
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        write_file(path, self.class, self.complete, self.passes, |writer| {
+            writer.write_all(&self.values)
+        })
+    }
+}
+
+/// Reads the header & checks it for consistency, returns (class, complete, passes, count)
+fn read_header(reader: &mut impl Read, path: &Path) -> io::Result<(StoneClass, bool, u32, u64)> {
+    let mut header = [0u8; HEADER_LEN];
+    reader.read_exact(&mut header)?;
+
+    if &header[0..8] != MAGIC {
+        return Err(invalid_data(path, "not a mill database file"));
+    } else if header[8] != VERSION {
+        return Err(invalid_data(path, "unsupported database version"));
+    }
+
+    if FIELD_COUNT < header[9] as usize + header[10] as usize {
+        return Err(invalid_data(
+            path,
+            &format!(
+                "stores {} & {} stones, but the board has only {FIELD_COUNT} fields",
+                header[9], header[10]
+            ),
+        ));
+    }
+    let class = StoneClass::new(header[9], header[10]);
+    let complete = header[11] != 0;
+    let passes = u32::from_le_bytes(header[12..16].try_into().unwrap());
+    let count = u64::from_le_bytes(header[16..24].try_into().unwrap());
+
+    if count != class.size() {
+        return Err(invalid_data(
+            path,
+            &format!("stores {count} positions, but the class enumerates {}", class.size()),
+        ));
+    }
+    Ok((class, complete, passes, count))
+}
+
+/// Writes the database into a temporary file first, which is then renamed to `path`.
+/// This way an interrupted write never leaves a corrupted checkpoint behind.
+fn write_file(
+    path: &Path,
+    class: StoneClass,
+    complete: bool,
+    passes: u32,
+    write_values: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let temporary_path = path.with_extension("tmp");
+
+    {
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, class.movers_stones, class.opponents_stones, complete as u8])?;
+        writer.write_all(&passes.to_le_bytes())?;
+        writer.write_all(&class.size().to_le_bytes())?;
+        write_values(&mut writer)?;
+        writer.into_inner()?.sync_all()?;
+    }
+
+    fs::rename(temporary_path, path)
+}
+
+/// The directory holding the class database files
+pub struct Database {
+    directory: PathBuf,
+}
+
+impl Database {
+    pub fn open(directory: &Path) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        Ok(Database {
+            directory: directory.to_path_buf(),
+        })
+    }
+
+    pub fn path_of(&self, class: StoneClass) -> PathBuf {
+        self.directory
+            .join(format!("{}_{}.db", class.movers_stones, class.opponents_stones))
+    }
+
+    /// Returns the database of the class, if it was already (partially) solved
+    pub fn load(&self, class: StoneClass) -> io::Result<Option<ClassDatabase>> {
+        let path = self.path_of(class);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let class_database = ClassDatabase::load(&path)?;
+        if class_database.class != class {
+            return Err(invalid_data(&path, "file name doesn't match the stored class"));
+        }
+        Ok(Some(class_database))
+    }
+
+    /// Returns the class database only if it was solved completely
+    pub fn load_complete(&self, class: StoneClass) -> io::Result<Option<ClassDatabase>> {
+        Ok(self.load(class)?.filter(|class_database| class_database.complete))
+    }
+
+    /// Checks if the class was solved completely by reading only the header of its file
+    pub fn is_complete(&self, class: StoneClass) -> io::Result<bool> {
+        let path = self.path_of(class);
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        let (stored_class, complete, _, _) = read_header(&mut File::open(&path)?, &path)?;
+        if stored_class != class {
+            return Err(invalid_data(&path, "file name doesn't match the stored class"));
+        }
+        Ok(complete)
+    }
+
+    /// Reads the value of a single position from disk without loading its whole class.
+    /// Returns `None` if the class isn't solved completely.
+    pub fn lookup(&self, movers_stones: u32, opponents_stones: u32) -> io::Result<Option<PositionValue>> {
+        let class = StoneClass::new(movers_stones.count_ones() as u8, opponents_stones.count_ones() as u8);
+        let path = self.path_of(class);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let mut file = File::open(&path)?;
+        let (stored_class, complete, _, _) = read_header(&mut file, &path)?;
+        if stored_class != class {
+            return Err(invalid_data(&path, "file name doesn't match the stored class"));
+        } else if !complete {
+            return Ok(None);
+        }
+
+        let mut value = [0u8];
+        file.seek(SeekFrom::Start(
+            HEADER_LEN as u64 + class.rank(movers_stones, opponents_stones),
+        ))?;
+        file.read_exact(&mut value)?;
+        match value[0] {
+            0..=2 => Ok(Some(PositionValue::from(value[0]))),
+            _ => Err(invalid_data(&path, "invalid position value")),
+        }
+    }
+
+    pub fn store(&self, class_database: &ClassDatabase) -> io::Result<()> {
+        class_database.store(&self.path_of(class_database.class))
+    }
+
+    /// Stores the values of a class while it's being solved, without copying them first
+    pub fn store_atomic(&self, class: StoneClass, complete: bool, passes: u32, values: &[AtomicU8]) -> io::Result<()> {
+        assert!(values.len() as u64 == class.size());
+
+        write_file(&self.path_of(class), class, complete, passes, |writer| {
+            let mut buffer = [0u8; 1 << 16];
+            for chunk in values.chunks(buffer.len()) {
+                for (byte, value) in buffer.iter_mut().zip(chunk) {
+                    *byte = value.load(Ordering::Relaxed);
+                }
+                writer.write_all(&buffer[..chunk.len()])?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn invalid_data(path: &Path, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_and_load() {
+        let directory = std::env::temp_dir().join(format!("muehle_database_{}", std::process::id()));
+        let database = Database::open(&directory).unwrap();
+        let class = StoneClass::new(3, 4);
+
+        assert!(database.load(class).unwrap().is_none());
+
+        let mut class_database = ClassDatabase::new(class);
+        class_database.passes = 3;
+        class_database.values[17] = PositionValue::Win as u8;
+        database.store(&class_database).unwrap();
+
+        let loaded = database.load(class).unwrap().unwrap();
+        assert!(!loaded.complete);
+        assert_eq!(loaded.passes, 3);
+        assert_eq!(loaded.value(17), PositionValue::Win);
+        assert_eq!(loaded.value(18), PositionValue::Draw);
+        assert!(database.load_complete(class).unwrap().is_none());
+        assert!(!database.is_complete(class).unwrap());
+
+        let (movers_stones, opponents_stones) = class.unrank(17);
+        assert_eq!(database.lookup(movers_stones, opponents_stones).unwrap(), None);
+        class_database.complete = true;
+        database.store(&class_database).unwrap();
+        assert!(database.is_complete(class).unwrap());
+        assert_eq!(
+            database.lookup(movers_stones, opponents_stones).unwrap(),
+            Some(PositionValue::Win)
+        );
+
+        // A corrupt value is detected when loading, not when it's read
+        let path = database.path_of(class);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[HEADER_LEN + 18] = 7;
+        fs::write(&path, &bytes).unwrap();
+        let error = database.load(class).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // So is a file cut off while writing
+        let truncated_length = fs::metadata(&path).unwrap().len() - 1;
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(truncated_length)
+            .unwrap();
+        assert!(database.load(class).is_err());
+
+        // So is a header with impossible stone counts
+        let mut header = fs::read(&path).unwrap();
+        header[9] = 30;
+        fs::write(&path, &header).unwrap();
+        let error = database.is_complete(class).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
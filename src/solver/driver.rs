@@ -0,0 +1,400 @@
+//! Schedules the [StoneClass]es in dependency order & solves each of them with multiple threads.
+//!
+//! After every pass over a class its values are written to the database directory as checkpoint, so an interrupted run
+//! continues with the pass it was interrupted in when started again. Finished classes are skipped.
+
+use std::{
+    fmt::Display,
+    io, mem, panic,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    class::StoneClass,
+    database::{ClassDatabase, Database},
+    evaluate_position, PositionValue, MIN_STONES,
+};
+
+/// Amount of positions a worker thread claims at once
+const CHUNK_SIZE: u64 = 4096;
+
+/// Maximum time the reporting thread sleeps between checking on the workers
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+pub struct SolverConfig {
+    pub database_directory: PathBuf,
+    pub threads: usize,
+    /// The maximum amount of stones of each player, which is 9 for Nine Men's Morris
+    pub max_stones: u8,
+    /// Minimum time between two calls of the progress callback
+    pub progress_interval: Duration,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            database_directory: PathBuf::from("database"),
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            max_stones: 9,
+            progress_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Reported periodically while solving
+#[derive(Debug, Clone)]
+pub struct SolverProgress {
+    pub classes: (StoneClass, StoneClass),
+    pub pass: u32,
+    pub positions_done: u64,
+    pub positions_in_pass: u64,
+    pub positions_per_second: f64,
+    /// Rough estimate, assuming the remaining classes need as many passes as the ones solved during this run
+    pub estimated_remaining: Duration,
+}
+
+impl Display for SolverProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.estimated_remaining.as_secs();
+        write!(
+            f,
+            "class {}v{} & {}v{}, pass {}: {:.1}% | {:.0} positions/s | ~{}h {:02}m {:02}s remaining",
+            self.classes.0.movers_stones,
+            self.classes.0.opponents_stones,
+            self.classes.1.movers_stones,
+            self.classes.1.opponents_stones,
+            self.pass,
+            100.0 * self.positions_done as f64 / self.positions_in_pass.max(1) as f64,
+            self.positions_per_second,
+            seconds / 3600,
+            (seconds / 60) % 60,
+            seconds % 60
+        )
+    }
+}
+
+/// Returns the classes up to `max_stones` in the order they must be solved in.
+/// A class & its swapped class depend on each other (the opponent moves next), so they are returned as pair and must be
+/// solved together. Taking a stone leads into a pair with one stone less, which is always scheduled before.
+pub fn schedule(max_stones: u8) -> Vec<(StoneClass, StoneClass)> {
+    let mut pairs = Vec::new();
+
+    for total in 2 * MIN_STONES..=2 * max_stones {
+        for movers_stones in MIN_STONES..=max_stones.min(total / 2) {
+            let opponents_stones = total - movers_stones;
+            if max_stones < opponents_stones {
+                continue;
+            }
+
+            let class = StoneClass::new(movers_stones, opponents_stones);
+            pairs.push((class, class.swapped()));
+        }
+    }
+    pairs
+}
+
+/// Solves all classes of [schedule] which aren't already solved in the configured database directory.
+/// `on_progress` is called at most every `config.progress_interval`.
+pub fn solve_all(config: &SolverConfig, mut on_progress: impl FnMut(&SolverProgress)) -> io::Result<()> {
+    let database = Database::open(&config.database_directory)?;
+
+    let mut unsolved = Vec::new();
+    for (class, swapped_class) in schedule(config.max_stones) {
+        if !database.is_complete(class)? || !database.is_complete(swapped_class)? {
+            unsolved.push((class, swapped_class));
+        }
+    }
+
+    let mut estimate = Estimate {
+        positions_left: unsolved
+            .iter()
+            .map(|(class, swapped)| pair_size(*class, *swapped))
+            .sum(),
+        passes_done: 0,
+        pairs_done: 0,
+    };
+
+    for (class, swapped_class) in unsolved {
+        solve_pair(
+            &database,
+            config,
+            (class, swapped_class),
+            &mut estimate,
+            &mut on_progress,
+        )?;
+    }
+    Ok(())
+}
+
+struct Estimate {
+    /// Positions of the pairs which weren't started yet, including the current one
+    positions_left: u64,
+    passes_done: u32,
+    pairs_done: u32,
+}
+
+impl Estimate {
+    fn remaining(&self, positions_left_in_pass: u64, positions_per_second: f64) -> Duration {
+        let passes_per_pair = if self.pairs_done == 0 {
+            1.0
+        } else {
+            self.passes_done as f64 / self.pairs_done as f64
+        };
+        let positions = positions_left_in_pass as f64 + self.positions_left as f64 * passes_per_pair;
+
+        if positions_per_second <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(positions / positions_per_second)
+        }
+    }
+}
+
+fn pair_size(class: StoneClass, swapped_class: StoneClass) -> u64 {
+    if class == swapped_class {
+        class.size()
+    } else {
+        class.size() + swapped_class.size()
+    }
+}
+
+/// Loads the checkpoint of the class or starts from scratch
+fn load_or_create(database: &Database, class: StoneClass) -> io::Result<ClassDatabase> {
+    Ok(database.load(class)?.unwrap_or_else(|| ClassDatabase::new(class)))
+}
+
+/// Hands the values over to the worker threads in place, a copy would double the memory of the large classes
+fn into_atomics(values: Vec<u8>) -> Vec<AtomicU8> {
+    let mut values = mem::ManuallyDrop::new(values);
+    // SAFETY: AtomicU8 has the same size, alignment & valid bit patterns as u8, so the allocation is taken over as is
+    unsafe { Vec::from_raw_parts(values.as_mut_ptr().cast::<AtomicU8>(), values.len(), values.capacity()) }
+}
+
+fn solve_pair(
+    database: &Database,
+    config: &SolverConfig,
+    (class, swapped_class): (StoneClass, StoneClass),
+    estimate: &mut Estimate,
+    on_progress: &mut impl FnMut(&SolverProgress),
+) -> io::Result<()> {
+    let mut databases = vec![load_or_create(database, class)?];
+    if class != swapped_class {
+        databases.push(load_or_create(database, swapped_class)?);
+    }
+    let mut pass = databases
+        .iter()
+        .map(|class_database| class_database.passes)
+        .min()
+        .unwrap();
+
+    // The classes the pair leads to by taking a stone, which are solved already
+    let mut dependencies = Vec::new();
+    for dependency in [
+        StoneClass::new(class.opponents_stones - 1, class.movers_stones),
+        StoneClass::new(swapped_class.opponents_stones - 1, swapped_class.movers_stones),
+    ] {
+        if MIN_STONES <= dependency.movers_stones && !dependencies.iter().any(|d: &ClassDatabase| d.class == dependency)
+        {
+            dependencies.push(database.load_complete(dependency)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "Class {}v{} must be solved before {}v{}",
+                        dependency.movers_stones,
+                        dependency.opponents_stones,
+                        class.movers_stones,
+                        class.opponents_stones
+                    ),
+                )
+            })?);
+        }
+    }
+
+    let classes: Vec<StoneClass> = databases.iter().map(|class_database| class_database.class).collect();
+    let values: Vec<Vec<AtomicU8>> = databases
+        .into_iter()
+        .map(|class_database| into_atomics(class_database.values))
+        .collect();
+    let positions_in_pass = pair_size(class, swapped_class);
+    estimate.positions_left -= positions_in_pass;
+
+    let lookup = |lookup_class: StoneClass, index: u64| -> PositionValue {
+        if let Some(position) = classes.iter().position(|&c| c == lookup_class) {
+            PositionValue::from(values[position][index as usize].load(Ordering::Relaxed))
+        } else {
+            dependencies
+                .iter()
+                .find(|dependency| dependency.class == lookup_class)
+                .expect("Successor class is neither part of the pair nor a dependency")
+                .value(index)
+        }
+    };
+
+    loop {
+        pass += 1;
+
+        let next_chunk = AtomicU64::new(0);
+        let positions_done = AtomicU64::new(0);
+        let changed = AtomicBool::new(false);
+        let pass_start = Instant::now();
+
+        let reporter = thread::current();
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..config.threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| {
+                        loop {
+                            let chunk_start = next_chunk.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                            if positions_in_pass <= chunk_start {
+                                break;
+                            }
+                            let chunk_end = (chunk_start + CHUNK_SIZE).min(positions_in_pass);
+
+                            let mut changed_in_chunk = false;
+                            for global_index in chunk_start..chunk_end {
+                                // The pair's positions are numbered one class after the other
+                                let (class_position, index) = if global_index < classes[0].size() {
+                                    (0, global_index)
+                                } else {
+                                    (1, global_index - classes[0].size())
+                                };
+
+                                let value = &values[class_position][index as usize];
+                                if PositionValue::from(value.load(Ordering::Relaxed)) != PositionValue::Draw {
+                                    continue;
+                                }
+
+                                let (movers_stones, opponents_stones) = classes[class_position].unrank(index);
+                                let new_value =
+                                    evaluate_position(classes[class_position], movers_stones, opponents_stones, lookup);
+                                if new_value != PositionValue::Draw {
+                                    value.store(new_value as u8, Ordering::Relaxed);
+                                    changed_in_chunk = true;
+                                }
+                            }
+
+                            if changed_in_chunk {
+                                changed.store(true, Ordering::Relaxed);
+                            }
+                            positions_done.fetch_add(chunk_end - chunk_start, Ordering::Relaxed);
+                        }
+                        // Wakes the reporting thread up, so the pass doesn't end with an unnecessary wait
+                        reporter.unpark();
+                    })
+                })
+                .collect();
+
+            // Meanwhile report the progress of the workers
+            let mut last_report = Instant::now();
+            while !workers.iter().all(|worker| worker.is_finished()) {
+                thread::park_timeout(PROGRESS_POLL_INTERVAL);
+
+                if config.progress_interval <= last_report.elapsed() {
+                    last_report = Instant::now();
+
+                    let done = positions_done.load(Ordering::Relaxed);
+                    let positions_per_second = done as f64 / pass_start.elapsed().as_secs_f64();
+                    on_progress(&SolverProgress {
+                        classes: (class, swapped_class),
+                        pass,
+                        positions_done: done,
+                        positions_in_pass,
+                        positions_per_second,
+                        estimated_remaining: estimate.remaining(positions_in_pass - done, positions_per_second),
+                    });
+                }
+            }
+
+            // A panicking worker leaves its chunk unsolved, so the pass must not be stored
+            for worker in workers {
+                if let Err(panic) = worker.join() {
+                    panic::resume_unwind(panic);
+                }
+            }
+        });
+
+        let changed = changed.load(Ordering::Relaxed);
+        for (&solved_class, class_values) in classes.iter().zip(&values) {
+            database.store_atomic(solved_class, !changed, pass, class_values)?;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    estimate.passes_done += pass;
+    estimate.pairs_done += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_become_atomic_in_place() {
+        let values = vec![0, 1, 2, 1];
+        let address = values.as_ptr() as usize;
+        let atomics = into_atomics(values);
+        assert_eq!(atomics.as_ptr() as usize, address);
+        let loaded: Vec<u8> = atomics.iter().map(|value| value.load(Ordering::Relaxed)).collect();
+        assert_eq!(loaded, [0, 1, 2, 1]);
+    }
+
+    #[test]
+    fn schedule_respects_dependencies() {
+        let pairs = schedule(9);
+        // 7 * 7 classes, of which the 7 symmetric ones form a pair on their own
+        assert_eq!(pairs.len(), (49 - 7) / 2 + 7);
+
+        for (position, (class, swapped_class)) in pairs.iter().enumerate() {
+            assert_eq!(class.swapped(), *swapped_class);
+
+            for solved_class in [class, swapped_class] {
+                let dependency = StoneClass::new(solved_class.opponents_stones - 1, solved_class.movers_stones);
+                if dependency.movers_stones < MIN_STONES {
+                    continue;
+                }
+                assert!(pairs[..position]
+                    .iter()
+                    .any(|&(c, s)| c == dependency || s == dependency));
+            }
+        }
+    }
+
+    #[test]
+    fn finished_classes_are_skipped() {
+        let config = SolverConfig {
+            database_directory: std::env::temp_dir().join(format!("muehle_driver_{}", std::process::id())),
+            threads: 1,
+            max_stones: 3,
+            progress_interval: Duration::ZERO,
+        };
+        let database = Database::open(&config.database_directory).unwrap();
+
+        let mut class_database = ClassDatabase::new(StoneClass::new(3, 3));
+        class_database.complete = true;
+        database.store(&class_database).unwrap();
+
+        let mut reported = false;
+        solve_all(&config, |_| reported = true).unwrap();
+        assert!(!reported);
+
+        std::fs::remove_dir_all(&config.database_directory).unwrap();
+    }
+
+    #[test]
+    fn progress_estimate() {
+        let estimate = Estimate {
+            positions_left: 1000,
+            passes_done: 6,
+            pairs_done: 2,
+        };
+        assert_eq!(estimate.remaining(500, 100.0), Duration::from_secs(35));
+    }
+}
@@ -0,0 +1,402 @@
+//! Solves the set phase by a memoised forward search from the empty play field. The set phase can't repeat positions (a
+//! stone is placed at least every second ply), so a plain depth first search is exact. Its leaves are the positions in
+//! which both players placed all of their stones, their values are taken from the solved move phase database.
+//!
+//! The table of solved positions is held in memory & the leaves load whole classes, so the search is meant for games
+//! with few stones, up to [MAX_STONES_PER_PLAYER]. With 3 stones per player the game is a win for white.
+//!
+//! Nine Men's Morris itself, known to be a draw, is out of scope & not reproduced: its 9v9 class alone holds 6.5 billion
+//! positions of a byte each, and the table of its set phase outgrows the classes by far. Solving it needs the table on
+//! disk & leaves read position by position from the class files, besides the whole move phase solved up to 9 stones.
+//!
+//! The results are keyed by the canonical form of the play field & the stones in hand, always from the view of the
+//! player to move. They are stored as `set_phase.db` in the database directory.
+//!
+//! File layout (little endian): 8 bytes magic `MUEHLESP`, 1 byte format version, 1 byte stones per player, 6 bytes
+//! padding, 8 bytes amount of entries, then the entries sorted by key: 8 bytes key followed by 1 byte value.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use once_cell::sync::Lazy;
+
+use super::{class::StoneClass, database::Database, PositionValue, MIN_STONES};
+use crate::game::{
+    efficient_state::{
+        moves::{for_each_move_successor, for_each_set_successor, FIELD_COUNT},
+        symmetry::Symmetry,
+    },
+    position::Position,
+};
+
+const MAGIC: &[u8; 8] = b"MUEHLESP";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 24;
+
+pub const FILE_NAME: &str = "set_phase.db";
+
+/// The most stones per player the set phase is solved for, larger games don't fit into memory
+pub const MAX_STONES_PER_PLAYER: u8 = 5;
+
+/// For each symmetry the coded index every field is mapped to
+static PERMUTATIONS: Lazy<Vec<[u8; FIELD_COUNT]>> = Lazy::new(|| {
+    Symmetry::all()
+        .map(|symmetry| {
+            let mut permutation = [0u8; FIELD_COUNT];
+            for (field, image) in permutation.iter_mut().enumerate() {
+                *image = symmetry.map_field(field) as u8;
+            }
+            permutation
+        })
+        .collect()
+});
+
+fn permute(stones: u32, permutation: &[u8; FIELD_COUNT]) -> u32 {
+    let mut permuted = 0;
+    let mut remaining = stones;
+    while remaining != 0 {
+        let field = remaining.trailing_zeros() as usize;
+        remaining &= remaining - 1;
+        permuted |= 1 << permutation[field];
+    }
+    permuted
+}
+
+/// Packs a set phase position into a key which is the same for all of its symmetric positions:
+/// 24 bits movers stones, 24 bits opponents stones, 4 bits movers hand & 4 bits opponents hand
+pub fn set_phase_key(movers_stones: u32, opponents_stones: u32, movers_hand: u8, opponents_hand: u8) -> u64 {
+    let stones = PERMUTATIONS
+        .iter()
+        .map(|permutation| {
+            permute(movers_stones, permutation) as u64 | (permute(opponents_stones, permutation) as u64) << 24
+        })
+        .min()
+        .unwrap();
+
+    stones | (movers_hand as u64) << 48 | (opponents_hand as u64) << 52
+}
+
+/// The solved set phase positions of a game with `stones_per_player` stones
+#[derive(Debug, Default)]
+pub struct SetPhaseTable {
+    pub stones_per_player: u8,
+    pub values: HashMap<u64, PositionValue>,
+}
+
+impl SetPhaseTable {
+    pub fn new(stones_per_player: u8) -> Self {
+        SetPhaseTable {
+            stones_per_player,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Returns the value of the position for its player to move, if it was solved already
+    pub fn value_of(&self, position: &Position) -> Option<PositionValue> {
+        let (movers_stones, opponents_stones) = position.board.get_masks_of(position.side_to_move);
+        let key = set_phase_key(
+            movers_stones,
+            opponents_stones,
+            position.stones_in_hand_of(position.side_to_move),
+            position.stones_in_hand_of(!position.side_to_move),
+        );
+        self.values.get(&key).copied()
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        if &header[0..8] != MAGIC {
+            return Err(invalid_data(path, "not a set phase database file"));
+        } else if header[8] != VERSION {
+            return Err(invalid_data(path, "unsupported set phase database version"));
+        }
+        let stones_per_player = header[9];
+        let count = u64::from_le_bytes(header[16..24].try_into().unwrap());
+
+        let mut values = HashMap::with_capacity(count as usize);
+        let mut entry = [0u8; 9];
+        for _ in 0..count {
+            reader.read_exact(&mut entry)?;
+            if 2 < entry[8] {
+                return Err(invalid_data(path, "invalid position value"));
+            }
+            values.insert(
+                u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+                PositionValue::from(entry[8]),
+            );
+        }
+
+        Ok(SetPhaseTable {
+            stones_per_player,
+            values,
+        })
+    }
+
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        let temporary_path = path.with_extension("tmp");
+
+        {
+            let mut writer = BufWriter::new(File::create(&temporary_path)?);
+            writer.write_all(MAGIC)?;
+            writer.write_all(&[VERSION, self.stones_per_player, 0, 0, 0, 0, 0, 0])?;
+            writer.write_all(&(self.values.len() as u64).to_le_bytes())?;
+
+            let mut entries: Vec<_> = self.values.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| **key);
+            for (key, value) in entries {
+                writer.write_all(&key.to_le_bytes())?;
+                writer.write_all(&[*value as u8])?;
+            }
+            writer.into_inner()?.sync_all()?;
+        }
+
+        fs::rename(temporary_path, path)
+    }
+}
+
+/// Searches the set phase, asking `leaf_value` for the value of the positions after it from the view of the player to
+/// move, given as (movers stones, opponents stones).
+pub struct SetPhaseSolver<L> {
+    pub table: SetPhaseTable,
+    leaf_value: L,
+    pub leaves_visited: u64,
+}
+
+impl<L: FnMut(u32, u32) -> io::Result<PositionValue>> SetPhaseSolver<L> {
+    pub fn new(table: SetPhaseTable, leaf_value: L) -> Self {
+        SetPhaseSolver {
+            table,
+            leaf_value,
+            leaves_visited: 0,
+        }
+    }
+
+    /// Solves the empty play field, whose value is the one of the whole game for white
+    pub fn solve_game(&mut self) -> io::Result<PositionValue> {
+        // Hidden from the optimizer: with constant hands LLVM's interprocedural constant propagation narrows the hands of
+        // `solve` to the ones of the first plies & the search never reaches its leaves. examples/ipsccp_miscompile.rs
+        // reproduces the miscompile without this crate.
+        let stones = std::hint::black_box(self.table.stones_per_player);
+        self.solve(0, 0, stones, stones)
+    }
+
+    pub fn solve_position(&mut self, position: &Position) -> io::Result<PositionValue> {
+        let (movers_stones, opponents_stones) = position.board.get_masks_of(position.side_to_move);
+        self.solve(
+            movers_stones,
+            opponents_stones,
+            position.stones_in_hand_of(position.side_to_move),
+            position.stones_in_hand_of(!position.side_to_move),
+        )
+    }
+
+    /// Returns the value of the position for the player owning `movers_stones`, who is on turn
+    pub fn solve(
+        &mut self,
+        movers_stones: u32,
+        opponents_stones: u32,
+        movers_hand: u8,
+        opponents_hand: u8,
+    ) -> io::Result<PositionValue> {
+        if (movers_stones.count_ones() as u8 + movers_hand) < MIN_STONES {
+            return Ok(PositionValue::Loss);
+        } else if movers_hand == 0 && opponents_hand == 0 {
+            self.leaves_visited += 1;
+            return (self.leaf_value)(movers_stones, opponents_stones);
+        }
+
+        let key = set_phase_key(movers_stones, opponents_stones, movers_hand, opponents_hand);
+        if let Some(&value) = self.table.values.get(&key) {
+            return Ok(value);
+        }
+
+        let mut successors = Vec::new();
+        let mut collect = |new_movers_stones, new_opponents_stones| {
+            successors.push((new_movers_stones, new_opponents_stones));
+        };
+        if movers_hand != 0 {
+            for_each_set_successor(movers_stones, opponents_stones, &mut collect);
+        } else {
+            // The opponent still places stones, while the mover already slides
+            for_each_move_successor(movers_stones, opponents_stones, &mut collect);
+        }
+
+        // Taking a stone first finds most wins the fastest
+        successors.sort_unstable_by_key(|(_, new_opponents_stones)| new_opponents_stones.count_ones());
+
+        let mut value = PositionValue::Loss;
+        for (new_movers_stones, new_opponents_stones) in successors {
+            // The successor is viewed from the opponent, who's on turn next
+            match self.solve(
+                new_opponents_stones,
+                new_movers_stones,
+                opponents_hand,
+                movers_hand.saturating_sub(1),
+            )? {
+                PositionValue::Loss => {
+                    value = PositionValue::Win;
+                    break;
+                }
+                PositionValue::Draw => value = PositionValue::Draw,
+                PositionValue::Win => {}
+            }
+        }
+
+        self.table.values.insert(key, value);
+        Ok(value)
+    }
+}
+
+/// Returns the leaf lookup into the move phase database, which loads each class when it's first needed.
+/// Classes which aren't solved completely are reported as error.
+pub fn database_leaves(database: Database) -> impl FnMut(u32, u32) -> io::Result<PositionValue> {
+    let mut classes = HashMap::new();
+
+    move |movers_stones, opponents_stones| {
+        let class = StoneClass::new(movers_stones.count_ones() as u8, opponents_stones.count_ones() as u8);
+        let class_database = match classes.entry(class) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(database.load_complete(class)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "class {}v{} isn't solved, solve the move phase first",
+                        class.movers_stones, class.opponents_stones
+                    ),
+                )
+            })?),
+        };
+
+        Ok(class_database.value(class.rank(movers_stones, opponents_stones)))
+    }
+}
+
+/// Solves the set phase of a game with `stones_per_player` stones using the move phase database in `directory` & stores
+/// the results next to it. Already stored results are reused. Returns the value of the game for white.
+pub fn solve_set_phase(directory: &Path, stones_per_player: u8) -> io::Result<PositionValue> {
+    if !(MIN_STONES..=MAX_STONES_PER_PLAYER).contains(&stones_per_player) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the set phase is solved for {MIN_STONES} to {MAX_STONES_PER_PLAYER} stones per player"),
+        ));
+    }
+    let database = Database::open(directory)?;
+    let path = directory.join(FILE_NAME);
+
+    let table = match SetPhaseTable::load(&path) {
+        Ok(table) if table.stones_per_player == stones_per_player => table,
+        Ok(_) => SetPhaseTable::new(stones_per_player),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => SetPhaseTable::new(stones_per_player),
+        Err(error) => return Err(error),
+    };
+
+    let mut solver = SetPhaseSolver::new(table, database_leaves(database));
+    let value = solver.solve_game()?;
+    solver.table.store(&path)?;
+    Ok(value)
+}
+
+fn invalid_data(path: &Path, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("{}: {message}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{efficient_state::EfficientPlayField, PlayerColor};
+
+    #[test]
+    fn symmetric_positions_share_keys() {
+        let (movers_stones, opponents_stones) = EfficientPlayField::from_coded("WEEEEEEEBEEEEEEEEEEEEEEE").get_masks();
+        let (rotated_movers, rotated_opponents) =
+            EfficientPlayField::from_coded("EEWEEEEEEEBEEEEEEEEEEEEE").get_masks();
+
+        assert_eq!(
+            set_phase_key(movers_stones, opponents_stones, 8, 8),
+            set_phase_key(rotated_movers, rotated_opponents, 8, 8)
+        );
+        assert_ne!(
+            set_phase_key(movers_stones, opponents_stones, 8, 8),
+            set_phase_key(movers_stones, opponents_stones, 8, 7)
+        );
+    }
+
+    #[test]
+    fn closing_a_mill_against_three_stones_wins() {
+        // Black has placed all of its 3 stones, white places the last stone into a mill
+        let position = Position {
+            board: EfficientPlayField::from_coded("WWEEEEEEBBBEEEEEEEEEEEEE"),
+            side_to_move: PlayerColor::White,
+            stones_in_hand: (1, 0),
+        };
+        let mut solver = SetPhaseSolver::new(SetPhaseTable::new(3), |_, _| Ok(PositionValue::Draw));
+        assert_eq!(solver.solve_position(&position).unwrap(), PositionValue::Win);
+        assert_eq!(solver.leaves_visited, 0);
+    }
+
+    #[test]
+    fn game_value_follows_the_leaves() {
+        // Without draws at the leaves, the set phase can't end in a draw either
+        let mut solver = SetPhaseSolver::new(SetPhaseTable::new(3), |_, _| Ok(PositionValue::Win));
+        let value = solver.solve_game().unwrap();
+        assert_ne!(value, PositionValue::Draw);
+        assert!(0 < solver.leaves_visited);
+
+        let mut solver = SetPhaseSolver::new(SetPhaseTable::new(3), |_, _| Ok(PositionValue::Draw));
+        assert_eq!(solver.solve_game().unwrap(), PositionValue::Draw);
+    }
+
+    #[test]
+    fn table_round_trip() {
+        let path = std::env::temp_dir().join(format!("muehle_set_phase_{}.db", std::process::id()));
+
+        let mut solver = SetPhaseSolver::new(SetPhaseTable::new(3), |_, _| Ok(PositionValue::Draw));
+        solver.solve_game().unwrap();
+        solver.table.store(&path).unwrap();
+
+        let loaded = SetPhaseTable::load(&path).unwrap();
+        assert_eq!(loaded.stones_per_player, 3);
+        assert_eq!(loaded.values, solver.table.values);
+        assert_eq!(loaded.value_of(&Position::default()), None);
+        assert_eq!(
+            loaded.value_of(&Position {
+                stones_in_hand: (3, 3),
+                ..Default::default()
+            }),
+            Some(PositionValue::Draw)
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[ignore = "solves the move phase of 3 stones per player first, which takes minutes without --release"]
+    fn three_stones_are_a_win_for_white() {
+        use crate::solver::driver::{solve_all, SolverConfig};
+
+        let config = SolverConfig {
+            database_directory: std::env::temp_dir().join(format!("muehle_three_stones_{}", std::process::id())),
+            max_stones: 3,
+            ..SolverConfig::default()
+        };
+        solve_all(&config, |_| {}).unwrap();
+
+        assert_eq!(
+            solve_set_phase(&config.database_directory, 3).unwrap(),
+            PositionValue::Win
+        );
+        assert_eq!(
+            solve_set_phase(&config.database_directory, 9).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        fs::remove_dir_all(&config.database_directory).unwrap();
+    }
+}
@@ -0,0 +1,286 @@
+//! Re-checks a solved database: every stored value must match the value derived from the stored values of the
+//! successors, which is the fixpoint the solver stops at. A win needs a successor which is lost for the opponent, a loss
+//! needs only won successors, and a draw neither.
+//!
+//! This detects corrupted files & bugs which break the consistency of the values. A consistent database can't prove that
+//! a win in a cycle of positions isn't actually a draw though, as the solver doesn't store the distance to the win.
+
+use std::{
+    fmt::Display,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use super::{
+    class::StoneClass,
+    database::{ClassDatabase, Database},
+    driver::{schedule, SolverConfig},
+    evaluate_position, PositionValue, MIN_STONES,
+};
+use crate::game::efficient_state::EfficientPlayField;
+
+const CHUNK_SIZE: u64 = 4096;
+
+/// A position whose stored value doesn't match the values of its successors
+#[derive(Debug, Clone)]
+pub struct Inconsistency {
+    pub class: StoneClass,
+    pub index: u64,
+    /// The position as 24 character code, in which the player to move plays white
+    pub coded: String,
+    /// `None` if the stored byte isn't a valid [PositionValue]
+    pub stored: Option<PositionValue>,
+    pub derived: PositionValue,
+}
+
+impl Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (class {}v{}, index {}): stored {}, but the successors imply {:?}",
+            self.coded,
+            self.class.movers_stones,
+            self.class.opponents_stones,
+            self.index,
+            match self.stored {
+                Some(value) => format!("{value:?}"),
+                None => "an invalid value".to_string(),
+            },
+            self.derived
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    pub positions_checked: u64,
+    pub inconsistent_positions: u64,
+    /// The first inconsistencies in the order of the classes & their indices
+    pub first_inconsistencies: Vec<Inconsistency>,
+    /// Classes which couldn't be checked, e.g. because they are missing or their position count is wrong
+    pub class_errors: Vec<(StoneClass, String)>,
+}
+
+impl VerificationReport {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistent_positions == 0 && self.class_errors.is_empty()
+    }
+}
+
+/// Checks all classes of the database up to `config.max_stones`, reporting at most `max_reported` inconsistencies
+pub fn verify_database(config: &SolverConfig, max_reported: usize) -> io::Result<VerificationReport> {
+    let database = Database::open(&config.database_directory)?;
+    let mut report = VerificationReport::default();
+
+    for (class, swapped_class) in schedule(config.max_stones) {
+        verify_class(&database, config, class, max_reported, &mut report);
+        if class != swapped_class {
+            verify_class(&database, config, swapped_class, max_reported, &mut report);
+        }
+    }
+    Ok(report)
+}
+
+/// Loads a class which must be solved completely & have as many positions as its enumeration
+fn load_checked(database: &Database, class: StoneClass) -> Result<ClassDatabase, String> {
+    match database.load(class) {
+        Ok(Some(class_database)) if !class_database.complete => Err(format!(
+            "class {}v{} is not solved completely",
+            class.movers_stones, class.opponents_stones
+        )),
+        Ok(Some(class_database)) => Ok(class_database),
+        Ok(None) => Err(format!(
+            "class {}v{} is missing",
+            class.movers_stones, class.opponents_stones
+        )),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+fn verify_class(
+    database: &Database,
+    config: &SolverConfig,
+    class: StoneClass,
+    max_reported: usize,
+    report: &mut VerificationReport,
+) {
+    let mut class_databases = Vec::new();
+    let mut successor_classes = vec![class, class.swapped()];
+    if MIN_STONES < class.opponents_stones {
+        successor_classes.push(StoneClass::new(class.opponents_stones - 1, class.movers_stones));
+    }
+    successor_classes.dedup();
+
+    for successor_class in successor_classes {
+        match load_checked(database, successor_class) {
+            Ok(class_database) => class_databases.push(class_database),
+            Err(message) => {
+                let message = if successor_class == class {
+                    message
+                } else {
+                    format!("successor {message}")
+                };
+                report.class_errors.push((class, message));
+                return;
+            }
+        }
+    }
+    let checked = &class_databases[0];
+
+    let lookup = |lookup_class: StoneClass, index: u64| -> PositionValue {
+        let class_database = class_databases
+            .iter()
+            .find(|class_database| class_database.class == lookup_class)
+            .expect("Successor class wasn't loaded");
+        // Invalid values are reported on their own position, for the predecessors they count as undecided
+        stored_value(class_database.values[index as usize]).unwrap_or(PositionValue::Draw)
+    };
+
+    let next_chunk = AtomicU64::new(0);
+    let inconsistent_positions = AtomicU64::new(0);
+    let first_inconsistencies = Mutex::new(Vec::<Inconsistency>::new());
+    let enumerator_errors = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..config.threads.max(1) {
+            scope.spawn(|| loop {
+                let chunk_start = next_chunk.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                if class.size() <= chunk_start {
+                    break;
+                }
+                let chunk_end = (chunk_start + CHUNK_SIZE).min(class.size());
+
+                let mut found = Vec::new();
+                for index in chunk_start..chunk_end {
+                    let (movers_stones, opponents_stones) = class.unrank(index);
+                    if class.rank(movers_stones, opponents_stones) != index {
+                        enumerator_errors.lock().unwrap().push(index);
+                        continue;
+                    }
+
+                    let stored = checked.values[index as usize];
+                    if let Some(inconsistency) =
+                        check_position(class, index, (movers_stones, opponents_stones), stored, lookup)
+                    {
+                        inconsistent_positions.fetch_add(1, Ordering::Relaxed);
+                        if found.len() < max_reported {
+                            found.push(inconsistency);
+                        }
+                    }
+                }
+
+                if !found.is_empty() {
+                    let mut first_inconsistencies = first_inconsistencies.lock().unwrap();
+                    first_inconsistencies.append(&mut found);
+                    first_inconsistencies.sort_by_key(|inconsistency| inconsistency.index);
+                    first_inconsistencies.truncate(max_reported);
+                }
+            });
+        }
+    });
+
+    if let Some(index) = enumerator_errors.into_inner().unwrap().into_iter().min() {
+        report.class_errors.push((
+            class,
+            format!(
+                "the enumeration of class {}v{} isn't a bijection, first at index {index}",
+                class.movers_stones, class.opponents_stones
+            ),
+        ));
+    }
+
+    report.positions_checked += class.size();
+    report.inconsistent_positions += inconsistent_positions.into_inner();
+
+    let still_reportable = max_reported.saturating_sub(report.first_inconsistencies.len());
+    let mut first_inconsistencies = first_inconsistencies.into_inner().unwrap();
+    first_inconsistencies.truncate(still_reportable);
+    report.first_inconsistencies.append(&mut first_inconsistencies);
+}
+
+/// Compares the stored value of a position with the value derived from its successors
+fn check_position(
+    class: StoneClass,
+    index: u64,
+    (movers_stones, opponents_stones): (u32, u32),
+    stored: u8,
+    lookup: impl FnMut(StoneClass, u64) -> PositionValue,
+) -> Option<Inconsistency> {
+    let stored = stored_value(stored);
+    let derived = evaluate_position(class, movers_stones, opponents_stones, lookup);
+
+    if stored == Some(derived) {
+        None
+    } else {
+        Some(Inconsistency {
+            class,
+            index,
+            coded: EfficientPlayField::from_masks(movers_stones, opponents_stones).to_string_representation(),
+            stored,
+            derived,
+        })
+    }
+}
+
+fn stored_value(value: u8) -> Option<PositionValue> {
+    match value {
+        0..=2 => Some(PositionValue::from(value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn wrong_values_are_reported_as_code() {
+        let class = StoneClass::new(3, 3);
+        let masks = EfficientPlayField::from_coded("WEEEEEEWEEEEEEEWBBBEEEEE").get_masks();
+        let index = class.rank(masks.0, masks.1);
+
+        // Closing the mill wins, regardless of the other successors
+        assert!(check_position(class, index, masks, PositionValue::Win as u8, |_, _| {
+            PositionValue::Draw
+        })
+        .is_none());
+
+        let inconsistency = check_position(class, index, masks, PositionValue::Draw as u8, |_, _| {
+            PositionValue::Draw
+        })
+        .unwrap();
+        assert_eq!(inconsistency.coded, "WEEEEEEWEEEEEEEWBBBEEEEE");
+        assert_eq!(inconsistency.stored, Some(PositionValue::Draw));
+        assert_eq!(inconsistency.derived, PositionValue::Win);
+
+        let inconsistency = check_position(class, index, masks, 7, |_, _| PositionValue::Draw).unwrap();
+        assert_eq!(inconsistency.stored, None);
+    }
+
+    #[test]
+    fn missing_and_incomplete_classes() {
+        let config = SolverConfig {
+            database_directory: std::env::temp_dir().join(format!("muehle_verify_{}", std::process::id())),
+            threads: 1,
+            max_stones: 4,
+            progress_interval: Duration::ZERO,
+        };
+        let database = Database::open(&config.database_directory).unwrap();
+        database.store(&ClassDatabase::new(StoneClass::new(3, 3))).unwrap();
+
+        let report = verify_database(&config, 10).unwrap();
+        assert!(!report.is_consistent());
+        assert_eq!(report.positions_checked, 0);
+        assert_eq!(report.class_errors.len(), 4);
+        assert!(report.class_errors[0].1.contains("not solved completely"));
+        assert!(report.class_errors[1].1.contains("missing"));
+
+        fs::remove_dir_all(&config.database_directory).unwrap();
+    }
+}